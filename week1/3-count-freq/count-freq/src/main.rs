@@ -1,7 +1,19 @@
 /*
 This example code counts the frequency of each number in the vector.
  */
+use clap::Parser;
 use std::collections::HashMap;
+use std::path::PathBuf;
+use term_charts::{bar_chart, svg};
+
+/// Counts the words of ./data/poe_holmes.txt
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+	/// Also write the chart of the top words as SVG to this file
+	#[arg(long)]
+	svg: Option<PathBuf>,
+}
 
 fn pretty_print(freq: &Vec<(String, i32)>) {
 	println!("| Word      | Frequency |");
@@ -105,6 +117,7 @@ fn freq_to_file(sorted_freq: &Vec<(String, i32)>) {
 
 
 fn main() {
+	let args = Args::parse();
 	//let text = get_user_input();
 
 	// A bit more: Read a text file and count the frequency of each word.
@@ -119,6 +132,16 @@ fn main() {
 	println!("Your input: {}", text);
 	pretty_print(&sorted_freq);
 
+	// The top 15 words as a chart, in the terminal and as SVG with --svg
+	let top: Vec<(String, i32)> = sorted_freq.iter().take(15).cloned().collect();
+	print!("{}", bar_chart(&top, 40));
+	if let Some(path) = args.svg {
+		if let Err(e) = std::fs::write(&path, svg::bar_chart(&top, "Word frequencies")) {
+			eprintln!("Unable to write {}: {}", path.display(), e);
+			std::process::exit(1);
+		}
+	}
+
 	// Save the sorted frequencies to a file
 
 	freq_to_file(&sorted_freq);
@@ -1,10 +1,22 @@
 mod authors;
-mod graph_utils;
 
 use authors::Author;
-use graph_utils::add_edge;
-use petgraph::graph::{NodeIndex, UnGraph};
+use clap::Parser;
+use graph_utils::render::{Centrality, Colouring, Renderer};
+use graph_utils::UnGraphBuilder;
+use petgraph::graph::NodeIndex;
 use petgraph::{Direction, Graph, Undirected};
+use std::collections::HashMap;
+use std::process;
+
+/// Closeness of a few classic authors
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Write the author graph to this file (.svg or .dot)
+    #[arg(long)]
+    render: Option<String>,
+}
 
 fn get_authors() -> Vec<Author> {
     vec![
@@ -17,17 +29,25 @@ fn get_authors() -> Vec<Author> {
     ]
 }
 
-fn add_edges(mut graph: &mut Graph<&Author, f32, Undirected>, author_nodes: &Vec<NodeIndex>) {
-	add_edge(&mut graph, &author_nodes, 0, 1, 0.3); // Boccaccio -> Chaucer
-	add_edge(&mut graph, &author_nodes, 0, 2, 3.0); // Boccaccio -> Dante
-	add_edge(&mut graph, &author_nodes, 1, 3, 0.0); // Chaucer -> Homer
-	add_edge(&mut graph, &author_nodes, 3, 0, 2.0); // Homer -> Boccaccio
-	add_edge(&mut graph, &author_nodes, 3, 2, 4.0); // Homer -> Dante
-	add_edge(&mut graph, &author_nodes, 3, 4, 5.0); // Homer -> Virgil
-	add_edge(&mut graph, &author_nodes, 0, 4, 0.0); // Boccaccio -> Virgil
-	add_edge(&mut graph, &author_nodes, 2, 4, 10.0); // Dante -> Virgil
-	add_edge(&mut graph, &author_nodes, 4, 5, 1.4); // Virgil -> Shakespeare
-	add_edge(&mut graph, &author_nodes, 1, 5, 2.4); // Chaucer -> Shakespeare
+fn add_edges(builder: &mut UnGraphBuilder<&str, &Author, f32>) {
+	let edges = [
+		("Boccaccio", "Chaucer", 0.3),
+		("Boccaccio", "Dante", 3.0),
+		("Chaucer", "Homer", 0.0),
+		("Homer", "Boccaccio", 2.0),
+		("Homer", "Dante", 4.0),
+		("Homer", "Virgil", 5.0),
+		("Boccaccio", "Virgil", 0.0),
+		("Dante", "Virgil", 10.0),
+		("Virgil", "Shakespeare", 1.4),
+		("Chaucer", "Shakespeare", 2.4),
+	];
+
+	for (a, b, weight) in edges {
+		builder
+			.add_edge(a, b, weight)
+			.expect("Every author in the edge list is a node");
+	}
 }
 
 /// Explain the data
@@ -37,9 +57,9 @@ fn add_edges(mut graph: &mut Graph<&Author, f32, Undirected>, author_nodes: &Vec
 ///
 /// # Arguments
 ///
-/// * `authors` - A reference to a vector of `Author` references. These authors are to be added as nodes to the graph.
-/// * `graph` - A mutable reference to a `Graph` object where the authors will be added as nodes. The graph is undirected and the edges, if any, will have a weight of type `f32`.
-/// * `author_nodes` - A vector of `NodeIndex` containing the indices of the newly added author nodes in the graph.
+/// * `authors` - The authors, in the order they should be reported.
+/// * `graph` - The undirected graph of authors, the edges have a weight of type `f32`.
+/// * `author_nodes` - The lookup from author name to its index in the graph.
 ///
 /// # Further Explanation
 ///
@@ -49,9 +69,10 @@ fn add_edges(mut graph: &mut Graph<&Author, f32, Undirected>, author_nodes: &Vec
 /// It is calculated as the reciprocal of the sum of the shortest path distances from the node to all other
 /// nodes in the graph. A node with high closeness centrality is close to many other nodes in the graph.
 ///
-fn explain_data(authors: &Vec<Author>, graph: &mut Graph<&Author, f32, Undirected>, author_nodes: Vec<NodeIndex>) {
-	for (i, &node) in author_nodes.iter().enumerate() {
-		let name = &authors[i].name;
+fn explain_data(authors: &[Author], graph: &Graph<&Author, f32, Undirected>, author_nodes: &HashMap<&str, NodeIndex>) {
+	for author in authors {
+		let name = &author.name;
+		let node = author_nodes[name.as_str()];
 		let degree = graph.edges_directed(node, Direction::Outgoing).count() as f32;
 		let closeness = 1.0 / degree;
 		println!("The closeness centrality of {} is {:.2}", name, closeness);
@@ -73,13 +94,31 @@ fn explain_data(authors: &Vec<Author>, graph: &mut Graph<&Author, f32, Undirecte
 
 
 fn main() {
+    let args = Args::parse();
     let authors = get_authors();
-    let mut graph = UnGraph::new_undirected();
+    let mut builder = UnGraphBuilder::new();
 
-	let author_nodes = graph_utils::vec_to_graph_nodes(&authors, &mut graph);
+	for author in &authors {
+		builder.add_node(author.name.as_str(), author);
+	}
 
-	add_edges(&mut graph, &author_nodes);
+	add_edges(&mut builder);
 
-	explain_data(&authors, &mut graph, author_nodes);
+	let (graph, author_nodes) = builder.build();
+
+	explain_data(&authors, &graph, &author_nodes);
+
+	if let Some(path) = args.render {
+		let renderer = Renderer::with_node_label(&graph, |author| author.name.clone())
+			.edge_label(|weight| weight.to_string())
+			.edge_weight(|weight| *weight as f64)
+			.centrality(Centrality::Closeness)
+			.colouring(Colouring::Communities);
+		if let Err(e) = renderer.write(&path) {
+			eprintln!("{}", e);
+			process::exit(1);
+		}
+		println!("Graph rendered to {}", path);
+	}
 }
 
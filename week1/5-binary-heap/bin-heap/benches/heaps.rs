@@ -0,0 +1,98 @@
+use bin_heap::dijkstra::{dijkstra, dijkstra_lazy};
+use bin_heap::heap::{AddressableHeap, IndexedBinaryHeap, PairingHeap};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+fn random_priorities(n: usize) -> Vec<u64> {
+	let mut rng = StdRng::seed_from_u64(42);
+	(0..n).map(|_| rng.gen()).collect()
+}
+
+/// A random graph with `nodes * degree` edges
+fn random_graph(nodes: usize, degree: usize) -> Vec<Vec<(usize, u64)>> {
+	let mut rng = StdRng::seed_from_u64(7);
+	(0..nodes)
+		.map(|_| {
+			(0..degree)
+				.map(|_| (rng.gen_range(0..nodes), rng.gen_range(1..1000)))
+				.collect()
+		})
+		.collect()
+}
+
+fn push_pop<H: AddressableHeap<u64, usize> + Default>(priorities: &[u64]) -> u64 {
+	let mut heap = H::default();
+	for (i, &priority) in priorities.iter().enumerate() {
+		heap.push(priority, i);
+	}
+	let mut sum = 0u64;
+	while let Some((priority, _)) = heap.pop() {
+		sum = sum.wrapping_add(priority);
+	}
+	sum
+}
+
+fn bench_push_pop(c: &mut Criterion) {
+	let mut group = c.benchmark_group("push_pop");
+	for n in [1_000, 100_000] {
+		let priorities = random_priorities(n);
+		group.bench_with_input(
+			BenchmarkId::new("BinaryHeap", n),
+			&priorities,
+			|b, priorities| {
+				b.iter(|| {
+					let mut heap: BinaryHeap<_> = priorities
+						.iter()
+						.enumerate()
+						.map(|(i, &p)| Reverse((p, i)))
+						.collect();
+					let mut sum = 0u64;
+					while let Some(Reverse((priority, _))) = heap.pop() {
+						sum = sum.wrapping_add(priority);
+					}
+					black_box(sum)
+				})
+			},
+		);
+		group.bench_with_input(
+			BenchmarkId::new("IndexedBinaryHeap", n),
+			&priorities,
+			|b, priorities| b.iter(|| black_box(push_pop::<IndexedBinaryHeap<_, _>>(priorities))),
+		);
+		group.bench_with_input(
+			BenchmarkId::new("PairingHeap", n),
+			&priorities,
+			|b, priorities| b.iter(|| black_box(push_pop::<PairingHeap<_, _>>(priorities))),
+		);
+	}
+	group.finish();
+}
+
+fn bench_dijkstra(c: &mut Criterion) {
+	let mut group = c.benchmark_group("dijkstra");
+	for nodes in [1_000, 50_000] {
+		let graph = random_graph(nodes, 8);
+		group.bench_with_input(
+			BenchmarkId::new("BinaryHeap (lazy)", nodes),
+			&graph,
+			|b, graph| b.iter(|| black_box(dijkstra_lazy(graph, 0))),
+		);
+		group.bench_with_input(
+			BenchmarkId::new("IndexedBinaryHeap", nodes),
+			&graph,
+			|b, graph| b.iter(|| black_box(dijkstra::<IndexedBinaryHeap<_, _>>(graph, 0))),
+		);
+		group.bench_with_input(
+			BenchmarkId::new("PairingHeap", nodes),
+			&graph,
+			|b, graph| b.iter(|| black_box(dijkstra::<PairingHeap<_, _>>(graph, 0))),
+		);
+	}
+	group.finish();
+}
+
+criterion_group!(benches, bench_push_pop, bench_dijkstra);
+criterion_main!(benches);
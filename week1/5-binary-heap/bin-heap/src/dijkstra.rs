@@ -0,0 +1,172 @@
+//! Shortest paths with an [AddressableHeap]
+//!
+//! Dijkstra's algorithm keeps every node that has been reached but not settled in a
+//! priority queue, by tentative distance. When a shorter path to a queued node is found,
+//! its distance is lowered in place with `decrease_key`, so that the queue never holds more
+//! than one entry per node.
+//! [dijkstra_lazy] is the usual alternative with `std::collections::BinaryHeap`, which
+//! pushes a new entry instead and skips the outdated ones when they are popped.
+
+use crate::heap::{AddressableHeap, Handle};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Adjacency list: `graph[node]` holds `(neighbour, weight)` pairs
+pub type Graph = [Vec<(usize, u64)>];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortestPaths {
+	pub source: usize,
+	/// Distance from the source, `None` for unreachable nodes
+	pub distances: Vec<Option<u64>>,
+	/// Node before each one on a shortest path
+	pub predecessors: Vec<Option<usize>>,
+}
+
+impl ShortestPaths {
+	/// The nodes of a shortest path from the source to `target`, both included
+	pub fn path_to(&self, target: usize) -> Option<Vec<usize>> {
+		self.distances.get(target).copied().flatten()?;
+		let mut path = vec![target];
+		let mut node = target;
+		while let Some(previous) = self.predecessors[node] {
+			path.push(previous);
+			node = previous;
+		}
+		path.reverse();
+		Some(path)
+	}
+}
+
+/// Shortest paths from `source`, with any addressable heap as queue
+///
+/// # Example
+///
+/// ```rust
+/// use bin_heap::dijkstra::dijkstra;
+/// use bin_heap::heap::PairingHeap;
+///
+/// let graph = vec![vec![(1, 4), (2, 1)], vec![(3, 1)], vec![(1, 2)], vec![]];
+/// let paths = dijkstra::<PairingHeap<_, _>>(&graph, 0);
+///
+/// assert_eq!(paths.distances, vec![Some(0), Some(3), Some(1), Some(4)]);
+/// assert_eq!(paths.path_to(3), Some(vec![0, 2, 1, 3]));
+/// ```
+pub fn dijkstra<H: AddressableHeap<u64, usize> + Default>(
+	graph: &Graph,
+	source: usize,
+) -> ShortestPaths {
+	let mut distances = vec![None; graph.len()];
+	let mut predecessors = vec![None; graph.len()];
+	// handles of the queued nodes
+	let mut queued: Vec<Option<Handle>> = vec![None; graph.len()];
+	let mut queue = H::default();
+
+	distances[source] = Some(0);
+	queued[source] = Some(queue.push(0, source));
+	while let Some((distance, node)) = queue.pop() {
+		queued[node] = None;
+		for &(next, weight) in &graph[node] {
+			let candidate = distance.saturating_add(weight);
+			if distances[next].is_some_and(|known| known <= candidate) {
+				continue;
+			}
+			distances[next] = Some(candidate);
+			predecessors[next] = Some(node);
+			match queued[next] {
+				Some(handle) => queue
+					.decrease_key(handle, candidate)
+					.expect("Queued nodes have a valid handle and a larger distance"),
+				None => queued[next] = Some(queue.push(candidate, next)),
+			}
+		}
+	}
+
+	ShortestPaths {
+		source,
+		distances,
+		predecessors,
+	}
+}
+
+/// Shortest paths from `source` with `std::collections::BinaryHeap`, which has no
+/// `decrease_key`: outdated entries stay in the queue and are skipped
+pub fn dijkstra_lazy(graph: &Graph, source: usize) -> ShortestPaths {
+	let mut distances = vec![None; graph.len()];
+	let mut predecessors = vec![None; graph.len()];
+	let mut queue = BinaryHeap::new();
+
+	distances[source] = Some(0);
+	queue.push(Reverse((0u64, source)));
+	while let Some(Reverse((distance, node))) = queue.pop() {
+		if distances[node].is_some_and(|known| known < distance) {
+			continue;
+		}
+		for &(next, weight) in &graph[node] {
+			let candidate = distance.saturating_add(weight);
+			if distances[next].is_some_and(|known| known <= candidate) {
+				continue;
+			}
+			distances[next] = Some(candidate);
+			predecessors[next] = Some(node);
+			queue.push(Reverse((candidate, next)));
+		}
+	}
+
+	ShortestPaths {
+		source,
+		distances,
+		predecessors,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::heap::{IndexedBinaryHeap, PairingHeap};
+	use proptest::prelude::*;
+
+	/// Random graphs with up to 30 nodes and 150 edges
+	fn graph() -> impl Strategy<Value = Vec<Vec<(usize, u64)>>> {
+		(1..30usize).prop_flat_map(|nodes| {
+			prop::collection::vec((0..nodes, 0..nodes, 0..100u64), 0..150).prop_map(move |edges| {
+				let mut graph = vec![Vec::new(); nodes];
+				for (from, to, weight) in edges {
+					graph[from].push((to, weight));
+				}
+				graph
+			})
+		})
+	}
+
+	proptest! {
+		#[test]
+		fn the_heaps_find_the_same_distances(graph in graph()) {
+			let expected = dijkstra_lazy(&graph, 0);
+			let indexed = dijkstra::<IndexedBinaryHeap<_, _>>(&graph, 0);
+			let pairing = dijkstra::<PairingHeap<_, _>>(&graph, 0);
+			prop_assert_eq!(&indexed.distances, &expected.distances);
+			prop_assert_eq!(&pairing.distances, &expected.distances);
+
+			// every path has the length of its distance
+			for target in 0..graph.len() {
+				if let Some(path) = pairing.path_to(target) {
+					let length: u64 = path
+						.windows(2)
+						.map(|step| graph[step[0]].iter().filter(|(to, _)| *to == step[1]).map(|(_, w)| *w).min().unwrap())
+						.sum();
+					prop_assert_eq!(Some(length), pairing.distances[target]);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn unreachable_nodes_have_no_path() {
+		let graph = vec![vec![(1, 2)], vec![], vec![(0, 1)]];
+		let paths = dijkstra::<IndexedBinaryHeap<_, _>>(&graph, 0);
+		assert_eq!(paths.distances, vec![Some(0), Some(2), None]);
+		assert_eq!(paths.path_to(2), None);
+		assert_eq!(paths.path_to(0), Some(vec![0]));
+	}
+}
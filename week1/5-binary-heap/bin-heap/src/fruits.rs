@@ -1,25 +1,36 @@
 use std::cmp::{Ord, Ordering};
 use std::fmt::Display;
 
-#[derive(Debug, Eq, PartialEq)]
-pub enum Fruit {
-	Coconut, // Which I love and will get higher priority
-	Other(String),
+use crate::preferences::Preferences;
+
+/// A fruit and how much it is wanted: the higher the priority, the sooner it leaves the heap
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Fruit {
+	pub name: String,
+	pub priority: i32,
+}
+
+impl Fruit {
+	pub fn new(name: impl Into<String>, priority: i32) -> Self {
+		Self {
+			name: name.into(),
+			priority,
+		}
+	}
 }
 
 /// Implementing Ord for Fruit
 ///
-/// This is done by comparing the two fruits and returning the ordering
-/// In this fictional example, I am giving higher priority to Coconut
-/// and lower priority to Other
+/// Fruits are compared by priority first. Ties are broken by name, in alphabetical order
+/// (`Apple` is greater than `Banana`, so it comes out of a max-heap first).
+///
+/// Comparing every field makes the order total and consistent with `Eq`:
+/// `a.cmp(&b) == Ordering::Equal` exactly when `a == b`, which the heap relies on.
 impl Ord for Fruit {
 	fn cmp(&self, other: &Self) -> Ordering {
-		match (self, other) {
-			(Fruit::Coconut, Fruit::Coconut) => Ordering::Equal,
-			(Fruit::Coconut, Fruit::Other(_)) => Ordering::Greater,
-			(Fruit::Other(_), Fruit::Coconut) => Ordering::Less,
-			_ => Ordering::Equal,
-		}
+		self.priority
+			.cmp(&other.priority)
+			.then_with(|| other.name.cmp(&self.name))
 	}
 }
 
@@ -34,27 +45,56 @@ impl PartialOrd for Fruit {
 
 impl Display for Fruit {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		match self {
-			Fruit::Coconut => write!(f, "Coconuts 🥥"),
-			Fruit::Other(name) => write!(f, "{}", name),
+		match self.name.as_str() {
+			"Coconut" => write!(f, "Coconuts 🥥 ({})", self.priority),
+			name => write!(f, "{} ({})", name, self.priority),
 		}
 	}
 }
 
-pub fn fruit_vec() -> Vec<Fruit> {
-	vec![
-		Fruit::Coconut,
-		Fruit::Other("Apple".to_string()),
-		Fruit::Other("Banana".to_string()),
-		Fruit::Other("Cherry".to_string()),
-		Fruit::Other("Date".to_string()),
-		Fruit::Other("Elderberry".to_string()),
-		Fruit::Other("Fig".to_string()),
-		Fruit::Other("Grape".to_string()),
-		Fruit::Other("Honeydew".to_string()),
-		Fruit::Other("Icaco".to_string()),
-		Fruit::Other("Jackfruit".to_string()),
-		Fruit::Other("Kiwi".to_string()),
-		Fruit::Other("Lemon".to_string()),
-	]
+pub const FRUIT_NAMES: [&str; 13] = [
+	"Coconut",
+	"Apple",
+	"Banana",
+	"Cherry",
+	"Date",
+	"Elderberry",
+	"Fig",
+	"Grape",
+	"Honeydew",
+	"Icaco",
+	"Jackfruit",
+	"Kiwi",
+	"Lemon",
+];
+
+pub fn fruit_vec(preferences: &Preferences) -> Vec<Fruit> {
+	FRUIT_NAMES
+		.iter()
+		.map(|name| preferences.fruit(name))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn order_is_total_and_consistent_with_eq() {
+		let fruits = [
+			Fruit::new("Coconut", 10),
+			Fruit::new("Apple", 0),
+			Fruit::new("Banana", 0),
+			Fruit::new("Apple", 0),
+			Fruit::new("Lemon", -2),
+		];
+		for a in &fruits {
+			for b in &fruits {
+				assert_eq!(a.cmp(b) == Ordering::Equal, a == b);
+				assert_eq!(a.cmp(b), b.cmp(a).reverse());
+			}
+		}
+		assert!(Fruit::new("Apple", 0) > Fruit::new("Banana", 0));
+		assert!(Fruit::new("Lemon", 1) > Fruit::new("Apple", 0));
+	}
 }
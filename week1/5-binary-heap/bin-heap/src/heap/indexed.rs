@@ -0,0 +1,247 @@
+//! Binary heap that knows where every item is
+
+use super::{AddressableHeap, Handle, HeapError, Slots};
+
+#[derive(Debug, Clone)]
+struct Entry<P, T> {
+	priority: P,
+	item: T,
+	/// Index in `IndexedBinaryHeap::heap`
+	position: usize,
+}
+
+/// A binary min-heap with [Handle]s
+///
+/// The heap array holds slot numbers; every slot stores its item together with its position
+/// in the array, which is updated as items move. A handle therefore finds its item in O(1)
+/// and `decrease_key`/`remove` only need to sift it up or down.
+///
+/// # Example
+///
+/// ```rust
+/// use bin_heap::heap::{AddressableHeap, IndexedBinaryHeap};
+///
+/// let mut heap = IndexedBinaryHeap::new();
+/// heap.push(3, "Kiwi");
+/// let fig = heap.push(5, "Fig");
+/// heap.push(4, "Lemon");
+///
+/// heap.decrease_key(fig, 1).unwrap();
+/// assert_eq!(heap.peek(), Some((&1, &"Fig")));
+/// assert_eq!(heap.into_sorted_vec(), vec![(1, "Fig"), (3, "Kiwi"), (4, "Lemon")]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct IndexedBinaryHeap<P, T> {
+	heap: Vec<usize>,
+	slots: Slots<Entry<P, T>>,
+}
+
+impl<P: Ord, T> IndexedBinaryHeap<P, T> {
+	pub fn new() -> Self {
+		Self {
+			heap: Vec::new(),
+			slots: Slots::new(),
+		}
+	}
+
+	/// The items from the smallest priority to the largest, without consuming the heap
+	///
+	/// Walks the heap with a second heap of positions, `O(k log k)` for the first `k` items.
+	pub fn iter(&self) -> Iter<'_, P, T> {
+		let mut frontier = IndexedBinaryHeap::new();
+		if let Some(&root) = self.heap.first() {
+			frontier.push(&self.slots[root].priority, 0);
+		}
+		Iter {
+			heap: self,
+			frontier,
+		}
+	}
+
+	/// Moves all items of `other` into this heap, O(m log(n + m))
+	///
+	/// The handles of `other` are no longer valid.
+	pub fn merge(&mut self, other: Self) {
+		for (priority, item) in other {
+			self.push(priority, item);
+		}
+	}
+
+	/// The items from the smallest priority to the largest
+	pub fn into_sorted_vec(self) -> Vec<(P, T)> {
+		self.into_iter().collect()
+	}
+
+	fn less(&self, a: usize, b: usize) -> bool {
+		self.slots[self.heap[a]].priority < self.slots[self.heap[b]].priority
+	}
+
+	fn swap(&mut self, a: usize, b: usize) {
+		self.heap.swap(a, b);
+		self.slots[self.heap[a]].position = a;
+		self.slots[self.heap[b]].position = b;
+	}
+
+	fn sift_up(&mut self, mut position: usize) {
+		while position > 0 {
+			let parent = (position - 1) / 2;
+			if !self.less(position, parent) {
+				break;
+			}
+			self.swap(position, parent);
+			position = parent;
+		}
+	}
+
+	fn sift_down(&mut self, mut position: usize) {
+		loop {
+			let left = 2 * position + 1;
+			let right = left + 1;
+			let mut smallest = position;
+			if left < self.heap.len() && self.less(left, smallest) {
+				smallest = left;
+			}
+			if right < self.heap.len() && self.less(right, smallest) {
+				smallest = right;
+			}
+			if smallest == position {
+				break;
+			}
+			self.swap(position, smallest);
+			position = smallest;
+		}
+	}
+
+	/// Takes out the item at a position of the heap array
+	fn remove_at(&mut self, position: usize) -> (P, T) {
+		let last = self.heap.len() - 1;
+		self.swap(position, last);
+		let slot = self.heap.pop().expect("The heap is not empty");
+		if position < self.heap.len() {
+			// the former last item may belong above or below
+			let moved = self.heap[position];
+			self.sift_up(position);
+			self.sift_down(self.slots[moved].position);
+		}
+		let entry = self.slots.take(slot);
+		(entry.priority, entry.item)
+	}
+}
+
+impl<P: Ord, T> AddressableHeap<P, T> for IndexedBinaryHeap<P, T> {
+	fn push(&mut self, priority: P, item: T) -> Handle {
+		let position = self.heap.len();
+		let handle = self.slots.insert(Entry {
+			priority,
+			item,
+			position,
+		});
+		self.heap.push(handle.slot);
+		self.sift_up(position);
+		handle
+	}
+
+	fn peek(&self) -> Option<(&P, &T)> {
+		let entry = &self.slots[*self.heap.first()?];
+		Some((&entry.priority, &entry.item))
+	}
+
+	fn pop(&mut self) -> Option<(P, T)> {
+		(!self.heap.is_empty()).then(|| self.remove_at(0))
+	}
+
+	fn decrease_key(&mut self, handle: Handle, priority: P) -> Result<(), HeapError> {
+		let slot = self.slots.slot(handle)?;
+		let entry = &mut self.slots[slot];
+		if priority > entry.priority {
+			return Err(HeapError::PriorityIncrease);
+		}
+		entry.priority = priority;
+		let position = entry.position;
+		self.sift_up(position);
+		Ok(())
+	}
+
+	fn remove(&mut self, handle: Handle) -> Result<(P, T), HeapError> {
+		let slot = self.slots.slot(handle)?;
+		Ok(self.remove_at(self.slots[slot].position))
+	}
+
+	fn get(&self, handle: Handle) -> Option<(&P, &T)> {
+		let entry = &self.slots[self.slots.slot(handle).ok()?];
+		Some((&entry.priority, &entry.item))
+	}
+
+	fn len(&self) -> usize {
+		self.slots.len()
+	}
+}
+
+impl<P: Ord, T> Default for IndexedBinaryHeap<P, T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Consumes the heap in priority order
+impl<P: Ord, T> IntoIterator for IndexedBinaryHeap<P, T> {
+	type Item = (P, T);
+	type IntoIter = IntoIter<P, T>;
+
+	fn into_iter(self) -> IntoIter<P, T> {
+		IntoIter(self)
+	}
+}
+
+/// Pops the items in priority order
+pub struct IntoIter<P, T>(IndexedBinaryHeap<P, T>);
+
+impl<P: Ord, T> Iterator for IntoIter<P, T> {
+	type Item = (P, T);
+
+	fn next(&mut self) -> Option<(P, T)> {
+		self.0.pop()
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.0.len(), Some(self.0.len()))
+	}
+}
+
+impl<P: Ord, T> FromIterator<(P, T)> for IndexedBinaryHeap<P, T> {
+	fn from_iter<I: IntoIterator<Item = (P, T)>>(iter: I) -> Self {
+		let mut heap = Self::new();
+		heap.extend(iter);
+		heap
+	}
+}
+
+impl<P: Ord, T> Extend<(P, T)> for IndexedBinaryHeap<P, T> {
+	fn extend<I: IntoIterator<Item = (P, T)>>(&mut self, iter: I) {
+		for (priority, item) in iter {
+			self.push(priority, item);
+		}
+	}
+}
+
+/// Borrowing iterator in priority order, see [IndexedBinaryHeap::iter]
+pub struct Iter<'a, P, T> {
+	heap: &'a IndexedBinaryHeap<P, T>,
+	/// Positions whose parent was already returned, by priority
+	frontier: IndexedBinaryHeap<&'a P, usize>,
+}
+
+impl<'a, P: Ord, T> Iterator for Iter<'a, P, T> {
+	type Item = (&'a P, &'a T);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let (_, position) = self.frontier.pop()?;
+		for child in [2 * position + 1, 2 * position + 2] {
+			if let Some(&slot) = self.heap.heap.get(child) {
+				self.frontier.push(&self.heap.slots[slot].priority, child);
+			}
+		}
+		let entry = &self.heap.slots[self.heap.heap[position]];
+		Some((&entry.priority, &entry.item))
+	}
+}
@@ -0,0 +1,282 @@
+//! Addressable min-heaps
+//!
+//! `std::collections::BinaryHeap` can only push and pop: once an item is in, it cannot be
+//! found again. The heaps of this module return a [Handle] for every pushed item, which can
+//! later be used to lower its priority ([AddressableHeap::decrease_key]) or to remove it,
+//! as Dijkstra's algorithm and other graph searches need.
+//!
+//! - [IndexedBinaryHeap]: an array-based binary heap plus the position of every item,
+//!   O(log n) for every operation
+//! - [PairingHeap]: a multi-way tree, O(1) push and `decrease_key`, amortised O(log n) pop
+//!
+//! Both are min-heaps: the smallest priority comes out first. Wrap priorities in
+//! `std::cmp::Reverse` for a max-heap.
+
+pub mod indexed;
+pub mod pairing;
+
+pub use indexed::IndexedBinaryHeap;
+pub use pairing::PairingHeap;
+
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Identifies an item of a heap until it is popped or removed
+///
+/// Handles are only valid for the heap that returned them; using a handle of an item that
+/// left the heap, or of another heap, gives a [HeapError::InvalidHandle] instead of touching
+/// an unrelated item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle {
+	heap: u64,
+	slot: usize,
+	generation: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeapError {
+	/// The item left the heap, or the handle belongs to another heap
+	InvalidHandle,
+	/// `decrease_key` was asked to increase the priority
+	PriorityIncrease,
+}
+
+impl Display for HeapError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			HeapError::InvalidHandle => {
+				write!(f, "The handle does not refer to an item of this heap")
+			}
+			HeapError::PriorityIncrease => {
+				write!(f, "The new priority is greater than the current one")
+			}
+		}
+	}
+}
+
+impl std::error::Error for HeapError {}
+
+/// A min-heap whose items can be reached through handles
+pub trait AddressableHeap<P: Ord, T> {
+	fn push(&mut self, priority: P, item: T) -> Handle;
+
+	/// The item with the smallest priority
+	fn peek(&self) -> Option<(&P, &T)>;
+
+	fn pop(&mut self) -> Option<(P, T)>;
+
+	/// Lowers the priority of an item, the new priority must not be greater than the current one
+	fn decrease_key(&mut self, handle: Handle, priority: P) -> Result<(), HeapError>;
+
+	/// Takes the item out of the heap, wherever it is
+	fn remove(&mut self, handle: Handle) -> Result<(P, T), HeapError>;
+
+	fn get(&self, handle: Handle) -> Option<(&P, &T)>;
+
+	fn len(&self) -> usize;
+
+	fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+}
+
+/// A unique id for every heap, so that handles cannot be mixed up between heaps
+fn next_heap_id() -> u64 {
+	static NEXT: AtomicU64 = AtomicU64::new(0);
+	NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Storage for items that keeps their index while others come and go
+///
+/// Freed slots are reused, a generation counter tells their successive items apart.
+#[derive(Debug, Clone)]
+struct Slots<E> {
+	heap: u64,
+	slots: Vec<(u64, Option<E>)>,
+	free: Vec<usize>,
+	len: usize,
+}
+
+impl<E> Slots<E> {
+	fn new() -> Self {
+		Self {
+			heap: next_heap_id(),
+			slots: Vec::new(),
+			free: Vec::new(),
+			len: 0,
+		}
+	}
+
+	fn insert(&mut self, entry: E) -> Handle {
+		self.len += 1;
+		let slot = match self.free.pop() {
+			Some(slot) => {
+				self.slots[slot].1 = Some(entry);
+				slot
+			}
+			None => {
+				self.slots.push((0, Some(entry)));
+				self.slots.len() - 1
+			}
+		};
+		self.handle(slot)
+	}
+
+	fn handle(&self, slot: usize) -> Handle {
+		Handle {
+			heap: self.heap,
+			slot,
+			generation: self.slots[slot].0,
+		}
+	}
+
+	/// The slot of a valid handle
+	fn slot(&self, handle: Handle) -> Result<usize, HeapError> {
+		match self.slots.get(handle.slot) {
+			Some((generation, Some(_)))
+				if handle.heap == self.heap && *generation == handle.generation =>
+			{
+				Ok(handle.slot)
+			}
+			_ => Err(HeapError::InvalidHandle),
+		}
+	}
+
+	fn take(&mut self, slot: usize) -> E {
+		let (generation, entry) = &mut self.slots[slot];
+		*generation += 1;
+		self.free.push(slot);
+		self.len -= 1;
+		entry.take().expect("Only occupied slots are taken")
+	}
+
+	fn len(&self) -> usize {
+		self.len
+	}
+}
+
+impl<E> std::ops::Index<usize> for Slots<E> {
+	type Output = E;
+
+	fn index(&self, slot: usize) -> &E {
+		self.slots[slot]
+			.1
+			.as_ref()
+			.expect("Only occupied slots are read")
+	}
+}
+
+impl<E> std::ops::IndexMut<usize> for Slots<E> {
+	fn index_mut(&mut self, slot: usize) -> &mut E {
+		self.slots[slot]
+			.1
+			.as_mut()
+			.expect("Only occupied slots are written")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use proptest::prelude::*;
+	use std::collections::BTreeSet;
+
+	#[derive(Debug, Clone)]
+	enum Operation {
+		Push(u8),
+		Pop,
+		/// Decrease the key of the n-th live handle (modulo their number) by this much
+		Decrease(usize, u8),
+		Remove(usize),
+	}
+
+	fn operation() -> impl Strategy<Value = Operation> {
+		prop_oneof![
+			3 => any::<u8>().prop_map(Operation::Push),
+			1 => Just(Operation::Pop),
+			2 => (any::<usize>(), any::<u8>()).prop_map(|(i, d)| Operation::Decrease(i, d)),
+			1 => any::<usize>().prop_map(Operation::Remove),
+		]
+	}
+
+	/// Replays the operations on the heap and on a sorted set of `(priority, id)`
+	fn agrees_with_a_sorted_set<H: AddressableHeap<i32, usize>>(
+		mut heap: H,
+		operations: Vec<Operation>,
+	) {
+		let mut expected = BTreeSet::new();
+		let mut live: Vec<(Handle, usize)> = Vec::new();
+		let mut priorities = Vec::new();
+
+		for operation in operations {
+			match operation {
+				Operation::Push(priority) => {
+					let id = priorities.len();
+					priorities.push(priority as i32);
+					live.push((heap.push(priority as i32, id), id));
+					expected.insert((priority as i32, id));
+				}
+				Operation::Pop => {
+					let popped = heap.pop();
+					// ties may come out in any order, only the priority is fixed
+					assert_eq!(popped.map(|(p, _)| p), expected.first().map(|(p, _)| *p));
+					if let Some((priority, id)) = popped {
+						assert!(expected.remove(&(priority, id)));
+						let position = live.iter().position(|(_, live_id)| *live_id == id).unwrap();
+						let (handle, _) = live.swap_remove(position);
+						assert_eq!(heap.get(handle), None);
+					}
+				}
+				Operation::Decrease(i, by) if !live.is_empty() => {
+					let (handle, id) = live[i % live.len()];
+					let priority = priorities[id] - by as i32;
+					expected.remove(&(priorities[id], id));
+					expected.insert((priority, id));
+					priorities[id] = priority;
+					heap.decrease_key(handle, priority).unwrap();
+				}
+				Operation::Remove(i) if !live.is_empty() => {
+					let (handle, id) = live.swap_remove(i % live.len());
+					assert_eq!(heap.remove(handle), Ok((priorities[id], id)));
+					assert_eq!(heap.remove(handle), Err(HeapError::InvalidHandle));
+					expected.remove(&(priorities[id], id));
+				}
+				_ => {}
+			}
+			assert_eq!(heap.len(), expected.len());
+			assert_eq!(
+				heap.peek().map(|(p, _)| *p),
+				expected.first().map(|(p, _)| *p)
+			);
+		}
+	}
+
+	proptest! {
+		#[test]
+		fn indexed_binary_heap_agrees_with_a_sorted_set(operations in prop::collection::vec(operation(), 0..200)) {
+			agrees_with_a_sorted_set(IndexedBinaryHeap::new(), operations);
+		}
+
+		#[test]
+		fn pairing_heap_agrees_with_a_sorted_set(operations in prop::collection::vec(operation(), 0..200)) {
+			agrees_with_a_sorted_set(PairingHeap::new(), operations);
+		}
+	}
+
+	#[test]
+	fn handles_are_checked() {
+		let mut heap = IndexedBinaryHeap::new();
+		let mut other = IndexedBinaryHeap::new();
+		let handle = heap.push(1, "a");
+		let foreign = other.push(1, "b");
+		assert_eq!(
+			heap.decrease_key(handle, 2),
+			Err(HeapError::PriorityIncrease)
+		);
+		assert_eq!(heap.remove(foreign), Err(HeapError::InvalidHandle));
+		heap.pop();
+		// the slot is reused, the old handle must not reach the new item
+		heap.push(5, "c");
+		assert_eq!(heap.get(handle), None);
+	}
+}
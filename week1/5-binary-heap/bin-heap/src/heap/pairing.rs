@@ -0,0 +1,326 @@
+//! Pairing heap stored in an arena
+
+use super::indexed::IndexedBinaryHeap;
+use super::{AddressableHeap, Handle, HeapError, Slots};
+
+#[derive(Debug, Clone)]
+struct Node<P, T> {
+	priority: P,
+	item: T,
+	/// First child
+	child: Option<usize>,
+	/// Next sibling
+	next: Option<usize>,
+	/// Previous sibling, or the parent for a first child
+	prev: Option<usize>,
+}
+
+/// A pairing min-heap with [Handle]s
+///
+/// A tree where every node is smaller than its children, which can have any number of
+/// them. Pushing and decreasing a key link a single node below or above the root in O(1);
+/// popping merges the children of the root pairwise, amortised O(log n).
+/// The nodes live in a vector and point to each other by index.
+///
+/// # Example
+///
+/// ```rust
+/// use bin_heap::heap::{AddressableHeap, PairingHeap};
+///
+/// let mut morning: PairingHeap<i32, &str> = [(3, "Kiwi"), (5, "Fig")].into_iter().collect();
+/// let mut evening = PairingHeap::new();
+/// let lemon = evening.push(4, "Lemon");
+/// evening.decrease_key(lemon, 2).unwrap();
+///
+/// morning.merge(evening);
+/// let served: Vec<_> = morning.iter().map(|(_, fruit)| *fruit).collect();
+/// assert_eq!(served, vec!["Lemon", "Kiwi", "Fig"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PairingHeap<P, T> {
+	root: Option<usize>,
+	nodes: Slots<Node<P, T>>,
+}
+
+impl<P: Ord, T> PairingHeap<P, T> {
+	pub fn new() -> Self {
+		Self {
+			root: None,
+			nodes: Slots::new(),
+		}
+	}
+
+	/// The items from the smallest priority to the largest, without consuming the heap
+	pub fn iter(&self) -> Iter<'_, P, T> {
+		let mut frontier = IndexedBinaryHeap::new();
+		if let Some(root) = self.root {
+			frontier.push(&self.nodes[root].priority, root);
+		}
+		Iter {
+			heap: self,
+			frontier,
+		}
+	}
+
+	/// Moves all items of `other` into this heap
+	///
+	/// The two roots are linked in O(1), but the nodes of `other` have to be copied into this
+	/// heap's arena first, O(m). The handles of `other` are no longer valid.
+	pub fn merge(&mut self, other: Self) {
+		let offset = self.nodes.slots.len();
+		let shift = |index: Option<usize>| index.map(|i| i + offset);
+		for (_, node) in other.nodes.slots {
+			let slot = self.nodes.slots.len();
+			match node {
+				Some(node) => {
+					self.nodes.slots.push((
+						0,
+						Some(Node {
+							child: shift(node.child),
+							next: shift(node.next),
+							prev: shift(node.prev),
+							..node
+						}),
+					));
+					self.nodes.len += 1;
+				}
+				None => {
+					self.nodes.slots.push((0, None));
+					self.nodes.free.push(slot);
+				}
+			}
+		}
+		self.root = self.meld(self.root, shift(other.root));
+	}
+
+	/// The items from the smallest priority to the largest
+	pub fn into_sorted_vec(self) -> Vec<(P, T)> {
+		self.into_iter().collect()
+	}
+
+	/// Makes the root with the larger priority the first child of the other one
+	fn link(&mut self, a: usize, b: usize) -> usize {
+		let (parent, child) = if self.nodes[b].priority < self.nodes[a].priority {
+			(b, a)
+		} else {
+			(a, b)
+		};
+		let first = self.nodes[parent].child;
+		if let Some(first) = first {
+			self.nodes[first].prev = Some(child);
+		}
+		let node = &mut self.nodes[child];
+		node.next = first;
+		node.prev = Some(parent);
+		self.nodes[parent].child = Some(child);
+		parent
+	}
+
+	fn meld(&mut self, a: Option<usize>, b: Option<usize>) -> Option<usize> {
+		match (a, b) {
+			(Some(a), Some(b)) => Some(self.link(a, b)),
+			_ => a.or(b),
+		}
+	}
+
+	/// Detaches a node, with its subtree, from its parent and siblings
+	fn cut(&mut self, index: usize) {
+		let Node { prev, next, .. } = self.nodes[index];
+		if let Some(prev) = prev {
+			if self.nodes[prev].child == Some(index) {
+				self.nodes[prev].child = next;
+			} else {
+				self.nodes[prev].next = next;
+			}
+		}
+		if let Some(next) = next {
+			self.nodes[next].prev = prev;
+		}
+		let node = &mut self.nodes[index];
+		node.prev = None;
+		node.next = None;
+	}
+
+	/// Merges a whole list of siblings into one tree: pairs from left to right, then the
+	/// pairs from right to left
+	fn merge_pairs(&mut self, first: Option<usize>) -> Option<usize> {
+		let mut pairs = Vec::new();
+		let mut current = first;
+		while let Some(a) = current {
+			let b = self.nodes[a].next;
+			current = b.and_then(|b| self.nodes[b].next);
+			for index in [Some(a), b].into_iter().flatten() {
+				let node = &mut self.nodes[index];
+				node.prev = None;
+				node.next = None;
+			}
+			pairs.push(self.meld(Some(a), b));
+		}
+		pairs
+			.into_iter()
+			.rev()
+			.fold(None, |root, pair| self.meld(pair, root))
+	}
+
+	/// Takes a node out of the heap, its children take its place
+	fn remove_node(&mut self, index: usize) -> (P, T) {
+		let children = self.nodes[index].child.take();
+		if Some(index) == self.root {
+			self.root = self.merge_pairs(children);
+		} else {
+			self.cut(index);
+			let subtree = self.merge_pairs(children);
+			self.root = self.meld(self.root, subtree);
+		}
+		let node = self.nodes.take(index);
+		(node.priority, node.item)
+	}
+}
+
+impl<P: Ord, T> AddressableHeap<P, T> for PairingHeap<P, T> {
+	fn push(&mut self, priority: P, item: T) -> Handle {
+		let handle = self.nodes.insert(Node {
+			priority,
+			item,
+			child: None,
+			next: None,
+			prev: None,
+		});
+		self.root = self.meld(self.root, Some(handle.slot));
+		handle
+	}
+
+	fn peek(&self) -> Option<(&P, &T)> {
+		let node = &self.nodes[self.root?];
+		Some((&node.priority, &node.item))
+	}
+
+	fn pop(&mut self) -> Option<(P, T)> {
+		let root = self.root?;
+		Some(self.remove_node(root))
+	}
+
+	fn decrease_key(&mut self, handle: Handle, priority: P) -> Result<(), HeapError> {
+		let index = self.nodes.slot(handle)?;
+		let node = &mut self.nodes[index];
+		if priority > node.priority {
+			return Err(HeapError::PriorityIncrease);
+		}
+		node.priority = priority;
+		if Some(index) != self.root {
+			self.cut(index);
+			self.root = self.meld(self.root, Some(index));
+		}
+		Ok(())
+	}
+
+	fn remove(&mut self, handle: Handle) -> Result<(P, T), HeapError> {
+		let index = self.nodes.slot(handle)?;
+		Ok(self.remove_node(index))
+	}
+
+	fn get(&self, handle: Handle) -> Option<(&P, &T)> {
+		let node = &self.nodes[self.nodes.slot(handle).ok()?];
+		Some((&node.priority, &node.item))
+	}
+
+	fn len(&self) -> usize {
+		self.nodes.len()
+	}
+}
+
+impl<P: Ord, T> Default for PairingHeap<P, T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Consumes the heap in priority order
+impl<P: Ord, T> IntoIterator for PairingHeap<P, T> {
+	type Item = (P, T);
+	type IntoIter = IntoIter<P, T>;
+
+	fn into_iter(self) -> IntoIter<P, T> {
+		IntoIter(self)
+	}
+}
+
+/// Pops the items in priority order
+pub struct IntoIter<P, T>(PairingHeap<P, T>);
+
+impl<P: Ord, T> Iterator for IntoIter<P, T> {
+	type Item = (P, T);
+
+	fn next(&mut self) -> Option<(P, T)> {
+		self.0.pop()
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.0.len(), Some(self.0.len()))
+	}
+}
+
+impl<P: Ord, T> FromIterator<(P, T)> for PairingHeap<P, T> {
+	fn from_iter<I: IntoIterator<Item = (P, T)>>(iter: I) -> Self {
+		let mut heap = Self::new();
+		heap.extend(iter);
+		heap
+	}
+}
+
+impl<P: Ord, T> Extend<(P, T)> for PairingHeap<P, T> {
+	fn extend<I: IntoIterator<Item = (P, T)>>(&mut self, iter: I) {
+		for (priority, item) in iter {
+			self.push(priority, item);
+		}
+	}
+}
+
+/// Borrowing iterator in priority order, see [PairingHeap::iter]
+pub struct Iter<'a, P, T> {
+	heap: &'a PairingHeap<P, T>,
+	/// Nodes whose parent was already returned, by priority
+	frontier: IndexedBinaryHeap<&'a P, usize>,
+}
+
+impl<'a, P: Ord, T> Iterator for Iter<'a, P, T> {
+	type Item = (&'a P, &'a T);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let (_, index) = self.frontier.pop()?;
+		let node = &self.heap.nodes[index];
+		let mut child = node.child;
+		while let Some(c) = child {
+			self.frontier.push(&self.heap.nodes[c].priority, c);
+			child = self.heap.nodes[c].next;
+		}
+		Some((&node.priority, &node.item))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn merged_heaps_keep_working() {
+		let mut a: PairingHeap<i32, char> = [(4, 'a'), (1, 'b'), (7, 'c')].into_iter().collect();
+		let mut b = PairingHeap::new();
+		b.push(3, 'd');
+		let e = b.push(9, 'e');
+		// leaves a free slot in the arena of b
+		b.pop();
+		assert_eq!(b.get(e), Some((&9, &'e')));
+
+		a.merge(b);
+		assert_eq!(a.len(), 4);
+		// the merged handle is stale
+		assert_eq!(a.remove(e), Err(HeapError::InvalidHandle));
+		let f = a.push(8, 'f');
+		a.decrease_key(f, 0).unwrap();
+
+		let priorities: Vec<i32> = a.iter().map(|(p, _)| *p).collect();
+		assert_eq!(priorities, vec![0, 1, 4, 7, 9]);
+		assert_eq!(a.into_sorted_vec().len(), 5);
+	}
+}
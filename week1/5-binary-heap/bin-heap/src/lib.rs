@@ -0,0 +1,16 @@
+//! # Binary heap
+//!
+//! Fruit salads served through a priority queue: every fruit has a priority, read from
+//! a preferences file, and the favourite ones come out of the heap first.
+//!
+//! [heap] adds min-heaps whose items can be reached again through handles, used by
+//! [dijkstra] for shortest paths, and [streaming] selects, merges and summarises long
+//! streams of items with small heaps.
+
+pub mod dijkstra;
+pub mod fruits;
+pub mod heap;
+pub mod preferences;
+pub mod queue;
+pub mod salad;
+pub mod streaming;
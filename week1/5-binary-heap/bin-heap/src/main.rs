@@ -1,35 +1,85 @@
-use std::collections::BinaryHeap;
-use rand::seq::SliceRandom;
-use crate::fruits::Fruit;
+use bin_heap::dijkstra::dijkstra;
+use bin_heap::fruits;
+use bin_heap::heap::PairingHeap;
+use bin_heap::preferences::Preferences;
+use bin_heap::queue::PriorityQueue;
+use bin_heap::salad::Recipe;
+use bin_heap::streaming;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::process;
 
-mod fruits;
+const PREFERENCES: &str = "data/preferences.toml";
+const SALAD_SIZE: usize = 12;
+const STALLS: [&str; 5] = ["Entrance", "Citrus", "Tropical", "Berries", "Checkout"];
 
-fn generate_random_fruit_salad(fruits: Vec<Fruit>) -> BinaryHeap<Fruit> {
-	let mut rng = rand::thread_rng();
-	let mut salad: BinaryHeap<Fruit> = BinaryHeap::new();
-
-
-	let mut coconut_count = 0;
-	while coconut_count < 2 {
-		let fruit = fruits.choose(&mut rng).unwrap();
-		if *fruit == Fruit::Coconut {
-			coconut_count += 1;
-			salad.push(Fruit::Coconut);
-		} else {
-			salad.push(Fruit::Other(fruit.to_string()));
+fn main() {
+	// The preferences file can be given as first argument, a seed for the salad as second
+	let path = std::env::args().nth(1).unwrap_or(PREFERENCES.to_string());
+	let mut rng = match std::env::args().nth(2).map(|seed| seed.parse::<u64>()) {
+		Some(Ok(seed)) => StdRng::seed_from_u64(seed),
+		Some(Err(e)) => {
+			eprintln!("Invalid seed: {}", e);
+			process::exit(1);
 		}
-	}
+		None => StdRng::from_entropy(),
+	};
+	let preferences = Preferences::load(&path).unwrap_or_else(|e| {
+		eprintln!("{}: {}", path, e);
+		process::exit(1);
+	});
 
-	salad
-}
+	let fruits = fruits::fruit_vec(&preferences);
+	let recipe = Recipe::new(SALAD_SIZE)
+		.at_least("Coconut", 2)
+		.at_most_each(3);
+	let salad = recipe.generate(&fruits, &mut rng).unwrap_or_else(|e| {
+		eprintln!("{}", e);
+		process::exit(1);
+	});
+	println!(
+		"Random fruit salad of {} fruits, with at least 2 coconuts and at most 3 of each:",
+		SALAD_SIZE
+	);
 
+	for fruit in salad.clone().into_sorted_vec() {
+		println!("{}", fruit);
+	}
 
-fn main() {
-    let fruits = fruits::fruit_vec();
-	let salad = generate_random_fruit_salad(fruits);
-	println!("Random fruit salad with at least 2 coconuts:");
+	let favourites = streaming::top_k(salad.iter().cloned(), 3);
+	println!(
+		"\nTop 3: {}",
+		favourites
+			.iter()
+			.map(|fruit| fruit.name.as_str())
+			.collect::<Vec<_>>()
+			.join(", ")
+	);
 
-	for fruit in salad.into_sorted_vec() {
+	// Changed my mind: no more coconuts, and lemons first
+	let mut queue: PriorityQueue = salad.into_iter().collect();
+	queue.remove("Coconut");
+	queue.change_priority("Lemon", 100);
+	println!("\nServed without coconuts, lemons first:");
+	while let Some(fruit) = queue.pop() {
 		println!("{}", fruit);
 	}
+
+	// The shortest walk through the market, in metres between stalls
+	let market = vec![
+		vec![(1, 40), (2, 90)],
+		vec![(2, 30), (3, 70)],
+		vec![(3, 20), (4, 100)],
+		vec![(4, 30)],
+		vec![],
+	];
+	let paths = dijkstra::<PairingHeap<_, _>>(&market, 0);
+	if let (Some(path), Some(distance)) = (paths.path_to(4), paths.distances[4]) {
+		let stalls: Vec<&str> = path.iter().map(|&stall| STALLS[stall]).collect();
+		println!(
+			"\nShortest walk to the checkout ({} m): {}",
+			distance,
+			stalls.join(" -> ")
+		);
+	}
 }
@@ -0,0 +1,89 @@
+//! Fruit priorities, loaded from a TOML file
+//!
+//! ```toml
+//! default = 0
+//!
+//! [priorities]
+//! Coconut = 10
+//! Lemon = -2
+//! ```
+
+use crate::fruits::Fruit;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum PreferencesError {
+	Io(std::io::Error),
+	Toml(toml::de::Error),
+}
+
+impl Display for PreferencesError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			PreferencesError::Io(e) => write!(f, "Could not read the preferences: {}", e),
+			PreferencesError::Toml(e) => write!(f, "Invalid preferences: {}", e),
+		}
+	}
+}
+
+impl std::error::Error for PreferencesError {}
+
+impl From<std::io::Error> for PreferencesError {
+	fn from(e: std::io::Error) -> Self {
+		PreferencesError::Io(e)
+	}
+}
+
+impl From<toml::de::Error> for PreferencesError {
+	fn from(e: toml::de::Error) -> Self {
+		PreferencesError::Toml(e)
+	}
+}
+
+/// How much every fruit is liked
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Preferences {
+	/// Priority of the fruits without an explicit one
+	#[serde(default)]
+	pub default: i32,
+	#[serde(default)]
+	pub priorities: HashMap<String, i32>,
+}
+
+impl Preferences {
+	pub fn load(path: impl AsRef<Path>) -> Result<Self, PreferencesError> {
+		let contents = fs::read_to_string(path)?;
+		Self::from_toml(&contents)
+	}
+
+	pub fn from_toml(contents: &str) -> Result<Self, PreferencesError> {
+		Ok(toml::from_str(contents)?)
+	}
+
+	pub fn priority(&self, name: &str) -> i32 {
+		self.priorities.get(name).copied().unwrap_or(self.default)
+	}
+
+	/// The fruit with its preferred priority
+	pub fn fruit(&self, name: &str) -> Fruit {
+		Fruit::new(name, self.priority(name))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn unlisted_fruits_get_the_default() {
+		let preferences =
+			Preferences::from_toml("default = 1\n[priorities]\nCoconut = 10\n").unwrap();
+		assert_eq!(preferences.fruit("Coconut"), Fruit::new("Coconut", 10));
+		assert_eq!(preferences.priority("Apple"), 1);
+		assert!(Preferences::from_toml("[priorities]\nCoconut = \"a lot\"\n").is_err());
+	}
+}
@@ -0,0 +1,132 @@
+//! A priority queue of fruits that can be re-prioritised
+
+use crate::fruits::Fruit;
+use std::collections::BinaryHeap;
+
+/// A max-heap of fruits, addressable by name
+///
+/// `std::collections::BinaryHeap` cannot find an element, so changing a priority or removing
+/// a fruit rebuilds the heap in O(n). The other operations are the ones of `BinaryHeap`.
+#[derive(Debug, Clone, Default)]
+pub struct PriorityQueue {
+	heap: BinaryHeap<Fruit>,
+}
+
+impl PriorityQueue {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn push(&mut self, fruit: Fruit) {
+		self.heap.push(fruit);
+	}
+
+	/// The fruit with the highest priority
+	pub fn pop(&mut self) -> Option<Fruit> {
+		self.heap.pop()
+	}
+
+	pub fn peek(&self) -> Option<&Fruit> {
+		self.heap.peek()
+	}
+
+	pub fn len(&self) -> usize {
+		self.heap.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.heap.is_empty()
+	}
+
+	pub fn contains(&self, name: &str) -> bool {
+		self.heap.iter().any(|fruit| fruit.name == name)
+	}
+
+	/// Gives every fruit called `name` the new priority, returns how many there were
+	///
+	/// # Example
+	///
+	/// ```rust
+	/// use bin_heap::fruits::Fruit;
+	/// use bin_heap::queue::PriorityQueue;
+	///
+	/// let mut queue: PriorityQueue = [Fruit::new("Coconut", 10), Fruit::new("Kiwi", 3)].into_iter().collect();
+	/// assert_eq!(queue.change_priority("Kiwi", 20), 1);
+	/// assert_eq!(queue.pop(), Some(Fruit::new("Kiwi", 20)));
+	/// ```
+	pub fn change_priority(&mut self, name: &str, priority: i32) -> usize {
+		let mut fruits = std::mem::take(&mut self.heap).into_vec();
+		let mut changed = 0;
+		for fruit in fruits.iter_mut().filter(|fruit| fruit.name == name) {
+			fruit.priority = priority;
+			changed += 1;
+		}
+		self.heap = BinaryHeap::from(fruits);
+		changed
+	}
+
+	/// Takes every fruit called `name` out of the queue
+	pub fn remove(&mut self, name: &str) -> Vec<Fruit> {
+		let mut removed = Vec::new();
+		self.heap.retain(|fruit| {
+			if fruit.name == name {
+				removed.push(fruit.clone());
+				false
+			} else {
+				true
+			}
+		});
+		removed
+	}
+
+	/// The fruits from the lowest to the highest priority, like `BinaryHeap::into_sorted_vec`
+	pub fn into_sorted_vec(self) -> Vec<Fruit> {
+		self.heap.into_sorted_vec()
+	}
+}
+
+impl FromIterator<Fruit> for PriorityQueue {
+	fn from_iter<I: IntoIterator<Item = Fruit>>(fruits: I) -> Self {
+		Self {
+			heap: fruits.into_iter().collect(),
+		}
+	}
+}
+
+impl Extend<Fruit> for PriorityQueue {
+	fn extend<I: IntoIterator<Item = Fruit>>(&mut self, fruits: I) {
+		self.heap.extend(fruits);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn pops_by_priority_then_name() {
+		let mut queue: PriorityQueue = [
+			Fruit::new("Banana", 0),
+			Fruit::new("Coconut", 10),
+			Fruit::new("Apple", 0),
+			Fruit::new("Coconut", 10),
+		]
+		.into_iter()
+		.collect();
+
+		let removed = queue.remove("Coconut");
+		assert_eq!(removed.len(), 2);
+		assert!(!queue.contains("Coconut"));
+		assert_eq!(queue.pop().map(|f| f.name), Some("Apple".to_string()));
+		assert_eq!(queue.pop().map(|f| f.name), Some("Banana".to_string()));
+		assert!(queue.is_empty());
+	}
+
+	#[test]
+	fn unknown_names_change_nothing() {
+		let mut queue: PriorityQueue = [Fruit::new("Fig", 5)].into_iter().collect();
+		assert_eq!(queue.change_priority("Durian", 100), 0);
+		assert!(queue.remove("Durian").is_empty());
+		assert_eq!(queue.peek(), Some(&Fruit::new("Fig", 5)));
+	}
+}
@@ -0,0 +1,309 @@
+//! Random fruit salads of a given size, with quotas per fruit
+//!
+//! A [Recipe] fixes how many fruits go in the salad and how many of each fruit are allowed.
+//! The quotas are checked before drawing anything: either they can be met and the salad is
+//! drawn in exactly `size` steps, or a [SaladError] says why not.
+
+use crate::fruits::Fruit;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SaladError {
+	/// A quota names a fruit that is not in the list
+	UnknownFruit(String),
+	/// The minimum of a fruit is above its maximum
+	InvalidQuota {
+		fruit: String,
+		min: usize,
+		max: usize,
+	},
+	/// The minimums need more fruits than the salad size
+	TooManyRequired { required: usize, size: usize },
+	/// The maximums allow fewer fruits than the salad size
+	TooFewAllowed { allowed: usize, size: usize },
+}
+
+impl Display for SaladError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			SaladError::UnknownFruit(name) => write!(f, "There is no {} to put in the salad", name),
+			SaladError::InvalidQuota { fruit, min, max } => {
+				write!(f, "At least {} but at most {} {}", min, max, fruit)
+			}
+			SaladError::TooManyRequired { required, size } => write!(
+				f,
+				"The quotas require {} fruits, the salad only has room for {}",
+				required, size
+			),
+			SaladError::TooFewAllowed { allowed, size } => write!(
+				f,
+				"The quotas allow only {} fruits, the salad needs {}",
+				allowed, size
+			),
+		}
+	}
+}
+
+impl std::error::Error for SaladError {}
+
+/// How many of one fruit a salad may contain
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Quota {
+	pub min: usize,
+	/// `None` for no limit other than the salad size
+	pub max: Option<usize>,
+}
+
+/// Size and quotas of a salad
+///
+/// # Example
+///
+/// ```rust
+/// use bin_heap::fruits::Fruit;
+/// use bin_heap::salad::Recipe;
+/// use rand::rngs::StdRng;
+/// use rand::SeedableRng;
+///
+/// let fruits = vec![Fruit::new("Coconut", 10), Fruit::new("Fig", 5), Fruit::new("Kiwi", 3)];
+/// // at least 2 coconuts and no limit on them, at most 3 of any other fruit
+/// let recipe = Recipe::new(6).at_least("Coconut", 2).at_most_each(3);
+///
+/// let salad = recipe.generate(&fruits, &mut StdRng::seed_from_u64(1)).unwrap();
+/// assert_eq!(salad.len(), 6);
+/// assert!(salad.iter().filter(|fruit| fruit.name == "Coconut").count() >= 2);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recipe {
+	pub size: usize,
+	/// Quotas of single fruits, by name
+	pub quotas: HashMap<String, Quota>,
+	/// Maximum of the fruits without a quota
+	pub max_each: Option<usize>,
+}
+
+impl Recipe {
+	/// A salad of `size` fruits, any of them as often as it gets drawn
+	pub fn new(size: usize) -> Self {
+		Self {
+			size,
+			..Self::default()
+		}
+	}
+
+	pub fn at_least(mut self, fruit: &str, min: usize) -> Self {
+		self.quotas.entry(fruit.to_string()).or_default().min = min;
+		self
+	}
+
+	pub fn at_most(mut self, fruit: &str, max: usize) -> Self {
+		self.quotas.entry(fruit.to_string()).or_default().max = Some(max);
+		self
+	}
+
+	/// Limits every fruit that has no quota of its own
+	///
+	/// A fruit with a minimum is not limited by it, [Recipe::at_most] caps it as well.
+	pub fn at_most_each(mut self, max: usize) -> Self {
+		self.max_each = Some(max);
+		self
+	}
+
+	/// `(min, max)` of every fruit of the list, after checking the quotas can be met
+	fn bounds(&self, fruits: &[Fruit]) -> Result<Vec<(usize, usize)>, SaladError> {
+		if let Some(unknown) = self
+			.quotas
+			.keys()
+			.find(|name| !fruits.iter().any(|fruit| &fruit.name == *name))
+		{
+			return Err(SaladError::UnknownFruit(unknown.clone()));
+		}
+
+		let mut bounds = Vec::with_capacity(fruits.len());
+		for fruit in fruits {
+			let (quota, max) = match self.quotas.get(&fruit.name) {
+				Some(quota) => (*quota, quota.max),
+				None => (Quota::default(), self.max_each),
+			};
+			let max = max.unwrap_or(self.size);
+			if quota.min > max {
+				return Err(SaladError::InvalidQuota {
+					fruit: fruit.name.clone(),
+					min: quota.min,
+					max,
+				});
+			}
+			bounds.push((quota.min, max));
+		}
+
+		let required: usize = bounds.iter().map(|(min, _)| min).sum();
+		if required > self.size {
+			return Err(SaladError::TooManyRequired {
+				required,
+				size: self.size,
+			});
+		}
+		let allowed = bounds
+			.iter()
+			.fold(0usize, |allowed, (_, max)| allowed.saturating_add(*max));
+		if allowed < self.size {
+			return Err(SaladError::TooFewAllowed {
+				allowed,
+				size: self.size,
+			});
+		}
+		Ok(bounds)
+	}
+
+	/// Draws a salad from `fruits`
+	///
+	/// The minimum of every fruit goes in first, the rest is drawn uniformly among the fruits
+	/// that have not reached their maximum. A seeded `rng` always gives the same salad.
+	pub fn generate<R: Rng + ?Sized>(
+		&self,
+		fruits: &[Fruit],
+		rng: &mut R,
+	) -> Result<BinaryHeap<Fruit>, SaladError> {
+		let bounds = self.bounds(fruits)?;
+		let mut counts: Vec<usize> = bounds.iter().map(|(min, _)| *min).collect();
+		let mut salad = BinaryHeap::with_capacity(self.size);
+		for (fruit, &count) in fruits.iter().zip(&counts) {
+			salad.extend(std::iter::repeat_n(fruit.clone(), count));
+		}
+
+		// fruits that can still be drawn
+		let mut available: Vec<usize> = (0..fruits.len())
+			.filter(|&i| counts[i] < bounds[i].1)
+			.collect();
+		while salad.len() < self.size {
+			let &i = available
+				.choose(rng)
+				.expect("The maximums allow enough fruits");
+			salad.push(fruits[i].clone());
+			counts[i] += 1;
+			if counts[i] == bounds[i].1 {
+				available.retain(|&j| j != i);
+			}
+		}
+		Ok(salad)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rand::rngs::StdRng;
+	use rand::SeedableRng;
+
+	fn fruits() -> Vec<Fruit> {
+		["Coconut", "Apple", "Banana"]
+			.iter()
+			.map(|name| Fruit::new(*name, 0))
+			.collect()
+	}
+
+	fn count(salad: &BinaryHeap<Fruit>, name: &str) -> usize {
+		salad.iter().filter(|fruit| fruit.name == name).count()
+	}
+
+	#[test]
+	fn quotas_are_respected() {
+		let recipe = Recipe::new(8)
+			.at_least("Coconut", 2)
+			.at_most("Coconut", 2)
+			.at_most_each(3);
+		for seed in 0..50 {
+			let salad = recipe
+				.generate(&fruits(), &mut StdRng::seed_from_u64(seed))
+				.unwrap();
+			assert_eq!(salad.len(), 8);
+			assert_eq!(count(&salad, "Coconut"), 2);
+			assert_eq!(count(&salad, "Apple"), 3);
+			assert_eq!(count(&salad, "Banana"), 3);
+		}
+	}
+
+	#[test]
+	fn fruits_with_a_quota_are_not_limited_by_at_most_each() {
+		let recipe = Recipe::new(8).at_least("Coconut", 4).at_most_each(3);
+		for seed in 0..50 {
+			let salad = recipe
+				.generate(&fruits(), &mut StdRng::seed_from_u64(seed))
+				.unwrap();
+			assert!(count(&salad, "Coconut") >= 4);
+			assert!(count(&salad, "Apple") <= 3);
+			assert!(count(&salad, "Banana") <= 3);
+		}
+		// only the coconuts can fill the salad past 6 other fruits
+		let salad = Recipe::new(12)
+			.at_least("Coconut", 2)
+			.at_most_each(3)
+			.generate(&fruits(), &mut StdRng::seed_from_u64(0))
+			.unwrap();
+		assert_eq!(count(&salad, "Coconut"), 6);
+	}
+
+	#[test]
+	fn the_same_seed_gives_the_same_salad() {
+		let recipe = Recipe::new(20).at_least("Coconut", 2);
+		let salad = |seed| {
+			recipe
+				.generate(&fruits(), &mut StdRng::seed_from_u64(seed))
+				.unwrap()
+				.into_sorted_vec()
+		};
+		assert_eq!(salad(7), salad(7));
+	}
+
+	#[test]
+	fn impossible_quotas_are_errors() {
+		let without_coconuts = &fruits()[1..];
+		let mut rng = StdRng::seed_from_u64(0);
+		let generate =
+			|recipe: Recipe, fruits: &[Fruit], rng: &mut StdRng| recipe.generate(fruits, rng).err();
+
+		assert_eq!(
+			generate(
+				Recipe::new(5).at_least("Coconut", 2),
+				without_coconuts,
+				&mut rng
+			),
+			Some(SaladError::UnknownFruit("Coconut".to_string()))
+		);
+		assert_eq!(
+			generate(
+				Recipe::new(3).at_least("Coconut", 2).at_least("Apple", 2),
+				&fruits(),
+				&mut rng
+			),
+			Some(SaladError::TooManyRequired {
+				required: 4,
+				size: 3
+			})
+		);
+		assert_eq!(
+			generate(Recipe::new(10).at_most_each(3), &fruits(), &mut rng),
+			Some(SaladError::TooFewAllowed {
+				allowed: 9,
+				size: 10
+			})
+		);
+		assert!(matches!(
+			generate(
+				Recipe::new(5).at_least("Apple", 3).at_most("Apple", 2),
+				&fruits(),
+				&mut rng
+			),
+			Some(SaladError::InvalidQuota { min: 3, max: 2, .. })
+		));
+		assert_eq!(
+			generate(Recipe::new(1), &[], &mut rng),
+			Some(SaladError::TooFewAllowed {
+				allowed: 0,
+				size: 1
+			})
+		);
+	}
+}
@@ -0,0 +1,290 @@
+//! Heap algorithms over streams
+//!
+//! Each function looks at every item once and keeps only a heap in memory, so that they
+//! also work on iterators too long to be sorted:
+//!
+//! - [top_k] / [bottom_k]: the `k` largest or smallest items, O(n log k)
+//! - [kmerge]: merges already sorted iterators into one sorted iterator
+//! - [RunningMedian]: the median of all the items seen so far
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// The `k` largest items, from the largest
+///
+/// A min-heap of the best `k` items so far: every new item only has to beat its smallest one.
+///
+/// ```rust
+/// use bin_heap::streaming::top_k;
+///
+/// assert_eq!(top_k([5, 1, 9, 3, 7], 3), vec![9, 7, 5]);
+/// assert_eq!(top_k([2, 1], 5), vec![2, 1]);
+/// ```
+pub fn top_k<T: Ord, I: IntoIterator<Item = T>>(items: I, k: usize) -> Vec<T> {
+	let items = items.into_iter();
+	let mut best = BinaryHeap::with_capacity(heap_capacity(&items, k));
+	for item in items {
+		if best.len() < k {
+			best.push(Reverse(item));
+		} else if best
+			.peek()
+			.is_some_and(|Reverse(smallest)| item > *smallest)
+		{
+			best.pop();
+			best.push(Reverse(item));
+		}
+	}
+	best.into_sorted_vec()
+		.into_iter()
+		.map(|Reverse(item)| item)
+		.collect()
+}
+
+/// The `k` smallest items, from the smallest
+///
+/// ```rust
+/// use bin_heap::streaming::bottom_k;
+///
+/// assert_eq!(bottom_k([5, 1, 9, 3, 7], 2), vec![1, 3]);
+/// ```
+pub fn bottom_k<T: Ord, I: IntoIterator<Item = T>>(items: I, k: usize) -> Vec<T> {
+	let items = items.into_iter();
+	let mut best = BinaryHeap::with_capacity(heap_capacity(&items, k));
+	for item in items {
+		if best.len() < k {
+			best.push(item);
+		} else if best.peek().is_some_and(|largest| item < *largest) {
+			best.pop();
+			best.push(item);
+		}
+	}
+	best.into_sorted_vec()
+}
+
+/// Room for `k` items, or for all the items when there are fewer: `k` can be far larger than
+/// the input
+fn heap_capacity<I: Iterator>(items: &I, k: usize) -> usize {
+	k.min(items.size_hint().0).saturating_add(1)
+}
+
+/// Merges sorted iterators into a single sorted iterator
+///
+/// Only the next item of every iterator is kept in a heap, O(log k) per item for `k`
+/// iterators. The result is only sorted if every iterator is.
+///
+/// ```rust
+/// use bin_heap::streaming::kmerge;
+///
+/// let merged: Vec<i32> = kmerge(vec![vec![1, 4, 7], vec![2, 5], vec![], vec![3, 6]]).collect();
+/// assert_eq!(merged, vec![1, 2, 3, 4, 5, 6, 7]);
+/// ```
+pub fn kmerge<I>(iterators: impl IntoIterator<Item = I>) -> KMerge<I::IntoIter>
+where
+	I: IntoIterator,
+	I::Item: Ord,
+{
+	let mut sources: Vec<I::IntoIter> =
+		iterators.into_iter().map(IntoIterator::into_iter).collect();
+	let heads = sources
+		.iter_mut()
+		.enumerate()
+		.filter_map(|(source, iterator)| Some(Reverse((iterator.next()?, source))))
+		.collect();
+	KMerge { sources, heads }
+}
+
+/// Iterator returned by [kmerge]
+#[derive(Debug, Clone)]
+pub struct KMerge<I: Iterator> {
+	sources: Vec<I>,
+	/// Next item of every iterator that is not exhausted, with the iterator index
+	heads: BinaryHeap<Reverse<(I::Item, usize)>>,
+}
+
+impl<I> Iterator for KMerge<I>
+where
+	I: Iterator,
+	I::Item: Ord,
+{
+	type Item = I::Item;
+
+	fn next(&mut self) -> Option<I::Item> {
+		let Reverse((item, source)) = self.heads.pop()?;
+		if let Some(next) = self.sources[source].next() {
+			self.heads.push(Reverse((next, source)));
+		}
+		Some(item)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.sources.iter().fold(
+			(self.heads.len(), Some(self.heads.len())),
+			|(low, high), source| {
+				let (source_low, source_high) = source.size_hint();
+				(
+					low.saturating_add(source_low),
+					high.zip(source_high).and_then(|(a, b)| a.checked_add(b)),
+				)
+			},
+		)
+	}
+}
+
+/// Median of a stream of items
+///
+/// The smaller half of the items is kept in a max-heap and the larger half in a min-heap,
+/// with at most one more item in the smaller half: the median is on top of the heaps.
+/// O(log n) per item.
+///
+/// ```rust
+/// use bin_heap::streaming::RunningMedian;
+///
+/// let mut median = RunningMedian::new();
+/// median.extend([5, 15, 1]);
+/// assert_eq!(median.median(), Some(5.0));
+/// median.push(3);
+/// assert_eq!(median.middle(), Some((&3, &5)));
+/// assert_eq!(median.median(), Some(4.0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RunningMedian<T> {
+	lower: BinaryHeap<T>,
+	upper: BinaryHeap<Reverse<T>>,
+}
+
+impl<T: Ord> RunningMedian<T> {
+	pub fn new() -> Self {
+		Self {
+			lower: BinaryHeap::new(),
+			upper: BinaryHeap::new(),
+		}
+	}
+
+	pub fn push(&mut self, item: T) {
+		match self.lower.peek() {
+			Some(largest_lower) if item > *largest_lower => self.upper.push(Reverse(item)),
+			_ => self.lower.push(item),
+		}
+		// rebalance, so that lower has as many items as upper, or one more
+		if self.lower.len() > self.upper.len() + 1 {
+			let item = self.lower.pop().expect("lower is not empty");
+			self.upper.push(Reverse(item));
+		} else if self.upper.len() > self.lower.len() {
+			let Reverse(item) = self.upper.pop().expect("upper is not empty");
+			self.lower.push(item);
+		}
+	}
+
+	pub fn len(&self) -> usize {
+		self.lower.len() + self.upper.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.lower.is_empty()
+	}
+
+	/// The two middle items, the same one twice for an odd count
+	pub fn middle(&self) -> Option<(&T, &T)> {
+		let low = self.lower.peek()?;
+		if self.lower.len() > self.upper.len() {
+			Some((low, low))
+		} else {
+			self.upper.peek().map(|Reverse(high)| (low, high))
+		}
+	}
+
+	/// The median, the mean of the two middle items for an even count
+	pub fn median(&self) -> Option<f64>
+	where
+		T: Copy + Into<f64>,
+	{
+		self.middle()
+			.map(|(&low, &high)| (low.into() + high.into()) / 2.0)
+	}
+}
+
+impl<T: Ord> Default for RunningMedian<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: Ord> Extend<T> for RunningMedian<T> {
+	fn extend<I: IntoIterator<Item = T>>(&mut self, items: I) {
+		for item in items {
+			self.push(item);
+		}
+	}
+}
+
+/// The median after every item of the stream
+///
+/// ```rust
+/// use bin_heap::streaming::running_medians;
+///
+/// let medians: Vec<f64> = running_medians([2, 8, 4, 6]).collect();
+/// assert_eq!(medians, vec![2.0, 5.0, 4.0, 5.0]);
+/// ```
+pub fn running_medians<T, I>(items: I) -> impl Iterator<Item = f64>
+where
+	T: Ord + Copy + Into<f64>,
+	I: IntoIterator<Item = T>,
+{
+	let mut median = RunningMedian::new();
+	items.into_iter().map(move |item| {
+		median.push(item);
+		median.median().expect("An item was just pushed")
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use proptest::prelude::*;
+
+	fn naive_median(items: &[i32]) -> f64 {
+		let mut sorted = items.to_vec();
+		sorted.sort();
+		let n = sorted.len();
+		(sorted[(n - 1) / 2] as f64 + sorted[n / 2] as f64) / 2.0
+	}
+
+	#[test]
+	fn huge_k_keeps_every_item() {
+		assert_eq!(top_k(vec![3, 1, 2], 1 << 40), vec![3, 2, 1]);
+		assert_eq!(bottom_k(vec![3, 1, 2], usize::MAX), vec![1, 2, 3]);
+		assert_eq!(top_k((0..5).filter(|n| n % 2 == 0), usize::MAX), vec![4, 2, 0]);
+	}
+
+	proptest! {
+		#[test]
+		fn top_and_bottom_k_match_sorting(items in prop::collection::vec(-50i32..50, 0..100), k in 0usize..20) {
+			let mut sorted = items.clone();
+			sorted.sort();
+			let bottom: Vec<i32> = sorted.iter().copied().take(k).collect();
+			let top: Vec<i32> = sorted.iter().rev().copied().take(k).collect();
+			prop_assert_eq!(bottom_k(items.clone(), k), bottom);
+			prop_assert_eq!(top_k(items, k), top);
+		}
+
+		#[test]
+		fn kmerge_matches_sorting(mut lists in prop::collection::vec(prop::collection::vec(any::<i16>(), 0..20), 0..8)) {
+			for list in lists.iter_mut() {
+				list.sort();
+			}
+			let mut expected: Vec<i16> = lists.concat();
+			expected.sort();
+			let merged = kmerge(lists.clone());
+			prop_assert_eq!(merged.size_hint(), (expected.len(), Some(expected.len())));
+			prop_assert_eq!(merged.collect::<Vec<_>>(), expected);
+		}
+
+		#[test]
+		fn running_median_matches_sorting(items in prop::collection::vec(any::<i32>(), 1..100)) {
+			let medians: Vec<f64> = running_medians(items.clone()).collect();
+			for (n, median) in medians.into_iter().enumerate() {
+				prop_assert_eq!(median, naive_median(&items[..=n]));
+			}
+		}
+	}
+}
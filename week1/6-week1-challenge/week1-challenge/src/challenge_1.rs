@@ -1,149 +1,271 @@
-use csv::{DeserializeRecordsIter, Reader};
-use serde::Deserialize;
+use crate::OutputFormat;
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::error::Error;
-use std::fmt::Display;
-use std::fs::File;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use term_charts::{sparkline, svg, Binning, BoxPlot, Histogram};
+use week1_challenge::csv_statistics::{GroupedStatistics, Selection, SkippedRow};
+use week1_challenge::timeseries::{Aggregation, Anomaly, Frequency, SeriesSet, TimeSeries, Trend};
 
-pub fn challenge_1() {
-    let data = get_data_from_csv("./data/sample-data.csv").unwrap();
-    let stats = calc_statistics(data);
+/// Statistics of the `Value` column of the sample data
+pub fn challenge_1(path: &str) -> Result<(), Box<dyn Error>> {
+    let selection = Selection {
+        columns: vec!["Value".to_string()],
+        ..Selection::default()
+    };
+    let grouped = GroupedStatistics::from_path(Path::new(path), &selection)?;
+    let stats = grouped
+        .groups
+        .first()
+        .and_then(|group| group.statistics.as_ref())
+        .ok_or("The Value column is empty")?;
 
     println!("Data Statistics: {}", stats);
+    let charts = ChartOptions {
+        chart: true,
+        ..ChartOptions::default()
+    };
+    charts.draw("Value", &grouped.groups[0].values)?;
+    print_skipped(&grouped.skipped);
+    Ok(())
 }
 
-/// This struct represents a record in the CSV file
-/// The file itself is just a random one I googled
-#[derive(Debug, Deserialize)]
-struct Record {
-    #[serde(rename = "Series_reference")]
-    reference: String,
-    #[serde(rename = "Period")]
-    period: String,
-    #[serde(rename = "Value")]
-    value: f64,
-    #[serde(rename = "STATUS")]
-    status: String,
-    #[serde(rename = "UNITS")]
-    units: String,
+/// How to draw the values of a column
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct ChartOptions {
+    /// Print a histogram and a box plot of every column
+    #[arg(long)]
+    pub chart: bool,
+    /// Histogram bins: sturges, freedman-diaconis (fd) or a number
+    #[arg(long, default_value = "sturges")]
+    pub bins: Binning,
+    /// Also write the charts as SVG files to this directory
+    #[arg(long)]
+    pub svg: Option<PathBuf>,
 }
 
-fn get_data_from_csv(path: &str) -> Result<Vec<f64>, Box<dyn Error>> {
-    // Initialize CSV reader
-    let mut reader = Reader::from_path(path).expect("Could not read file");
-    // Initialize vector to store records
-    let mut records = Vec::new();
-
-    // Deserialize the CSV records into the vector
-    let iter: DeserializeRecordsIter<File, Record> = reader.deserialize();
-
-    for record in iter {
-        if let Ok(Record { value, .. }) = record {
-            records.push(value);
+impl ChartOptions {
+    /// Prints and/or writes the histogram and box plot of the values
+    fn draw(&self, title: &str, values: &[f64]) -> Result<(), Box<dyn Error>> {
+        let (Some(histogram), Some(box_plot)) =
+            (Histogram::new(values, self.bins), BoxPlot::new(values))
+        else {
+            return Ok(());
+        };
+        if self.chart {
+            println!(
+                "\n{}\n{}\n{}",
+                title,
+                histogram.render(40),
+                box_plot.render(60)
+            );
+        }
+        if let Some(directory) = &self.svg {
+            fs::create_dir_all(directory)?;
+            let name = file_name(title);
+            fs::write(
+                directory.join(format!("{}-histogram.svg", name)),
+                svg::histogram(&histogram, title),
+            )?;
+            fs::write(
+                directory.join(format!("{}-box-plot.svg", name)),
+                svg::box_plot(&box_plot, title),
+            )?;
         }
+        Ok(())
     }
-
-    Ok(records)
 }
 
-/// This struct holds the statistics of the data
-///
-/// It has the following fields:
-/// - min: The minimum value in the data
-/// - max: The maximum value in the data
-/// - mean: The mean of the data
-/// - variance: The variance of the data
-/// - std_dev: The standard deviation of the data
-struct Statistics {
-    min: f64,
-    max: f64,
-    mean: f64,
-    variance: f64,
-    std_dev: f64,
+/// A title usable as a file name: `BDCQ.SF1AA2CA / Value` -> `BDCQ.SF1AA2CA-Value`
+fn file_name(title: &str) -> String {
+    title
+        .split(|c: char| !(c.is_alphanumeric() || c == '.' || c == '_'))
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
 }
 
-impl Display for Statistics {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "\n| {:<10} | {:<20} |", "Field", "Value")?;
-        writeln!(f, "|------------|----------------------|")?;
-        writeln!(f, "| {:<10} | {:<20} |", "Min", self.min)?;
-        writeln!(f, "| {:<10} | {:<20} |", "Max", self.max)?;
-        writeln!(f, "| {:<10} | {:<20} |", "Mean", self.mean)?;
-        writeln!(f, "| {:<10} | {:<20} |", "Variance", self.variance)?;
-        writeln!(f, "| {:<10} | {:<20} |", "Std Dev", self.std_dev);
-        writeln!(f, "|------------|----------------------|")
+/// Statistics of any CSV file, per group, in the requested format
+pub fn statistics(
+    path: &str,
+    selection: &Selection,
+    format: TableFormat,
+    charts: &ChartOptions,
+) -> Result<(), Box<dyn Error>> {
+    let grouped = GroupedStatistics::from_path(Path::new(path), selection)?;
+    match format {
+        TableFormat::Markdown => {
+            print!("{}", grouped.to_markdown());
+            for group in &grouped.groups {
+                let mut title = group.group.clone();
+                title.push(group.column.clone());
+                charts.draw(&title.join(" / "), &group.values)?;
+            }
+            print_skipped(&grouped.skipped);
+        }
+        TableFormat::Csv => grouped.write_csv(std::io::stdout())?,
+        TableFormat::Json => println!("{}", grouped.to_json()?),
     }
+    Ok(())
 }
 
-fn calc_statistics(data: Vec<f64>) -> Statistics {
-    let min = calc_min(&data);
-    let max = calc_max(&data);
-    let mean = calc_mean(&data);
-    let variance = calc_variance(&data);
-    let std_dev = calc_std_dev(&data);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum TableFormat {
+    Markdown,
+    Csv,
+    Json,
+}
 
-    Statistics {
-        min,
-        max,
-        mean,
-        variance,
-        std_dev,
+/// Prints how many rows were skipped, grouped by column and reason
+fn print_skipped(skipped: &[SkippedRow]) {
+    if skipped.is_empty() {
+        return;
+    }
+    let mut reasons: BTreeMap<(Option<&str>, &str), Vec<u64>> = BTreeMap::new();
+    for row in skipped {
+        reasons
+            .entry((row.column.as_deref(), &row.reason))
+            .or_default()
+            .push(row.line);
     }
-}
 
-/// Calculate the minimum value in the data
-///
-/// ## How it is done in Rust
-///
-/// The `calc_min` function takes a reference to a vector of f64 values
-/// and returns the minimum value in the vector.
-///
-/// It uses the `iter` method to get an iterator over the vector,
-/// and the `min_by` method to get the minimum value.
-/// the `unwrap` method is used to get the value from the `Option` returned by `min_by`.
-fn calc_min(data: &Vec<f64>) -> f64 {
-    *data
-        .iter()
-        .min_by(|a, b| a.partial_cmp(b).unwrap())
-        .unwrap()
+    println!("\nSkipped {} cells:", skipped.len());
+    for ((column, reason), lines) in reasons {
+        println!(
+            "- {}{} ({} times, first at line {})",
+            column.map_or(String::new(), |c| format!("{}: ", c)),
+            reason,
+            lines.len(),
+            lines[0]
+        );
+    }
 }
 
-fn calc_max(data: &Vec<f64>) -> f64 {
-    *data
-        .iter()
-        .max_by(|a, b| a.partial_cmp(b).unwrap())
-        .unwrap()
+/// Options of the time series analysis
+#[derive(Debug, Clone, clap::Args)]
+pub struct TimeSeriesOptions {
+    /// Analyse only these series references, can be repeated
+    #[arg(short, long)]
+    pub series: Vec<String>,
+    #[arg(long, value_enum, default_value_t = Frequency::Quarterly)]
+    pub frequency: Frequency,
+    /// How the values of a period are combined when resampling
+    #[arg(long, value_enum, default_value_t = Aggregation::Mean)]
+    pub aggregation: Aggregation,
+    /// Number of periods of the rolling mean and standard deviation
+    #[arg(long, default_value_t = 4)]
+    pub window: usize,
+    /// Flag the values whose z-score is at least this far from 0
+    #[arg(long, default_value_t = 3.0)]
+    pub threshold: f64,
+    /// Also write a line chart of every series as SVG to this directory
+    #[arg(long)]
+    pub svg: Option<PathBuf>,
 }
 
-fn calc_mean(data: &Vec<f64>) -> f64 {
-    data.iter().sum::<f64>() / data.len() as f64
+/// Analysis of the time series of one series reference
+#[derive(Debug, Serialize)]
+struct SeriesReport {
+    reference: String,
+    series: TimeSeries,
+    rolling_mean: TimeSeries,
+    rolling_std: TimeSeries,
+    year_over_year: TimeSeries,
+    trend: Option<Trend>,
+    anomalies: Vec<Anomaly>,
 }
 
-/// Calculate the variance of the data
-///
-/// The variance of a set of data is the average of the squared differences of each value from the mean.
-/// or in formula form:
-///
-/// `variance = Σ(x - mean)^2 / n`
-///
-/// ## How it is done in Rust
-///
-/// The `calc_variance` function takes a reference to a vector of f64 values
-/// and returns the variance of the data.
-///
-/// It calculates the mean of the data using the `calc_mean` function,
-/// then uses the `map` method to get the squared difference of each value from the mean.
-/// The `sum` method is used to get the sum of the squared differences.
-/// Finally, the sum is divided by the length of the data to get the variance.
-///
-/// ## See
-/// [calc_mean](fn.calc_mean.html)
-fn calc_variance(data: &Vec<f64>) -> f64 {
-    let n = data.len() as f64;
-    let mean = calc_mean(data);
-    data.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n
+impl SeriesReport {
+    fn new(reference: String, series: &TimeSeries, options: &TimeSeriesOptions) -> Self {
+        let series = series.resample(options.frequency, options.aggregation);
+        Self {
+            reference,
+            rolling_mean: series.rolling_mean(options.window),
+            rolling_std: series.rolling_std(options.window),
+            year_over_year: series.year_over_year(),
+            trend: series.linear_trend(),
+            anomalies: series.anomalies(options.threshold),
+            series,
+        }
+    }
+
+    fn print(&self) {
+        let (Some((first, _)), Some((last, value))) =
+            (self.series.points.first(), self.series.points.last())
+        else {
+            return;
+        };
+        println!(
+            "## {} ({} periods, {} to {})",
+            self.reference,
+            self.series.len(),
+            first,
+            last
+        );
+        let values: Vec<f64> = self.series.points.iter().map(|(_, value)| *value).collect();
+        println!("- {}", sparkline(&values));
+        println!("- latest value: {}", value);
+        if let Some((period, mean)) = self.rolling_mean.points.last() {
+            let std = self.rolling_std.get(*period).unwrap_or_default();
+            println!("- rolling mean: {:.3} (std {:.3}) at {}", mean, std, period);
+        }
+        if let Some((period, change)) = self.year_over_year.points.last() {
+            println!("- year over year: {:+.2}% at {}", change, period);
+        }
+        if let Some(trend) = self.trend {
+            println!(
+                "- trend: {:+.3} per year (R² {:.2})",
+                trend.slope_per_year, trend.r_squared
+            );
+        }
+        for anomaly in &self.anomalies {
+            println!(
+                "- anomaly: {} at {} (z-score {:+.2})",
+                anomaly.value, anomaly.period, anomaly.z_score
+            );
+        }
+    }
 }
 
-/// Calculate the standard deviation of the data
-fn calc_std_dev(data: &Vec<f64>) -> f64 {
-    calc_variance(data).sqrt()
+/// Time series analysis of the `Period` and `Value` columns, per `Series_reference`
+pub fn time_series(
+    path: &str,
+    options: &TimeSeriesOptions,
+    output: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    let file = File::open(path)?;
+    let set = SeriesSet::from_reader(file, "Series_reference", "Period", "Value")?;
+    let reports: Vec<SeriesReport> = set
+        .series
+        .iter()
+        .filter(|(reference, _)| options.series.is_empty() || options.series.contains(reference))
+        .map(|(reference, series)| SeriesReport::new(reference.clone(), series, options))
+        .collect();
+
+    if let Some(directory) = &options.svg {
+        fs::create_dir_all(directory)?;
+        for report in &reports {
+            let values: Vec<f64> = report
+                .series
+                .points
+                .iter()
+                .map(|(_, value)| *value)
+                .collect();
+            fs::write(
+                directory.join(format!("{}.svg", file_name(&report.reference))),
+                svg::sparkline(&values, &report.reference),
+            )?;
+        }
+    }
+    match output {
+        OutputFormat::Text => {
+            for report in &reports {
+                report.print();
+            }
+            print_skipped(&set.skipped);
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&reports)?),
+    }
+    Ok(())
 }
@@ -5,9 +5,9 @@ use std::io::Error as IoError;
 // Very rough blacklist, to be improved
 const MIN_WORD_LENGTH: usize = 3;
 
-pub fn challenge_2() {
+pub fn challenge_2(path: &str) {
     println!("Challenge 2");
-    let contents = get_file_content("data/poe_holmes.txt").expect("Could not read file");
+    let contents = get_file_content(path).expect("Could not read file");
 
     let words = get_words(&contents);
     let cleaned_words = clean_up(words);
@@ -22,9 +22,9 @@ pub fn challenge_2() {
     }
 }
 
-pub fn challenge_3() {
+pub fn challenge_3(path: &str) {
     println!("Challenge 3 -- Frequencies with LinkedList");
-    let contents = get_file_content("data/poe_holmes.txt").expect("Could not read file");
+    let contents = get_file_content(path).expect("Could not read file");
 
     let words = get_words(&contents);
     let cleaned_words = clean_up(words);
@@ -1,43 +1,140 @@
-use petgraph::algo::connected_components;
-use petgraph::dot::Dot;
-use petgraph::graph::DiGraph;
-
-pub fn challenge_4() {
-    println!("Challenge 4: Check if graph is fully connected");
-    let g = make_graph();
-    println!("Graph {:?}", Dot::new(&g));
-
-    if is_fully_connected(&g) {
-        println!("Graph is fully connected");
-    } else {
-        println!("Graph is not fully connected");
-    }
+use crate::{render_graph, OutputFormat};
+use graph_utils::render::Colouring;
+use petgraph::graph::NodeIndex;
+use serde::Serialize;
+use std::error::Error;
+use std::path::Path;
+use week1_challenge::connectivity::Connectivity;
+use week1_challenge::graph_io::GraphFile;
+
+/// Every connectivity notion of the graph, with nodes referred to by name
+#[derive(Debug, Serialize)]
+struct ConnectivityReport {
+    directed: bool,
+    weakly_connected: bool,
+    weak_components: Vec<Vec<String>>,
+    strongly_connected: bool,
+    strong_components: Vec<Vec<String>>,
+    complete: bool,
+    articulation_points: Vec<String>,
+    bridges: Vec<Bridge>,
+}
+
+#[derive(Debug, Serialize)]
+struct Bridge {
+    source: String,
+    target: String,
+    label: String,
 }
 
+impl ConnectivityReport {
+    fn new(file: &GraphFile) -> Self {
+        let g = &file.graph;
+        let connectivity = if file.directed {
+            Connectivity::analyse(g)
+        } else {
+            Connectivity::analyse(&file.undirected())
+        };
+        let names = |nodes: &[NodeIndex]| -> Vec<String> {
+            let mut names: Vec<String> = nodes.iter().map(|node| g[*node].clone()).collect();
+            names.sort();
+            names
+        };
+        let bridges = connectivity
+            .bridges
+            .iter()
+            .map(|edge| {
+                let (source, target) = g.edge_endpoints(*edge).expect("Bridges are edges of g");
+                Bridge {
+                    source: g[source].clone(),
+                    target: g[target].clone(),
+                    label: g[*edge].clone(),
+                }
+            })
+            .collect();
+
+        Self {
+            directed: file.directed,
+            weakly_connected: connectivity.is_weakly_connected(),
+            weak_components: connectivity
+                .weak_components
+                .iter()
+                .map(|c| names(c))
+                .collect(),
+            strongly_connected: connectivity.is_strongly_connected(),
+            strong_components: connectivity
+                .strong_components
+                .iter()
+                .map(|c| names(c))
+                .collect(),
+            complete: connectivity.complete,
+            articulation_points: names(&connectivity.articulation_points),
+            bridges,
+        }
+    }
 
-fn make_graph() -> DiGraph<&'static str, &'static str>{
-    let mut graph = DiGraph::new();
-    let a = graph.add_node("Node A");
-    let b = graph.add_node("Node B");
-    let c = graph.add_node("Node C");
-    graph.add_edge(a, b, "Rel 1");
-    graph.add_edge(b, a, "Rel 2");
-    graph.add_edge(c, c, "Rel 3");
+    fn print(&self) {
+        let yes_no = |verdict: bool| if verdict { "yes" } else { "no" };
 
-    graph
+        if self.directed {
+            println!(
+                "Weakly connected (edge directions ignored): {} ({} components)",
+                yes_no(self.weakly_connected),
+                self.weak_components.len()
+            );
+            println!(
+                "Strongly connected (every node reaches every other following edge directions): {} ({} components)",
+                yes_no(self.strongly_connected),
+                self.strong_components.len()
+            );
+            for component in &self.strong_components {
+                println!("  {:?}", component);
+            }
+            println!(
+                "Complete (every pair of distinct nodes linked in both directions): {}",
+                yes_no(self.complete)
+            );
+        } else {
+            println!(
+                "Connected: {} ({} components)",
+                yes_no(self.weakly_connected),
+                self.weak_components.len()
+            );
+            for component in &self.weak_components {
+                println!("  {:?}", component);
+            }
+            println!(
+                "Complete (every pair of distinct nodes linked): {}",
+                yes_no(self.complete)
+            );
+        }
+        println!(
+            "Articulation points (edge directions ignored): {:?}",
+            self.articulation_points
+        );
+        println!("Bridges (edge directions ignored):");
+        for bridge in &self.bridges {
+            println!("  {} - {} {}", bridge.source, bridge.target, bridge.label);
+        }
+    }
 }
 
-/// Check if the graph is fully connected
-///
-/// The [connected_components](https://docs.rs/petgraph/latest/petgraph/algo/fn.connected_components.html) function
-/// from the petgraph crate is used to determine the number of connected components in the graph.
-/// If the number of connected components is 1, then the graph is fully connected.
-///
-/// # Arguments
-/// * `graph` - The graph to check
-///
-/// # Returns
-/// * `true` if the graph is fully connected, `false` otherwise
-fn is_fully_connected(graph: &DiGraph<&'static str, &'static str>) -> bool {
-    connected_components(graph) == 1
-}
\ No newline at end of file
+pub fn challenge_4(
+    file: &GraphFile,
+    output: OutputFormat,
+    render: Option<&Path>,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(path) = render {
+        render_graph(file, Colouring::Components, path)?;
+    }
+
+    let report = ConnectivityReport::new(file);
+    match output {
+        OutputFormat::Text => {
+            println!("Challenge 4: Check how the graph is connected");
+            report.print();
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+    Ok(())
+}
@@ -1,169 +1,103 @@
-use petgraph::graph::{Graph, NodeIndex};
-use petgraph::visit::{DfsPostOrder, EdgeRef};
-use petgraph::Directed;
-use std::collections::HashSet;
-use petgraph::dot::Dot;
-
-pub fn challenge_5() {
-    println!("Challenge 5 - Kosaraju's Algorithm");
-    let graph = make_graph();
-
-    println!("Graph: {:?}", Dot::new(&graph));
-
-    let components = kosaraju_scc(&graph);
-
-    for (i, component) in components.iter().enumerate() {
-        println!("Component {}: {:?}", i, component);
-    }
+use crate::{render_graph, OutputFormat};
+use graph_utils::render::Colouring;
+use petgraph::graph::NodeIndex;
+use serde::Serialize;
+use std::error::Error;
+use std::path::Path;
+use week1_challenge::graph_io::GraphFile;
+use week1_challenge::scc::{kosaraju_scc, tarjan_scc, Condensation};
+
+/// Strongly connected components, with nodes referred to by name
+#[derive(Debug, Serialize)]
+struct SccReport {
+    /// Components in topological order: no edge goes back to an earlier component
+    components: Vec<Vec<String>>,
+    /// Components containing a cycle: more than one node, or a node depending on itself
+    cycles: Vec<Vec<String>>,
 }
 
-/// Create a graph for the challenge
-///
-/// The graph is a directed graph with the following nodes and edges (dot notation)
-///
-/// ```dot
-/// digraph {
-//     0 [ label = "1" ]
-//     1 [ label = "2" ]
-//     2 [ label = "3" ]
-//     3 [ label = "4" ]
-//     4 [ label = "5" ]
-//     5 [ label = "6" ]
-//     6 [ label = "7" ]
-//     0 -> 1 [ label = "1-2" ]
-//     1 -> 2 [ label = "2-3" ]
-//     2 -> 0 [ label = "3-1" ]
-//     1 -> 3 [ label = "2-4" ]
-//     3 -> 3 [ label = "4-4" ]
-//     4 -> 3 [ label = "4-5" ]
-//     4 -> 5 [ label = "5-6" ]
-//     5 -> 6 [ label = "6-4" ]
-//     6 -> 4 [ label = "7-5" ]
-// }
-/// ```
-fn make_graph() -> Graph<usize, &'static str, Directed> {
-    let mut graph = Graph::<usize, &str, Directed>::new();
-    let a = graph.add_node(1);
-    let b = graph.add_node(2);
-    let c = graph.add_node(3);
-    let d = graph.add_node(4);
-    let e = graph.add_node(5);
-    let f = graph.add_node(6);
-    let g = graph.add_node(7);
-
-    graph.add_edge(a, b, "1-2");
-    graph.add_edge(b, c, "2-3");
-    graph.add_edge(c, a, "3-1");
-
-    graph.add_edge(b,d, "2-4");
-    graph.add_edge(d,d, "4-4");
-
-    graph.add_edge(e, d, "4-5");
-    graph.add_edge(e, f, "5-6");
-    graph.add_edge(f, g, "6-4");
-    graph.add_edge(g, e, "7-5");
-
-
-    graph
-}
-
-fn kosaraju_scc(graph: &Graph<usize, &str, Directed>) -> Vec<Vec<usize>> {
-    let mut stack = Vec::new();
-    let mut discovered = HashSet::new();
-
-    // First pass: record the finish times of each node in a stack
-    for node in graph.node_indices() {
-        if !discovered.contains(&node) {
-            dfs_first_walk(node, graph, &mut discovered, &mut stack);
+impl SccReport {
+    fn new(file: &GraphFile) -> Self {
+        let g = &file.graph;
+        let labels = |nodes: &[NodeIndex]| -> Vec<String> {
+            let mut labels: Vec<String> = nodes.iter().map(|node| g[*node].clone()).collect();
+            labels.sort();
+            labels
+        };
+
+        let condensation = Condensation::new(g, kosaraju_scc(g));
+        let components: Vec<&Vec<NodeIndex>> = condensation
+            .topological_order()
+            .into_iter()
+            .map(|component| &condensation.graph[component])
+            .collect();
+        let cycles = components
+            .iter()
+            .filter(|members| members.len() > 1 || g.contains_edge(members[0], members[0]))
+            .map(|members| labels(members))
+            .collect();
+
+        Self {
+            components: components.iter().map(|members| labels(members)).collect(),
+            cycles,
         }
     }
 
-    // Transpose the graph
-    let transposed_graph = transpose_graph(graph);
-
-    // clear "discovered nodes", we need to run again DFS
-    discovered.clear();
-
-    let mut components = Vec::new();
+    fn print(&self, file: &GraphFile) {
+        for (i, component) in self.components.iter().enumerate() {
+            println!("Component {}: {:?}", i, component);
+        }
+        // the components as sorted sets, in any order
+        let mut kosaraju = self.components.clone();
+        kosaraju.sort();
+        let mut tarjan: Vec<Vec<String>> = tarjan_scc(&file.graph)
+            .iter()
+            .map(|nodes| {
+                let mut labels: Vec<String> =
+                    nodes.iter().map(|node| file.graph[*node].clone()).collect();
+                labels.sort();
+                labels
+            })
+            .collect();
+        tarjan.sort();
+        if tarjan == kosaraju {
+            println!("Tarjan finds the same {} components", tarjan.len());
+        } else {
+            println!("Tarjan finds different components: {:?}", tarjan);
+        }
 
-    // Second pass: collect strongly connected components
-    while let Some(node) = stack.pop() {
-        if !discovered.contains(&NodeIndex::new(node)) {
-            let mut component = Vec::new();
-            dfs_collect(NodeIndex::new(node), &transposed_graph, &mut discovered, &mut component);
-            components.push(component);
+        if self.cycles.is_empty() {
+            println!("No cycles: the graph is a DAG");
+        } else {
+            println!("Cycles:");
+            for cycle in &self.cycles {
+                println!("  {:?}", cycle);
+            }
         }
     }
-
-    components
 }
 
-/// Transpose the graph
-///
-/// # Arguments
-/// * `graph` - The graph to transpose
-///
-/// # Returns
-///
-/// The transposed graph. The nodes are the same,
-/// but pay attention to the direction of the edges, they are reversed.
-///
-fn transpose_graph<'a>(graph: &Graph<usize, &'a str>) -> Graph<usize, &'a str> {
-    let mut transposed_graph = Graph::<usize, &str, Directed>::new();
-    for node in graph.node_indices() {
-        transposed_graph.add_node(node.index());
-    }
-    for edge in graph.edge_references() {
-        transposed_graph.add_edge(edge.target(), edge.source(), *edge.weight());
+/// Prints the strongly connected components of a directed graph,
+/// returns whether the graph has cycles.
+pub fn challenge_5(
+    file: &GraphFile,
+    output: OutputFormat,
+    render: Option<&Path>,
+) -> Result<bool, Box<dyn Error>> {
+    if !file.directed {
+        return Err("Strongly connected components need a directed graph".into());
     }
-    transposed_graph
-}
-
-/// Perform a depth-first search on the graph and record the finish times of each node in a stack
-///
-/// # Arguments
-/// * `node` - The starting node for the depth-first search
-/// * `graph` - The graph to search
-/// * `visited` - A set of visited nodes
-/// * `stack` - A stack to record the finish times of each node
-///
-/// # Returns
-///
-/// The finish times of each node are recorded in the `finish_time` stack
-///
-/// # How it works
-///
-/// The DFS iterates over the nodes and if a node has not been visited, it is added to the visited set
-/// and its finish time is recorded in the stack
-fn dfs_first_walk(
-    node: NodeIndex,
-    graph: &Graph<usize, &str, Directed>,
-    visited: &mut HashSet<NodeIndex>,
-    stack: &mut Vec<usize>,
-) {
-    let mut dfs = DfsPostOrder::new(graph, node);
-    while let Some(node) = dfs.next(graph) {
-        if visited.insert(node) {
-            stack.push(node.index());
-        }
+    if let Some(path) = render {
+        render_graph(file, Colouring::Communities, path)?;
     }
-}
 
-fn dfs_collect(
-    node: NodeIndex,
-    graph: &Graph<usize, &str, Directed>,
-    visited: &mut HashSet<NodeIndex>,
-    component: &mut Vec<usize>,
-) {
-    let mut dfs = DfsPostOrder::new(graph, node);
-    while let Some(node) = dfs.next(graph) {
-        if visited.insert(node) {
-            component.push(node.index());
-            dfs_collect(node, graph, visited, component);
+    let report = SccReport::new(file);
+    match output {
+        OutputFormat::Text => {
+            println!("Challenge 5 - Strongly connected components (in topological order)");
+            report.print(file);
         }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
     }
-}
-
-fn main() {
-    challenge_5();
+    Ok(!report.cycles.is_empty())
 }
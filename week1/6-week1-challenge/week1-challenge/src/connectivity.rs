@@ -0,0 +1,243 @@
+//! Connectivity analysis of directed and undirected graphs
+//!
+//! "Connected" means different things for directed graphs, so every notion is reported separately:
+//!
+//! - **weakly connected**: every node reaches every other one when edge directions are ignored,
+//! - **strongly connected**: every node reaches every other one following edge directions,
+//! - **complete**: every pair of distinct nodes is linked by an edge (both ways, if directed).
+//!
+//! For undirected graphs weak and strong connectivity are the same thing.
+//! Articulation points and bridges are always computed on the undirected view of the graph.
+
+use crate::scc::tarjan_scc;
+use graph_utils::metrics::components;
+use petgraph::graph::{EdgeIndex, Graph, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::EdgeType;
+use std::collections::HashSet;
+
+/// Result of [Connectivity::analyse]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Connectivity {
+    pub directed: bool,
+    /// Components of the graph when edge directions are ignored
+    pub weak_components: Vec<Vec<NodeIndex>>,
+    /// Components whose nodes reach each other following edge directions
+    pub strong_components: Vec<Vec<NodeIndex>>,
+    /// Nodes whose removal disconnects their component (undirected view)
+    pub articulation_points: Vec<NodeIndex>,
+    /// Edges whose removal disconnects their component (undirected view)
+    pub bridges: Vec<EdgeIndex>,
+    /// Every pair of distinct nodes is adjacent (in both directions, for directed graphs)
+    pub complete: bool,
+}
+
+impl Connectivity {
+    /// Runs every analysis on the graph
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use petgraph::graph::DiGraph;
+    /// use week1_challenge::connectivity::Connectivity;
+    ///
+    /// // a -> b -> c: connected if you ignore directions, but c cannot reach a
+    /// let graph = DiGraph::<(), ()>::from_edges([(0, 1), (1, 2)]);
+    /// let connectivity = Connectivity::analyse(&graph);
+    ///
+    /// assert!(connectivity.is_weakly_connected());
+    /// assert!(!connectivity.is_strongly_connected());
+    /// assert_eq!(connectivity.articulation_points.len(), 1);
+    /// assert_eq!(connectivity.bridges.len(), 2);
+    /// ```
+    pub fn analyse<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Self {
+        let (articulation_points, bridges) = articulation_points_and_bridges(graph);
+        Self {
+            directed: graph.is_directed(),
+            weak_components: weak_components(graph),
+            strong_components: tarjan_scc(graph),
+            articulation_points,
+            bridges,
+            complete: is_complete(graph),
+        }
+    }
+
+    /// Whether there is at most one weak component. The empty graph is connected.
+    pub fn is_weakly_connected(&self) -> bool {
+        self.weak_components.len() <= 1
+    }
+
+    /// Whether there is at most one strong component. The empty graph is connected.
+    pub fn is_strongly_connected(&self) -> bool {
+        self.strong_components.len() <= 1
+    }
+}
+
+/// Components of the graph ignoring edge directions, nodes sorted by index
+pub fn weak_components<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Vec<Vec<NodeIndex>> {
+    let labels = components(graph);
+    let mut groups = vec![Vec::new(); labels.iter().max().map_or(0, |max| max + 1)];
+    for (index, label) in labels.into_iter().enumerate() {
+        groups[label].push(NodeIndex::new(index));
+    }
+    groups
+}
+
+/// Whether every pair of distinct nodes is adjacent. Self-loops and parallel edges are ignored.
+pub fn is_complete<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> bool {
+    let n = graph.node_count();
+    let mut pairs = HashSet::new();
+    for edge in graph.edge_references() {
+        let (a, b) = (edge.source(), edge.target());
+        if a == b {
+            continue;
+        }
+        if graph.is_directed() {
+            pairs.insert((a, b));
+        } else {
+            pairs.insert((a.min(b), a.max(b)));
+        }
+    }
+    let required = if graph.is_directed() {
+        n * n.saturating_sub(1)
+    } else {
+        n * n.saturating_sub(1) / 2
+    };
+    pairs.len() == required
+}
+
+/// Articulation points and bridges of the undirected view of the graph.
+///
+/// An iterative depth-first search numbers the nodes in discovery order and computes `low`,
+/// the smallest number reachable from the subtree of a node using at most one back edge.
+/// A tree edge `u - v` is a bridge when `low[v] > number[u]`, and `u` is an articulation point
+/// when `low[v] >= number[u]` (the root only when it has more than one child).
+/// Parallel edges are handled by skipping only the very edge used to reach a node.
+pub fn articulation_points_and_bridges<N, E, Ty: EdgeType>(
+    graph: &Graph<N, E, Ty>,
+) -> (Vec<NodeIndex>, Vec<EdgeIndex>) {
+    let n = graph.node_count();
+    let mut neighbours: Vec<Vec<(EdgeIndex, NodeIndex)>> = vec![Vec::new(); n];
+    for edge in graph.edge_references() {
+        let (a, b) = (edge.source(), edge.target());
+        if a != b {
+            neighbours[a.index()].push((edge.id(), b));
+            neighbours[b.index()].push((edge.id(), a));
+        }
+    }
+
+    let mut number: Vec<Option<usize>> = vec![None; n];
+    let mut low = vec![0; n];
+    let mut is_articulation = vec![false; n];
+    let mut bridges = Vec::new();
+    let mut next_number = 0;
+
+    for root in graph.node_indices() {
+        if number[root.index()].is_some() {
+            continue;
+        }
+        number[root.index()] = Some(next_number);
+        low[root.index()] = next_number;
+        next_number += 1;
+        let mut root_children = 0;
+
+        // (node, edge used to reach it, position in its neighbour list)
+        let mut stack: Vec<(NodeIndex, Option<EdgeIndex>, usize)> = vec![(root, None, 0)];
+        while let Some((node, via, position)) = stack.last_mut() {
+            let (node, via) = (*node, *via);
+            if let Some(&(edge, next)) = neighbours[node.index()].get(*position) {
+                *position += 1;
+                if Some(edge) == via {
+                    continue;
+                }
+                match number[next.index()] {
+                    Some(next_number) => {
+                        low[node.index()] = low[node.index()].min(next_number);
+                    }
+                    None => {
+                        number[next.index()] = Some(next_number);
+                        low[next.index()] = next_number;
+                        next_number += 1;
+                        if node == root {
+                            root_children += 1;
+                        }
+                        stack.push((next, Some(edge), 0));
+                    }
+                }
+                continue;
+            }
+
+            stack.pop();
+            let (Some(edge), Some(&(parent, _, _))) = (via, stack.last()) else {
+                continue;
+            };
+            low[parent.index()] = low[parent.index()].min(low[node.index()]);
+            let parent_number = number[parent.index()].unwrap_or_default();
+            if low[node.index()] > parent_number {
+                bridges.push(edge);
+            }
+            if parent != root && low[node.index()] >= parent_number {
+                is_articulation[parent.index()] = true;
+            }
+        }
+
+        if root_children > 1 {
+            is_articulation[root.index()] = true;
+        }
+    }
+
+    let articulation_points = graph
+        .node_indices()
+        .filter(|node| is_articulation[node.index()])
+        .collect();
+    bridges.sort();
+    (articulation_points, bridges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::graph::{DiGraph, UnGraph};
+
+    #[test]
+    fn cycle_is_strongly_connected_and_has_no_bridges() {
+        let graph = DiGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0)]);
+        let connectivity = Connectivity::analyse(&graph);
+
+        assert!(connectivity.is_strongly_connected());
+        assert!(connectivity.articulation_points.is_empty());
+        assert!(connectivity.bridges.is_empty());
+        assert!(!connectivity.complete);
+    }
+
+    #[test]
+    fn two_triangles_joined_by_a_bridge() {
+        let graph =
+            UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3), (2, 3)]);
+        let connectivity = Connectivity::analyse(&graph);
+
+        assert!(connectivity.is_weakly_connected());
+        assert!(connectivity.is_strongly_connected());
+        assert_eq!(
+            connectivity.articulation_points,
+            vec![NodeIndex::new(2), NodeIndex::new(3)]
+        );
+        assert_eq!(connectivity.bridges, vec![EdgeIndex::new(6)]);
+    }
+
+    #[test]
+    fn parallel_edges_are_not_bridges() {
+        let graph = UnGraph::<(), ()>::from_edges([(0, 1), (0, 1)]);
+        let connectivity = Connectivity::analyse(&graph);
+        assert!(connectivity.bridges.is_empty());
+        assert!(connectivity.complete);
+    }
+
+    #[test]
+    fn completeness_needs_both_directions() {
+        let one_way = DiGraph::<(), ()>::from_edges([(0, 1)]);
+        let both_ways = DiGraph::<(), ()>::from_edges([(0, 1), (1, 0)]);
+        assert!(!is_complete(&one_way));
+        assert!(is_complete(&both_ways));
+    }
+}
@@ -0,0 +1,385 @@
+//! [Statistics] of the numeric columns of any CSV file, optionally per group
+//!
+//! Columns are picked by header name. Rows are grouped by the values of one or more
+//! categorical columns, e.g. `Series_reference` and `UNITS`, and every selected column is
+//! described separately for every group.
+//! Cells that are empty or not a number are left out and reported as [SkippedRow]s.
+
+use crate::statistics::{NanPolicy, Statistics, StatisticsError};
+use csv::{Reader, StringRecord};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum CsvStatisticsError {
+    Csv(csv::Error),
+    /// A selected or grouping column is not in the header
+    UnknownColumn(String),
+    Statistics {
+        column: String,
+        group: Vec<String>,
+        error: StatisticsError,
+    },
+}
+
+impl Display for CsvStatisticsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvStatisticsError::Csv(e) => write!(f, "Could not read the CSV file: {}", e),
+            CsvStatisticsError::UnknownColumn(name) => write!(f, "No column named {}", name),
+            CsvStatisticsError::Statistics {
+                column,
+                group,
+                error,
+            } => write!(f, "{} of group {:?}: {}", column, group, error),
+        }
+    }
+}
+
+impl std::error::Error for CsvStatisticsError {}
+
+impl From<csv::Error> for CsvStatisticsError {
+    fn from(e: csv::Error) -> Self {
+        CsvStatisticsError::Csv(e)
+    }
+}
+
+/// A cell (or a whole row, when `column` is `None`) that could not be read
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SkippedRow {
+    /// Line in the file, the header is line 1
+    pub line: u64,
+    pub column: Option<String>,
+    pub reason: String,
+}
+
+/// Statistics of one column within one group
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GroupStatistics {
+    /// Values of the grouping columns, empty without grouping
+    pub group: Vec<String>,
+    pub column: String,
+    /// `None` when the group has no value in this column
+    pub statistics: Option<Statistics>,
+    /// The values the statistics are computed on, e.g. to draw them
+    #[serde(skip)]
+    pub values: Vec<f64>,
+}
+
+/// Which columns to describe and how to group the rows
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    pub columns: Vec<String>,
+    pub group_by: Vec<String>,
+    pub nan_policy: NanPolicy,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GroupedStatistics {
+    pub group_by: Vec<String>,
+    /// Sorted by group, then in the order the columns were selected
+    pub groups: Vec<GroupStatistics>,
+    pub skipped: Vec<SkippedRow>,
+}
+
+impl GroupedStatistics {
+    pub fn from_path(path: &Path, selection: &Selection) -> Result<Self, CsvStatisticsError> {
+        let file = File::open(path).map_err(csv::Error::from)?;
+        Self::from_reader(file, selection)
+    }
+
+    /// Reads a CSV with a header row
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use week1_challenge::csv_statistics::{GroupedStatistics, Selection};
+    ///
+    /// let csv = "UNITS,Value\nDollars,1\nDollars,3\nPercent,0.5\nPercent,\n";
+    /// let selection = Selection {
+    ///     columns: vec!["Value".to_string()],
+    ///     group_by: vec!["UNITS".to_string()],
+    ///     ..Selection::default()
+    /// };
+    /// let grouped = GroupedStatistics::from_reader(csv.as_bytes(), &selection).unwrap();
+    ///
+    /// assert_eq!(grouped.groups.len(), 2);
+    /// assert_eq!(grouped.groups[0].group, vec!["Dollars"]);
+    /// assert_eq!(grouped.groups[0].statistics.as_ref().unwrap().mean, 2.0);
+    /// assert_eq!(grouped.skipped[0].line, 5);
+    /// ```
+    pub fn from_reader<R: Read>(
+        reader: R,
+        selection: &Selection,
+    ) -> Result<Self, CsvStatisticsError> {
+        let mut reader = Reader::from_reader(reader);
+        let headers = reader.headers()?.clone();
+        let position = |name: &String| {
+            headers
+                .iter()
+                .position(|header| header == name)
+                .ok_or_else(|| CsvStatisticsError::UnknownColumn(name.clone()))
+        };
+        let columns = selection
+            .columns
+            .iter()
+            .map(position)
+            .collect::<Result<Vec<_>, _>>()?;
+        let group_by = selection
+            .group_by
+            .iter()
+            .map(position)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // values of every selected column, per group
+        let mut values: BTreeMap<Vec<String>, Vec<Vec<f64>>> = BTreeMap::new();
+        let mut skipped = Vec::new();
+        let mut record = StringRecord::new();
+        let mut line = 1;
+        loop {
+            match reader.read_record(&mut record) {
+                Ok(false) => break,
+                Ok(true) => {}
+                Err(e) => {
+                    line = e.position().map_or(line + 1, |position| position.line());
+                    skipped.push(SkippedRow {
+                        line,
+                        column: None,
+                        reason: row_error(&e),
+                    });
+                    continue;
+                }
+            }
+            line = record
+                .position()
+                .map_or(line + 1, |position| position.line());
+
+            let group = group_by.iter().map(|&i| record[i].to_string()).collect();
+            let group_values = values
+                .entry(group)
+                .or_insert_with(|| vec![Vec::new(); columns.len()]);
+            for (column, &i) in columns.iter().enumerate() {
+                let cell = record[i].trim();
+                match cell.parse::<f64>() {
+                    Ok(value) => group_values[column].push(value),
+                    Err(_) => skipped.push(SkippedRow {
+                        line,
+                        column: Some(selection.columns[column].clone()),
+                        reason: if cell.is_empty() {
+                            "missing value".to_string()
+                        } else {
+                            format!("not a number: {}", cell)
+                        },
+                    }),
+                }
+            }
+        }
+
+        let mut groups = Vec::new();
+        for (group, columns) in values {
+            for (name, column) in selection.columns.iter().zip(columns) {
+                let statistics = match Statistics::new(&column, selection.nan_policy) {
+                    Ok(statistics) => Some(statistics),
+                    Err(StatisticsError::Empty) => None,
+                    Err(error) => {
+                        return Err(CsvStatisticsError::Statistics {
+                            column: name.clone(),
+                            group,
+                            error,
+                        })
+                    }
+                };
+                groups.push(GroupStatistics {
+                    group: group.clone(),
+                    column: name.clone(),
+                    statistics,
+                    values: column,
+                });
+            }
+        }
+
+        Ok(Self {
+            group_by: selection.group_by.clone(),
+            groups,
+            skipped,
+        })
+    }
+
+    /// Header of the Markdown and CSV tables
+    fn header(&self) -> Vec<String> {
+        let mut header = self.group_by.clone();
+        header.extend(
+            [
+                "Column", "Count", "NaN", "Min", "Max", "Mean", "Variance", "Std Dev", "Median",
+                "Q1", "Q3", "IQR", "Skewness", "Kurtosis",
+            ]
+            .map(String::from),
+        );
+        header
+    }
+
+    /// One row per group and column, `-` for undefined statistics
+    fn rows(&self) -> Vec<Vec<String>> {
+        let optional = |value: Option<f64>| value.map_or("-".to_string(), |v| v.to_string());
+        self.groups
+            .iter()
+            .map(|group| {
+                let mut row = group.group.clone();
+                row.push(group.column.clone());
+                match &group.statistics {
+                    Some(s) => row.extend([
+                        s.count.to_string(),
+                        s.nan_count.to_string(),
+                        s.min.to_string(),
+                        s.max.to_string(),
+                        s.mean.to_string(),
+                        optional(s.sample_variance),
+                        s.std_dev.to_string(),
+                        s.median.to_string(),
+                        s.q1.to_string(),
+                        s.q3.to_string(),
+                        s.iqr.to_string(),
+                        optional(s.skewness),
+                        optional(s.kurtosis),
+                    ]),
+                    None => {
+                        row.push("0".to_string());
+                        row.extend(std::iter::repeat_n("-".to_string(), 12));
+                    }
+                }
+                row
+            })
+            .collect()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let header = self.header();
+        let mut markdown = format!("| {} |\n", header.join(" | "));
+        markdown.push_str(&format!("|{}\n", "---|".repeat(header.len())));
+        for row in self.rows() {
+            markdown.push_str(&format!("| {} |\n", row.join(" | ")));
+        }
+        markdown
+    }
+
+    pub fn write_csv<W: io::Write>(&self, writer: W) -> Result<(), csv::Error> {
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record(self.header())?;
+        for row in self.rows() {
+            writer.write_record(row)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// Why a whole row could not be read
+fn row_error(e: &csv::Error) -> String {
+    match e.kind() {
+        csv::ErrorKind::UnequalLengths {
+            expected_len, len, ..
+        } => format!("{} fields instead of {}", len, expected_len),
+        _ => e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV: &str = "\
+Series_reference,Period,Value,Other,UNITS
+A,2020.03,1,10,Dollars
+A,2020.06,3,x,Dollars
+B,2020.03,,30,Dollars
+B,2020.06,5,40
+B,2020.09,7,50,Percent
+";
+
+    fn selection(columns: &[&str], group_by: &[&str]) -> Selection {
+        Selection {
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            group_by: group_by.iter().map(|c| c.to_string()).collect(),
+            ..Selection::default()
+        }
+    }
+
+    #[test]
+    fn groups_by_several_columns() {
+        let grouped = GroupedStatistics::from_reader(
+            CSV.as_bytes(),
+            &selection(&["Value", "Other"], &["Series_reference", "UNITS"]),
+        )
+        .unwrap();
+
+        let summary: Vec<(Vec<&str>, &str, Option<usize>)> = grouped
+            .groups
+            .iter()
+            .map(|g| {
+                (
+                    g.group.iter().map(String::as_str).collect(),
+                    g.column.as_str(),
+                    g.statistics.as_ref().map(|s| s.count),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (vec!["A", "Dollars"], "Value", Some(2)),
+                (vec!["A", "Dollars"], "Other", Some(1)),
+                (vec!["B", "Dollars"], "Value", None),
+                (vec!["B", "Dollars"], "Other", Some(1)),
+                (vec!["B", "Percent"], "Value", Some(1)),
+                (vec!["B", "Percent"], "Other", Some(1)),
+            ]
+        );
+
+        let reasons: Vec<(u64, &str)> = grouped
+            .skipped
+            .iter()
+            .map(|row| (row.line, row.reason.as_str()))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (3, "not a number: x"),
+                (4, "missing value"),
+                (5, "4 fields instead of 5")
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_columns_are_an_error() {
+        let error = GroupedStatistics::from_reader(CSV.as_bytes(), &selection(&["Price"], &[]))
+            .unwrap_err();
+        assert!(matches!(error, CsvStatisticsError::UnknownColumn(name) if name == "Price"));
+    }
+
+    #[test]
+    fn tables_have_a_row_per_group_and_column() {
+        let grouped =
+            GroupedStatistics::from_reader(CSV.as_bytes(), &selection(&["Value"], &["UNITS"]))
+                .unwrap();
+        let markdown = grouped.to_markdown();
+        let lines: Vec<&str> = markdown.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("| UNITS | Column | Count |"));
+        assert!(lines[2].starts_with("| Dollars | Value | 2 |"));
+
+        let mut csv = Vec::new();
+        grouped.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with("UNITS,Column,Count,"));
+        assert_eq!(csv.lines().count(), 3);
+    }
+}
@@ -0,0 +1,342 @@
+//! Reading graphs from files
+//!
+//! Three formats are supported, so that graphs exported by other tools (e.g. the module
+//! dependencies of a build) can be analysed:
+//!
+//! - **DOT**: `digraph { a -> b [label="uses"]; c; }`. Nodes are named by their id,
+//!   edges take their `label` attribute. A graph using only `--` edges is undirected.
+//! - **edge list**: one `source target [label...]` per line, a single name declares an
+//!   isolated node, `#` starts a comment. Edge lists are directed.
+//! - **JSON**: `{"directed": true, "nodes": ["c"], "edges": [{"source": "a", "target": "b", "label": "uses"}]}`,
+//!   where `directed` defaults to `true`, `nodes` and `label` are optional
+//!   and an edge can also be written as `["a", "b"]`.
+
+use clap::ValueEnum;
+use graph_utils::DiGraphBuilder;
+use layout::gv::parser::ast::{self, ArrowKind, Stmt};
+use layout::gv::DotParser;
+use petgraph::graph::{DiGraph, UnGraph};
+use petgraph::Undirected;
+use serde::Deserialize;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Format of a graph file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GraphFormat {
+    Dot,
+    EdgeList,
+    Json,
+}
+
+impl GraphFormat {
+    /// Format from the file extension: `.dot`/`.gv`, `.json`, `.txt`/`.edges`/`.tsv`
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "dot" | "gv" => Some(Self::Dot),
+            "json" => Some(Self::Json),
+            "txt" | "edges" | "tsv" => Some(Self::EdgeList),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from the content, used for stdin and unknown extensions
+    pub fn detect(contents: &str) -> Self {
+        let start = contents.trim_start();
+        if start.starts_with('{') {
+            Self::Json
+        } else if ["digraph", "graph", "strict"].iter().any(|keyword| {
+            // a whole word: an edge list can start with a node like `graphite`
+            start
+                .strip_prefix(keyword)
+                .and_then(|rest| rest.chars().next())
+                .is_some_and(|next| next.is_whitespace() || next == '{')
+        }) {
+            Self::Dot
+        } else {
+            Self::EdgeList
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum GraphInputError {
+    Io(io::Error),
+    Dot(String),
+    EdgeList { line: usize, message: String },
+    Json(serde_json::Error),
+}
+
+impl Display for GraphInputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphInputError::Io(e) => write!(f, "Could not read the graph: {}", e),
+            GraphInputError::Dot(message) => write!(f, "Invalid DOT graph: {}", message),
+            GraphInputError::EdgeList { line, message } => {
+                write!(f, "Invalid edge list at line {}: {}", line, message)
+            }
+            GraphInputError::Json(e) => write!(f, "Invalid JSON graph: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for GraphInputError {}
+
+impl From<io::Error> for GraphInputError {
+    fn from(e: io::Error) -> Self {
+        GraphInputError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for GraphInputError {
+    fn from(e: serde_json::Error) -> Self {
+        GraphInputError::Json(e)
+    }
+}
+
+/// A graph read from a file: nodes are named, edges carry a (possibly empty) label.
+///
+/// Edges are always stored with their direction; for undirected graphs it is just the order
+/// they were written in, use [GraphFile::undirected] to analyse them.
+#[derive(Debug, Clone)]
+pub struct GraphFile {
+    pub directed: bool,
+    pub graph: DiGraph<String, String>,
+}
+
+impl GraphFile {
+    /// Reads the graph at `path`, or stdin when `path` is `-`.
+    /// Without an explicit format it is chosen from the extension, then from the content.
+    pub fn load(path: &Path, format: Option<GraphFormat>) -> Result<Self, GraphInputError> {
+        let contents = if path == Path::new("-") {
+            let mut contents = String::new();
+            io::stdin().read_to_string(&mut contents)?;
+            contents
+        } else {
+            fs::read_to_string(path)?
+        };
+        let format = format
+            .or_else(|| GraphFormat::from_path(path))
+            .unwrap_or_else(|| GraphFormat::detect(&contents));
+        Self::parse(&contents, format)
+    }
+
+    /// Parses a graph in the given format
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use week1_challenge::graph_io::{GraphFile, GraphFormat};
+    ///
+    /// let file = GraphFile::parse("app core uses\ncore log\n", GraphFormat::EdgeList).unwrap();
+    /// assert!(file.directed);
+    /// assert_eq!(file.graph.node_count(), 3);
+    /// assert_eq!(file.graph.edge_count(), 2);
+    /// ```
+    pub fn parse(contents: &str, format: GraphFormat) -> Result<Self, GraphInputError> {
+        match format {
+            GraphFormat::Dot => parse_dot(contents),
+            GraphFormat::EdgeList => parse_edge_list(contents),
+            GraphFormat::Json => parse_json(contents),
+        }
+    }
+
+    /// The same graph with edge directions ignored
+    pub fn undirected(&self) -> UnGraph<String, String> {
+        self.graph.clone().into_edge_type::<Undirected>()
+    }
+}
+
+fn parse_dot(contents: &str) -> Result<GraphFile, GraphInputError> {
+    let mut parser = DotParser::new(contents);
+    let graph = parser.process().map_err(GraphInputError::Dot)?;
+
+    let mut builder = DiGraphBuilder::new();
+    let mut directed = false;
+    add_dot_statements(&graph, &mut builder, &mut directed);
+    Ok(GraphFile {
+        // a graph without edges has nothing to say about directions
+        directed: directed || builder.edge_count() == 0,
+        graph: builder.build().0,
+    })
+}
+
+/// Adds the nodes and edges of `graph` and of its subgraphs
+fn add_dot_statements(
+    graph: &ast::Graph,
+    builder: &mut DiGraphBuilder<String, String, String>,
+    directed: &mut bool,
+) {
+    for statement in &graph.list.list {
+        match statement {
+            Stmt::Node(node) => {
+                builder.add_key(node.id.name.clone());
+            }
+            Stmt::Edge(edge) => {
+                let label = edge
+                    .list
+                    .iter()
+                    .find(|(key, _)| key == "label")
+                    .map(|(_, value)| value.clone())
+                    .unwrap_or_default();
+                let mut from = edge.from.name.clone();
+                for (to, arrow) in &edge.to {
+                    *directed |= matches!(arrow, ArrowKind::Arrow);
+                    builder.connect(from, to.name.clone(), label.clone());
+                    from = to.name.clone();
+                }
+            }
+            Stmt::SubGraph(subgraph) => add_dot_statements(subgraph, builder, directed),
+            Stmt::Attribute(_) => {}
+        }
+    }
+}
+
+fn parse_edge_list(contents: &str) -> Result<GraphFile, GraphInputError> {
+    let mut builder = DiGraphBuilder::new();
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut fields = line.split_whitespace();
+        let Some(source) = fields.next() else {
+            continue;
+        };
+        match fields.next() {
+            None => {
+                builder.add_key(source.to_string());
+            }
+            Some("->") => {
+                return Err(GraphInputError::EdgeList {
+                    line: number + 1,
+                    message: "expected `source target [label]`, without arrows".to_string(),
+                })
+            }
+            Some(target) => {
+                let label = fields.collect::<Vec<_>>().join(" ");
+                builder.connect(source.to_string(), target.to_string(), label);
+            }
+        }
+    }
+    Ok(GraphFile {
+        directed: true,
+        graph: builder.build().0,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonGraph {
+    #[serde(default = "directed_by_default")]
+    directed: bool,
+    #[serde(default)]
+    nodes: Vec<String>,
+    #[serde(default)]
+    edges: Vec<JsonEdge>,
+}
+
+fn directed_by_default() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum JsonEdge {
+    Pair(String, String),
+    Labelled {
+        source: String,
+        target: String,
+        #[serde(default)]
+        label: String,
+    },
+}
+
+fn parse_json(contents: &str) -> Result<GraphFile, GraphInputError> {
+    let json: JsonGraph = serde_json::from_str(contents)?;
+    let mut builder = DiGraphBuilder::new();
+    for node in json.nodes {
+        builder.add_key(node);
+    }
+    for edge in json.edges {
+        let (source, target, label) = match edge {
+            JsonEdge::Pair(source, target) => (source, target, String::new()),
+            JsonEdge::Labelled {
+                source,
+                target,
+                label,
+            } => (source, target, label),
+        };
+        builder.connect(source, target, label);
+    }
+    Ok(GraphFile {
+        directed: json.directed,
+        graph: builder.build().0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `(source, target, label)` of every edge
+    fn edges(file: &GraphFile) -> Vec<(&str, &str, &str)> {
+        file.graph
+            .edge_indices()
+            .map(|edge| {
+                let (a, b) = file.graph.edge_endpoints(edge).unwrap();
+                (
+                    file.graph[a].as_str(),
+                    file.graph[b].as_str(),
+                    file.graph[edge].as_str(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn the_three_formats_agree() {
+        let dot = r#"digraph deps { app -> core [label="uses"]; core -> log; io; }"#;
+        let edge_list = "# dependencies\napp core uses\ncore log\nio\n";
+        let json = r#"{"nodes": ["io"], "edges": [{"source": "app", "target": "core", "label": "uses"}, ["core", "log"]]}"#;
+
+        for (contents, format) in [
+            (dot, GraphFormat::Dot),
+            (edge_list, GraphFormat::EdgeList),
+            (json, GraphFormat::Json),
+        ] {
+            assert_eq!(GraphFormat::detect(contents), format);
+            let file = GraphFile::parse(contents, format).unwrap();
+            assert!(file.directed);
+            assert_eq!(file.graph.node_count(), 4);
+            let mut found = edges(&file);
+            found.sort();
+            assert_eq!(found, vec![("app", "core", "uses"), ("core", "log", "")]);
+        }
+    }
+
+    #[test]
+    fn dot_keywords_must_be_whole_words() {
+        assert_eq!(GraphFormat::detect("digraph{ a -> b }"), GraphFormat::Dot);
+        assert_eq!(GraphFormat::detect("strict graph { a }"), GraphFormat::Dot);
+        let edge_list = "graph_utils petgraph
+graphite stricter
+";
+        assert_eq!(GraphFormat::detect(edge_list), GraphFormat::EdgeList);
+        let file = GraphFile::parse(edge_list, GraphFormat::detect(edge_list)).unwrap();
+        assert_eq!(file.graph.node_count(), 4);
+    }
+
+    #[test]
+    fn dot_chains_and_undirected_graphs() {
+        let file = GraphFile::parse("graph { a -- b -- c }", GraphFormat::Dot).unwrap();
+        assert!(!file.directed);
+        assert_eq!(edges(&file), vec![("a", "b", ""), ("b", "c", "")]);
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        let error = GraphFile::parse("a b\nc -> d\n", GraphFormat::EdgeList).unwrap_err();
+        assert!(matches!(error, GraphInputError::EdgeList { line: 2, .. }));
+        assert!(GraphFile::parse("digraph {", GraphFormat::Dot).is_err());
+        assert!(GraphFile::parse("[1, 2]", GraphFormat::Json).is_err());
+    }
+}
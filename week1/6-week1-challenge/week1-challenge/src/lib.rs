@@ -0,0 +1,10 @@
+//! # Week 1 challenge
+//!
+//! Reusable pieces of the week 1 challenges, the challenges themselves live in the binary.
+
+pub mod connectivity;
+pub mod csv_statistics;
+pub mod graph_io;
+pub mod scc;
+pub mod statistics;
+pub mod timeseries;
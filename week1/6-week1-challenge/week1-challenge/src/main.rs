@@ -1,17 +1,241 @@
 mod challenge_1;
+// the original code of the lesson, kept as it was written
+#[allow(clippy::unnecessary_sort_by)]
 mod challenge_2;
 mod challenge_4;
 mod challenge_5;
 
-use crate::challenge_1::challenge_1;
+use crate::challenge_1::{
+    challenge_1, statistics, time_series, ChartOptions, TableFormat, TimeSeriesOptions,
+};
 use crate::challenge_2::{challenge_2, challenge_3};
 use crate::challenge_4::challenge_4;
 use crate::challenge_5::challenge_5;
+use clap::{Parser, Subcommand, ValueEnum};
+use graph_utils::render::{Colouring, Renderer};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process;
+use week1_challenge::csv_statistics::Selection;
+use week1_challenge::graph_io::{GraphFile, GraphFormat};
+use week1_challenge::statistics::NanPolicy;
+
+const STATISTICS_DATA: &str = "data/sample-data.csv";
+const FREQUENCIES_DATA: &str = "data/poe_holmes.txt";
+const CONNECTIVITY_GRAPH: &str = "data/challenge_4.dot";
+const SCC_GRAPH: &str = "data/challenge_5.txt";
+
+/// Week 1 challenges: statistics, word frequencies and graphs.
+///
+/// Without a subcommand every challenge runs on the example data.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Render the graphs of challenge 4 and 5 (.svg or .dot), without a subcommand.
+    /// `out.svg` is written as `out-challenge_4.svg` and `out-challenge_5.svg`
+    #[arg(long)]
+    render: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Challenge 1: statistics of the numeric columns of a CSV file, optionally per group
+    #[command(visible_alias = "challenge-1")]
+    Statistics {
+        #[arg(default_value = STATISTICS_DATA)]
+        csv: String,
+        /// Numeric column to describe, can be repeated
+        #[arg(short, long = "column", default_value = "Value")]
+        columns: Vec<String>,
+        /// Categorical column to group the rows by, can be repeated
+        #[arg(short, long)]
+        group_by: Vec<String>,
+        #[arg(long, value_enum, default_value_t = TableFormat::Markdown)]
+        format: TableFormat,
+        /// Fail on NaN values instead of leaving them out
+        #[arg(long)]
+        reject_nan: bool,
+        #[command(flatten)]
+        charts: ChartOptions,
+    },
+    /// Challenge 1 over time: resampling, rolling statistics, trend and anomalies per series reference
+    #[command(visible_alias = "challenge-1-timeseries")]
+    Timeseries {
+        #[arg(default_value = STATISTICS_DATA)]
+        csv: String,
+        #[command(flatten)]
+        options: TimeSeriesOptions,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+    /// Challenge 2 and 3: the most frequent words of a text
+    #[command(visible_alias = "challenge-2")]
+    Frequencies {
+        #[arg(default_value = FREQUENCIES_DATA)]
+        text: String,
+        /// Collect the words in a LinkedList (challenge 3)
+        #[arg(long)]
+        linked_list: bool,
+    },
+    /// Challenge 4: weak and strong connectivity, articulation points and bridges
+    #[command(visible_alias = "challenge-4")]
+    Connectivity {
+        #[command(flatten)]
+        graph: GraphArgs,
+    },
+    /// Challenge 5: strongly connected components and cycles of a directed graph
+    #[command(visible_alias = "challenge-5")]
+    Scc {
+        #[command(flatten)]
+        graph: GraphArgs,
+        /// Exit with status 2 when the graph has a cycle, e.g. to reject circular dependencies
+        #[arg(long)]
+        fail_on_cycles: bool,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+struct GraphArgs {
+    /// Graph file (DOT, edge list or JSON), `-` for stdin. Defaults to the example graph
+    input: Option<PathBuf>,
+
+    /// Format of the graph, guessed from the extension or the content when missing
+    #[arg(long, value_enum)]
+    format: Option<GraphFormat>,
+
+    /// Ignore edge directions
+    #[arg(long)]
+    undirected: bool,
+
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
+    /// Write the graph to this file (.svg or .dot)
+    #[arg(long)]
+    render: Option<PathBuf>,
+}
+
+impl GraphArgs {
+    fn load(&self, example: &str) -> Result<GraphFile, Box<dyn Error>> {
+        let path = self.input.as_deref().unwrap_or(Path::new(example));
+        let mut file = GraphFile::load(path, self.format)?;
+        file.directed &= !self.undirected;
+        Ok(file)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// Renders the graph file, undirected graphs without arrows
+pub fn render_graph(
+    file: &GraphFile,
+    colouring: Colouring,
+    path: &Path,
+) -> Result<(), Box<dyn Error>> {
+    if file.directed {
+        Renderer::new(&file.graph)
+            .edge_label(|label| label.clone())
+            .colouring(colouring)
+            .write(path)?;
+    } else {
+        Renderer::new(&file.undirected())
+            .edge_label(|label| label.clone())
+            .colouring(colouring)
+            .write(path)?;
+    }
+    // stdout may be JSON
+    eprintln!("Graph rendered to {}", path.display());
+    Ok(())
+}
+
+/// `out.svg` -> `out-<name>.svg`
+fn render_path(path: &Path, name: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let mut file_name = format!("{}-{}", stem, name);
+    if let Some(extension) = path.extension() {
+        file_name = format!("{}.{}", file_name, extension.to_string_lossy());
+    }
+    path.with_file_name(file_name)
+}
+
+/// Every challenge on the example data
+fn run_all(render: Option<&Path>) -> Result<(), Box<dyn Error>> {
+    let render_4 = render.map(|p| render_path(p, "challenge_4"));
+    let render_5 = render.map(|p| render_path(p, "challenge_5"));
+
+    challenge_1(STATISTICS_DATA)?;
+    challenge_2(FREQUENCIES_DATA);
+    challenge_3(FREQUENCIES_DATA);
+    let graph = GraphFile::load(Path::new(CONNECTIVITY_GRAPH), None)?;
+    challenge_4(&graph, OutputFormat::Text, render_4.as_deref())?;
+    let graph = GraphFile::load(Path::new(SCC_GRAPH), None)?;
+    challenge_5(&graph, OutputFormat::Text, render_5.as_deref())?;
+    Ok(())
+}
+
+fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    match args.command {
+        None => run_all(args.render.as_deref())?,
+        Some(Command::Statistics {
+            csv,
+            columns,
+            group_by,
+            format,
+            reject_nan,
+            charts,
+        }) => {
+            let selection = Selection {
+                columns,
+                group_by,
+                nan_policy: if reject_nan {
+                    NanPolicy::Reject
+                } else {
+                    NanPolicy::Skip
+                },
+            };
+            statistics(&csv, &selection, format, &charts)?
+        }
+        Some(Command::Timeseries {
+            csv,
+            options,
+            output,
+        }) => time_series(&csv, &options, output)?,
+        Some(Command::Frequencies { text, linked_list }) => {
+            if linked_list {
+                challenge_3(&text)
+            } else {
+                challenge_2(&text)
+            }
+        }
+        Some(Command::Connectivity { graph }) => {
+            let file = graph.load(CONNECTIVITY_GRAPH)?;
+            challenge_4(&file, graph.output, graph.render.as_deref())?;
+        }
+        Some(Command::Scc {
+            graph,
+            fail_on_cycles,
+        }) => {
+            let file = graph.load(SCC_GRAPH)?;
+            let has_cycles = challenge_5(&file, graph.output, graph.render.as_deref())?;
+            if has_cycles && fail_on_cycles {
+                process::exit(2);
+            }
+        }
+    }
+    Ok(())
+}
 
 fn main() {
-    challenge_1();
-    challenge_2();
-    challenge_3();
-    challenge_4();
-    challenge_5();
+    if let Err(e) = run(Args::parse()) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
 }
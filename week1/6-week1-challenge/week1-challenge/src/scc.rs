@@ -0,0 +1,274 @@
+//! Strongly connected components of directed graphs
+//!
+//! Two classic algorithms are implemented, both iteratively so that deep graphs
+//! (e.g. a long chain of dependencies) cannot overflow the call stack:
+//!
+//! - [kosaraju_scc]: two depth-first searches, the second one on the reversed edges
+//! - [tarjan_scc]: a single depth-first search tracking the lowest reachable index
+//!
+//! A component is a set of nodes that can all reach each other.
+//! Collapsing every component into a single node gives the [Condensation] of the graph,
+//! which is always a DAG and can therefore be sorted topologically.
+//!
+//! Both algorithms also accept undirected graphs, where the strongly connected components
+//! are simply the connected components.
+
+use petgraph::algo::toposort;
+use petgraph::graph::{DiGraph, Graph, Neighbors, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::{Directed, Direction, EdgeType};
+use std::collections::HashSet;
+
+/// Kosaraju's algorithm.
+///
+/// The first depth-first search records the nodes by finishing time,
+/// the second one walks the reversed edges from the last finished node:
+/// every walk collects exactly one component.
+///
+/// Components are returned in topological order: no edge goes from a component to an earlier one.
+pub fn kosaraju_scc<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Vec<Vec<NodeIndex>> {
+    let n = graph.node_count();
+
+    // First pass: nodes in order of finishing time
+    let mut visited = vec![false; n];
+    let mut finished = Vec::with_capacity(n);
+    let mut stack: Vec<(NodeIndex, Neighbors<E>)> = Vec::new();
+    for start in graph.node_indices() {
+        if visited[start.index()] {
+            continue;
+        }
+        visited[start.index()] = true;
+        stack.push((start, graph.neighbors_directed(start, Direction::Outgoing)));
+
+        while let Some((node, neighbours)) = stack.last_mut() {
+            let node = *node;
+            match neighbours.find(|next| !visited[next.index()]) {
+                Some(next) => {
+                    visited[next.index()] = true;
+                    stack.push((next, graph.neighbors_directed(next, Direction::Outgoing)));
+                }
+                None => {
+                    stack.pop();
+                    finished.push(node);
+                }
+            }
+        }
+    }
+
+    // Second pass: walk the reversed edges, latest finished node first
+    let mut assigned = vec![false; n];
+    let mut components = Vec::new();
+    let mut pending = Vec::new();
+    for &root in finished.iter().rev() {
+        if assigned[root.index()] {
+            continue;
+        }
+        let mut component = Vec::new();
+        assigned[root.index()] = true;
+        pending.push(root);
+
+        while let Some(node) = pending.pop() {
+            component.push(node);
+            for previous in graph.neighbors_directed(node, Direction::Incoming) {
+                if !assigned[previous.index()] {
+                    assigned[previous.index()] = true;
+                    pending.push(previous);
+                }
+            }
+        }
+        components.push(component);
+    }
+
+    components
+}
+
+/// Tarjan's algorithm.
+///
+/// Nodes are numbered in the order the depth-first search discovers them.
+/// The `lowlink` of a node is the smallest number reachable from it through the nodes
+/// still on the stack: a node whose `lowlink` is its own number is the root of a component.
+///
+/// Components are returned in reverse topological order, like `petgraph::algo::tarjan_scc`.
+pub fn tarjan_scc<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Vec<Vec<NodeIndex>> {
+    let n = graph.node_count();
+    let mut number: Vec<Option<usize>> = vec![None; n];
+    let mut lowlink = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut next_number = 0;
+    let mut components = Vec::new();
+
+    let mut calls: Vec<(NodeIndex, Neighbors<E>)> = Vec::new();
+    for start in graph.node_indices() {
+        if number[start.index()].is_some() {
+            continue;
+        }
+        let mut discovered = Some(start);
+
+        loop {
+            // Entering a node for the first time: number it and explore its successors
+            if let Some(node) = discovered.take() {
+                number[node.index()] = Some(next_number);
+                lowlink[node.index()] = next_number;
+                next_number += 1;
+                stack.push(node);
+                on_stack[node.index()] = true;
+                calls.push((node, graph.neighbors_directed(node, Direction::Outgoing)));
+            }
+
+            let Some((node, neighbours)) = calls.last_mut() else {
+                break;
+            };
+            let node = *node;
+            if let Some(next) = neighbours.next() {
+                match number[next.index()] {
+                    None => discovered = Some(next),
+                    Some(next_number) if on_stack[next.index()] => {
+                        lowlink[node.index()] = lowlink[node.index()].min(next_number);
+                    }
+                    Some(_) => {}
+                }
+                continue;
+            }
+
+            // Every successor has been explored: return to the caller
+            calls.pop();
+            if let Some((caller, _)) = calls.last() {
+                lowlink[caller.index()] = lowlink[caller.index()].min(lowlink[node.index()]);
+            }
+            if Some(lowlink[node.index()]) == number[node.index()] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack[member.index()] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+
+    components
+}
+
+/// The graph of the strongly connected components
+///
+/// Every node of `graph` holds the members of a component,
+/// there is an edge between two components when the original graph has
+/// at least one edge between their members.
+#[derive(Debug, Clone)]
+pub struct Condensation {
+    pub graph: DiGraph<Vec<NodeIndex>, ()>,
+    /// Component of each node of the original graph, indexed by `NodeIndex::index()`
+    pub component_of: Vec<NodeIndex>,
+}
+
+impl Condensation {
+    /// Builds the condensation from the components found by [kosaraju_scc] or [tarjan_scc]
+    pub fn new<N, E>(graph: &Graph<N, E, Directed>, components: Vec<Vec<NodeIndex>>) -> Self {
+        let mut condensed = DiGraph::with_capacity(components.len(), 0);
+        let mut component_of = vec![NodeIndex::end(); graph.node_count()];
+        for members in components {
+            let members_of = condensed.add_node(Vec::new());
+            for member in &members {
+                component_of[member.index()] = members_of;
+            }
+            condensed[members_of] = members;
+        }
+
+        let mut links = HashSet::new();
+        for edge in graph.edge_references() {
+            let from = component_of[edge.source().index()];
+            let to = component_of[edge.target().index()];
+            if from != to && links.insert((from, to)) {
+                condensed.add_edge(from, to, ());
+            }
+        }
+
+        Self {
+            graph: condensed,
+            component_of,
+        }
+    }
+
+    /// Components sorted so that every edge goes from an earlier to a later component
+    pub fn topological_order(&self) -> Vec<NodeIndex> {
+        toposort(&self.graph, None).expect("The condensation of a graph is always acyclic")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Components with sorted members, in a canonical order
+    fn canonical(mut components: Vec<Vec<NodeIndex>>) -> Vec<Vec<NodeIndex>> {
+        for component in components.iter_mut() {
+            component.sort();
+        }
+        components.sort();
+        components
+    }
+
+    fn random_graph() -> impl Strategy<Value = DiGraph<(), ()>> {
+        (1usize..40).prop_flat_map(|n| {
+            prop::collection::vec((0..n as u32, 0..n as u32), 0..n * 3).prop_map(move |edges| {
+                let mut graph = DiGraph::with_capacity(n, edges.len());
+                for _ in 0..n {
+                    graph.add_node(());
+                }
+                graph.extend_with_edges(edges);
+                graph
+            })
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn agrees_with_petgraph(graph in random_graph()) {
+            let expected = canonical(petgraph::algo::tarjan_scc(&graph));
+            prop_assert_eq!(canonical(kosaraju_scc(&graph)), expected.clone());
+            prop_assert_eq!(canonical(tarjan_scc(&graph)), expected);
+        }
+
+        #[test]
+        fn condensation_is_topologically_sorted(graph in random_graph()) {
+            let condensation = Condensation::new(&graph, tarjan_scc(&graph));
+            let order = condensation.topological_order();
+            let mut position = vec![0; order.len()];
+            for (i, component) in order.iter().enumerate() {
+                position[component.index()] = i;
+            }
+            for edge in graph.edge_references() {
+                let from = condensation.component_of[edge.source().index()];
+                let to = condensation.component_of[edge.target().index()];
+                prop_assert!(from == to || position[from.index()] < position[to.index()]);
+            }
+        }
+
+        #[test]
+        fn kosaraju_is_already_topological(graph in random_graph()) {
+            let condensation = Condensation::new(&graph, kosaraju_scc(&graph));
+            for edge in condensation.graph.edge_references() {
+                prop_assert!(edge.source() < edge.target());
+            }
+        }
+    }
+
+    #[test]
+    fn long_chains_do_not_overflow_the_stack() {
+        let n = 200_000;
+        let mut graph = DiGraph::<(), ()>::with_capacity(n, n);
+        for _ in 0..n {
+            graph.add_node(());
+        }
+        graph.extend_with_edges((1..n as u32).map(|i| (i - 1, i)));
+        graph.add_edge(NodeIndex::new(n - 1), NodeIndex::new(0), ());
+
+        assert_eq!(kosaraju_scc(&graph).len(), 1);
+        assert_eq!(tarjan_scc(&graph).len(), 1);
+    }
+}
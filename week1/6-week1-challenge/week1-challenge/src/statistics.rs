@@ -0,0 +1,337 @@
+//! Descriptive statistics of a column of numbers
+//!
+//! Moments (mean, variance, skewness, kurtosis) are computed in a single pass with
+//! Welford's online algorithm, extended to the third and fourth moments, which avoids the
+//! catastrophic cancellation of the textbook `Σx² - n·mean²` formula.
+//! Order statistics (median, quantiles) need the values sorted, NaN must therefore be
+//! dealt with first, according to a [NanPolicy].
+
+use serde::Serialize;
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatisticsError {
+    /// No values left to describe
+    Empty,
+    /// A NaN at this position, with [NanPolicy::Reject]
+    NaN { index: usize },
+    /// Quantiles are only defined between 0 and 1
+    InvalidQuantile(f64),
+}
+
+impl Display for StatisticsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatisticsError::Empty => write!(f, "No values to compute statistics on"),
+            StatisticsError::NaN { index } => write!(f, "Value {} is NaN", index),
+            StatisticsError::InvalidQuantile(q) => {
+                write!(f, "Quantile {} is not between 0 and 1", q)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StatisticsError {}
+
+/// What to do with NaN values
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NanPolicy {
+    /// Leave them out, they are counted in [Statistics::nan_count]
+    #[default]
+    Skip,
+    /// Fail with [StatisticsError::NaN]
+    Reject,
+}
+
+/// Single-pass mean and central moments (Welford, Terriberry for the 3rd and 4th moments)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RunningMoments {
+    count: usize,
+    mean: f64,
+    /// Sums of the 2nd, 3rd and 4th powers of the differences from the mean
+    m2: f64,
+    m3: f64,
+    m4: f64,
+}
+
+impl RunningMoments {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, x: f64) {
+        let n1 = self.count as f64;
+        self.count += 1;
+        let n = self.count as f64;
+        let delta = x - self.mean;
+        let delta_n = delta / n;
+        let delta_n2 = delta_n * delta_n;
+        let term = delta * delta_n * n1;
+
+        self.mean += delta_n;
+        self.m4 += term * delta_n2 * (n * n - 3.0 * n + 3.0) + 6.0 * delta_n2 * self.m2
+            - 4.0 * delta_n * self.m3;
+        self.m3 += term * delta_n * (n - 2.0) - 3.0 * delta_n * self.m2;
+        self.m2 += term;
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// `None` before the first value
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.mean)
+    }
+
+    /// Variance of the values themselves: `Σ(x - mean)² / n`
+    pub fn population_variance(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.m2 / self.count as f64)
+    }
+
+    /// Unbiased estimate of the variance of the population the values are sampled from:
+    /// `Σ(x - mean)² / (n - 1)`
+    pub fn sample_variance(&self) -> Option<f64> {
+        (self.count > 1).then(|| self.m2 / (self.count - 1) as f64)
+    }
+
+    /// Population skewness `g1`, `None` when all values are equal
+    pub fn skewness(&self) -> Option<f64> {
+        (self.count > 0 && self.m2 > 0.0)
+            .then(|| (self.count as f64).sqrt() * self.m3 / self.m2.powf(1.5))
+    }
+
+    /// Population excess kurtosis `g2` (0 for a normal distribution), `None` when all values are equal
+    pub fn excess_kurtosis(&self) -> Option<f64> {
+        (self.count > 0 && self.m2 > 0.0)
+            .then(|| self.count as f64 * self.m4 / (self.m2 * self.m2) - 3.0)
+    }
+}
+
+impl Extend<f64> for RunningMoments {
+    fn extend<I: IntoIterator<Item = f64>>(&mut self, values: I) {
+        for x in values {
+            self.push(x);
+        }
+    }
+}
+
+/// Summary of a column of numbers
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Statistics {
+    /// Values the statistics are computed on
+    pub count: usize,
+    /// NaN values left out, see [NanPolicy::Skip]
+    pub nan_count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub population_variance: f64,
+    /// `None` for a single value
+    pub sample_variance: Option<f64>,
+    /// Square root of the sample variance, or of the population one for a single value
+    pub std_dev: f64,
+    pub median: f64,
+    pub q1: f64,
+    pub q3: f64,
+    pub iqr: f64,
+    /// `None` when all values are equal
+    pub skewness: Option<f64>,
+    /// Excess kurtosis, `None` when all values are equal
+    pub kurtosis: Option<f64>,
+}
+
+impl Statistics {
+    /// Describes the values
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use week1_challenge::statistics::{NanPolicy, Statistics, StatisticsError};
+    ///
+    /// let stats = Statistics::new(&[1.0, 2.0, f64::NAN, 3.0, 4.0], NanPolicy::Skip).unwrap();
+    /// assert_eq!(stats.count, 4);
+    /// assert_eq!(stats.nan_count, 1);
+    /// assert_eq!(stats.median, 2.5);
+    /// assert_eq!(stats.population_variance, 1.25);
+    ///
+    /// let error = Statistics::new(&[], NanPolicy::Skip).unwrap_err();
+    /// assert_eq!(error, StatisticsError::Empty);
+    /// ```
+    pub fn new(values: &[f64], nan_policy: NanPolicy) -> Result<Self, StatisticsError> {
+        let mut sorted = Vec::with_capacity(values.len());
+        let mut nan_count = 0;
+        let mut moments = RunningMoments::new();
+        for (index, &x) in values.iter().enumerate() {
+            if x.is_nan() {
+                match nan_policy {
+                    NanPolicy::Skip => nan_count += 1,
+                    NanPolicy::Reject => return Err(StatisticsError::NaN { index }),
+                }
+                continue;
+            }
+            moments.push(x);
+            sorted.push(x);
+        }
+        if sorted.is_empty() {
+            return Err(StatisticsError::Empty);
+        }
+        sorted.sort_by(f64::total_cmp);
+
+        let q1 = sorted_quantile(&sorted, 0.25);
+        let q3 = sorted_quantile(&sorted, 0.75);
+        let population_variance = moments.population_variance().unwrap_or_default();
+        let sample_variance = moments.sample_variance();
+        Ok(Self {
+            count: sorted.len(),
+            nan_count,
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            mean: moments.mean().unwrap_or_default(),
+            population_variance,
+            sample_variance,
+            std_dev: sample_variance.unwrap_or(population_variance).sqrt(),
+            median: sorted_quantile(&sorted, 0.5),
+            q1,
+            q3,
+            iqr: q3 - q1,
+            skewness: moments.skewness(),
+            kurtosis: moments.excess_kurtosis(),
+        })
+    }
+}
+
+impl Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let optional = |value: Option<f64>| value.map_or("-".to_string(), |v| v.to_string());
+        let rows = [
+            ("Count", self.count.to_string()),
+            ("NaN", self.nan_count.to_string()),
+            ("Min", self.min.to_string()),
+            ("Max", self.max.to_string()),
+            ("Mean", self.mean.to_string()),
+            (
+                "Variance (population)",
+                self.population_variance.to_string(),
+            ),
+            ("Variance (sample)", optional(self.sample_variance)),
+            ("Std Dev", self.std_dev.to_string()),
+            ("Median", self.median.to_string()),
+            ("Q1", self.q1.to_string()),
+            ("Q3", self.q3.to_string()),
+            ("IQR", self.iqr.to_string()),
+            ("Skewness", optional(self.skewness)),
+            ("Kurtosis (excess)", optional(self.kurtosis)),
+        ];
+        writeln!(f, "\n| {:<21} | {:<22} |", "Field", "Value")?;
+        writeln!(f, "|-----------------------|------------------------|")?;
+        for (field, value) in rows {
+            writeln!(f, "| {:<21} | {:<22} |", field, value)?;
+        }
+        writeln!(f, "|-----------------------|------------------------|")
+    }
+}
+
+/// The `q`-quantile of the values, interpolating linearly between the closest ranks
+/// (the default method of R and NumPy). NaN values are not allowed.
+///
+/// ```rust
+/// use week1_challenge::statistics::quantile;
+///
+/// assert_eq!(quantile(&[4.0, 1.0, 3.0, 2.0], 0.5), Ok(2.5));
+/// assert_eq!(quantile(&[4.0, 1.0, 3.0, 2.0], 1.0), Ok(4.0));
+/// ```
+pub fn quantile(values: &[f64], q: f64) -> Result<f64, StatisticsError> {
+    if !(0.0..=1.0).contains(&q) {
+        return Err(StatisticsError::InvalidQuantile(q));
+    }
+    if let Some(index) = values.iter().position(|x| x.is_nan()) {
+        return Err(StatisticsError::NaN { index });
+    }
+    if values.is_empty() {
+        return Err(StatisticsError::Empty);
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    Ok(sorted_quantile(&sorted, q))
+}
+
+/// [quantile] of sorted, non-empty values
+fn sorted_quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let below = position.floor() as usize;
+    let above = position.ceil() as usize;
+    sorted[below] + (sorted[above] - sorted[below]) * (position - below as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9 * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn moments_match_the_two_pass_formulas() {
+        let values = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0, 30.0];
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let central = |p: i32| values.iter().map(|x| (x - mean).powi(p)).sum::<f64>() / n;
+
+        let stats = Statistics::new(&values, NanPolicy::Reject).unwrap();
+        assert!(close(stats.mean, mean));
+        assert!(close(stats.population_variance, central(2)));
+        assert!(close(
+            stats.sample_variance.unwrap(),
+            central(2) * n / (n - 1.0)
+        ));
+        assert!(close(
+            stats.skewness.unwrap(),
+            central(3) / central(2).powf(1.5)
+        ));
+        assert!(close(
+            stats.kurtosis.unwrap(),
+            central(4) / central(2).powi(2) - 3.0
+        ));
+    }
+
+    #[test]
+    fn welford_is_stable_with_a_large_offset() {
+        let values: Vec<f64> = [4.0, 7.0, 13.0, 16.0].iter().map(|x| x + 1e9).collect();
+        let stats = Statistics::new(&values, NanPolicy::Skip).unwrap();
+        assert!(close(stats.sample_variance.unwrap(), 30.0));
+    }
+
+    #[test]
+    fn quartiles_and_iqr() {
+        let values: Vec<f64> = (1..=9).map(f64::from).collect();
+        let stats = Statistics::new(&values, NanPolicy::Skip).unwrap();
+        assert_eq!(
+            (stats.q1, stats.median, stats.q3, stats.iqr),
+            (3.0, 5.0, 7.0, 4.0)
+        );
+        assert_eq!(quantile(&values, 0.1), Ok(1.8));
+        assert_eq!(
+            quantile(&values, 1.5),
+            Err(StatisticsError::InvalidQuantile(1.5))
+        );
+    }
+
+    #[test]
+    fn degenerate_inputs_do_not_panic() {
+        let single = Statistics::new(&[3.0], NanPolicy::Skip).unwrap();
+        assert_eq!(single.sample_variance, None);
+        assert_eq!(single.std_dev, 0.0);
+        assert_eq!(single.skewness, None);
+
+        assert_eq!(
+            Statistics::new(&[f64::NAN], NanPolicy::Skip),
+            Err(StatisticsError::Empty)
+        );
+        assert_eq!(
+            Statistics::new(&[1.0, f64::NAN], NanPolicy::Reject),
+            Err(StatisticsError::NaN { index: 1 })
+        );
+    }
+}
@@ -0,0 +1,450 @@
+//! Time series of the sample data, one per series reference
+//!
+//! The `Period` column holds dates as `YYYY.MM` numbers (`2016.06` is June 2016). Since the
+//! column is numeric in spreadsheets, October sometimes loses its trailing zero: `2016.1`
+//! is read as October, not January (which would be `2016.01`).
+
+use crate::csv_statistics::{CsvStatisticsError, SkippedRow};
+use crate::statistics::RunningMoments;
+use csv::Reader;
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::io::Read;
+use std::str::FromStr;
+
+/// A month of a year
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Period {
+    pub year: i32,
+    /// 1 to 12
+    pub month: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidPeriod(pub String);
+
+impl Display for InvalidPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid period {:?}, expected YYYY.MM", self.0)
+    }
+}
+
+impl std::error::Error for InvalidPeriod {}
+
+impl Period {
+    pub fn new(year: i32, month: u32) -> Option<Self> {
+        (1..=12).contains(&month).then_some(Self { year, month })
+    }
+
+    /// Months since January of year 0, to measure distances between periods
+    pub fn months(&self) -> i64 {
+        self.year as i64 * 12 + self.month as i64 - 1
+    }
+
+    /// 1 to 4
+    pub fn quarter(&self) -> u32 {
+        (self.month - 1) / 3 + 1
+    }
+
+    /// The same period one year earlier
+    pub fn previous_year(&self) -> Self {
+        Self {
+            year: self.year - 1,
+            month: self.month,
+        }
+    }
+}
+
+impl FromStr for Period {
+    type Err = InvalidPeriod;
+
+    /// Parses `YYYY.MM`, `YYYY.M` (with `.1` meaning October) or `YYYY-MM`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidPeriod(s.to_string());
+        let s = s.trim();
+        let separator = s.find(['.', '-']).ok_or_else(invalid)?;
+        let year = s[..separator].parse().map_err(|_| invalid())?;
+        let digits = &s[separator + 1..];
+        let month = match digits {
+            // the periods were saved as numbers, 2016.10 became 2016.1
+            "1" if s[separator..].starts_with('.') => 10,
+            _ => digits.parse().map_err(|_| invalid())?,
+        };
+        Period::new(year, month).ok_or_else(invalid)
+    }
+}
+
+impl Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{:02}", self.year, self.month)
+    }
+}
+
+impl Serialize for Period {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Length of the periods of a resampled series
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Frequency {
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+impl Frequency {
+    /// The period a month falls in, named by its last month (like the quarters of the sample data)
+    pub fn bucket(&self, period: Period) -> Period {
+        let month = match self {
+            Frequency::Monthly => period.month,
+            Frequency::Quarterly => period.quarter() * 3,
+            Frequency::Yearly => 12,
+        };
+        Period {
+            year: period.year,
+            month,
+        }
+    }
+}
+
+/// How the values falling in the same period are combined when resampling
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Aggregation {
+    /// For levels, e.g. prices
+    #[default]
+    Mean,
+    /// For flows, e.g. sales
+    Sum,
+    /// For stocks, e.g. the number of employees at the end of the period
+    Last,
+}
+
+/// Values sorted by period, at most one per period
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TimeSeries {
+    pub points: Vec<(Period, f64)>,
+}
+
+/// Least squares line through the values, with time measured in years
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Trend {
+    pub slope_per_year: f64,
+    /// Value of the line at the first period
+    pub intercept: f64,
+    /// Share of the variance explained by the line, from 0 to 1
+    pub r_squared: f64,
+}
+
+/// A value far from the mean of its series
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Anomaly {
+    pub period: Period,
+    pub value: f64,
+    pub z_score: f64,
+}
+
+impl TimeSeries {
+    /// Sorts the points; when a period appears more than once, the last value wins
+    pub fn new(points: impl IntoIterator<Item = (Period, f64)>) -> Self {
+        let by_period: BTreeMap<Period, f64> = points.into_iter().collect();
+        Self {
+            points: by_period.into_iter().collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn get(&self, period: Period) -> Option<f64> {
+        self.points
+            .binary_search_by_key(&period, |(p, _)| *p)
+            .ok()
+            .map(|i| self.points[i].1)
+    }
+
+    /// Combines the values of every period of the given frequency
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use week1_challenge::timeseries::{Aggregation, Frequency, Period, TimeSeries};
+    ///
+    /// let month = |m| Period::new(2020, m).unwrap();
+    /// let monthly = TimeSeries::new([(month(1), 1.0), (month(2), 2.0), (month(4), 4.0)]);
+    /// let quarterly = monthly.resample(Frequency::Quarterly, Aggregation::Sum);
+    /// assert_eq!(quarterly.points, vec![(month(3), 3.0), (month(6), 4.0)]);
+    /// ```
+    pub fn resample(&self, frequency: Frequency, aggregation: Aggregation) -> Self {
+        let mut buckets: BTreeMap<Period, Vec<f64>> = BTreeMap::new();
+        for &(period, value) in &self.points {
+            buckets
+                .entry(frequency.bucket(period))
+                .or_default()
+                .push(value);
+        }
+        let points = buckets.into_iter().map(|(period, values)| {
+            let value = match aggregation {
+                Aggregation::Mean => values.iter().sum::<f64>() / values.len() as f64,
+                Aggregation::Sum => values.iter().sum(),
+                Aggregation::Last => values[values.len() - 1],
+            };
+            (period, value)
+        });
+        Self::new(points)
+    }
+
+    /// Moments of every window of `window` consecutive points, keyed by the last period of the window
+    fn rolling(&self, window: usize) -> impl Iterator<Item = (Period, RunningMoments)> + '_ {
+        self.points.windows(window.max(1)).map(|points| {
+            let mut moments = RunningMoments::new();
+            moments.extend(points.iter().map(|(_, value)| *value));
+            (points[points.len() - 1].0, moments)
+        })
+    }
+
+    /// Mean of every window of `window` consecutive points
+    pub fn rolling_mean(&self, window: usize) -> Self {
+        Self::new(
+            self.rolling(window)
+                .filter_map(|(period, moments)| Some((period, moments.mean()?))),
+        )
+    }
+
+    /// Sample standard deviation of every window of `window` consecutive points (`window` > 1)
+    pub fn rolling_std(&self, window: usize) -> Self {
+        Self::new(
+            self.rolling(window)
+                .filter_map(|(period, moments)| Some((period, moments.sample_variance()?.sqrt()))),
+        )
+    }
+
+    /// Relative change from the same period of the previous year, in percent.
+    /// Periods without a (non-zero) value one year earlier are left out.
+    pub fn year_over_year(&self) -> Self {
+        Self::new(self.points.iter().filter_map(|&(period, value)| {
+            let previous = self.get(period.previous_year())?;
+            (previous != 0.0).then(|| (period, (value - previous) / previous.abs() * 100.0))
+        }))
+    }
+
+    /// Least squares fit, `None` with fewer than two periods
+    pub fn linear_trend(&self) -> Option<Trend> {
+        let (first, _) = *self.points.first()?;
+        let years: Vec<f64> = self
+            .points
+            .iter()
+            .map(|(period, _)| (period.months() - first.months()) as f64 / 12.0)
+            .collect();
+        let n = self.len() as f64;
+        let mean_x = years.iter().sum::<f64>() / n;
+        let mean_y = self.points.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+        let (mut sxx, mut sxy, mut syy) = (0.0, 0.0, 0.0);
+        for (x, (_, y)) in years.iter().zip(&self.points) {
+            sxx += (x - mean_x).powi(2);
+            sxy += (x - mean_x) * (y - mean_y);
+            syy += (y - mean_y).powi(2);
+        }
+        if sxx == 0.0 {
+            return None;
+        }
+        let slope = sxy / sxx;
+        Some(Trend {
+            slope_per_year: slope,
+            intercept: mean_y - slope * mean_x,
+            r_squared: if syy == 0.0 {
+                1.0
+            } else {
+                sxy * sxy / (sxx * syy)
+            },
+        })
+    }
+
+    /// Values whose z-score (distance from the mean, in sample standard deviations)
+    /// is at least `threshold`
+    pub fn anomalies(&self, threshold: f64) -> Vec<Anomaly> {
+        let mut moments = RunningMoments::new();
+        moments.extend(self.points.iter().map(|(_, value)| *value));
+        let (Some(mean), Some(variance)) = (moments.mean(), moments.sample_variance()) else {
+            return Vec::new();
+        };
+        if variance == 0.0 {
+            return Vec::new();
+        }
+        let std_dev = variance.sqrt();
+        self.points
+            .iter()
+            .map(|&(period, value)| Anomaly {
+                period,
+                value,
+                z_score: (value - mean) / std_dev,
+            })
+            .filter(|anomaly| anomaly.z_score.abs() >= threshold)
+            .collect()
+    }
+}
+
+/// Every series of a CSV file, keyed by series reference
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SeriesSet {
+    pub series: BTreeMap<String, TimeSeries>,
+    pub skipped: Vec<SkippedRow>,
+}
+
+impl SeriesSet {
+    /// Reads the `reference`, `period` and `value` columns of a CSV with a header row
+    pub fn from_reader<R: Read>(
+        reader: R,
+        reference: &str,
+        period: &str,
+        value: &str,
+    ) -> Result<Self, CsvStatisticsError> {
+        let mut reader = Reader::from_reader(reader);
+        let headers = reader.headers()?.clone();
+        let column = |name: &str| {
+            headers
+                .iter()
+                .position(|header| header == name)
+                .ok_or_else(|| CsvStatisticsError::UnknownColumn(name.to_string()))
+        };
+        let (reference, period_column, value_column) =
+            (column(reference)?, column(period)?, column(value)?);
+
+        let mut points: BTreeMap<String, Vec<(Period, f64)>> = BTreeMap::new();
+        let mut skipped = Vec::new();
+        for (i, record) in reader.records().enumerate() {
+            let skip = |column: &str, reason: String| SkippedRow {
+                line: i as u64 + 2,
+                column: Some(column.to_string()),
+                reason,
+            };
+            let record = match record {
+                Ok(record) => record,
+                Err(e) => {
+                    skipped.push(SkippedRow {
+                        line: i as u64 + 2,
+                        column: None,
+                        reason: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+            let parsed_period = match record[period_column].parse::<Period>() {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    skipped.push(skip(period, e.to_string()));
+                    continue;
+                }
+            };
+            let parsed_value = match record[value_column].trim() {
+                "" => Err("missing value".to_string()),
+                cell => cell
+                    .parse::<f64>()
+                    .map_err(|_| format!("not a number: {}", cell)),
+            };
+            match parsed_value {
+                Ok(parsed) => points
+                    .entry(record[reference].to_string())
+                    .or_default()
+                    .push((parsed_period, parsed)),
+                Err(reason) => skipped.push(skip(value, reason)),
+            }
+        }
+
+        Ok(Self {
+            series: points
+                .into_iter()
+                .map(|(reference, points)| (reference, TimeSeries::new(points)))
+                .collect(),
+            skipped,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn period(s: &str) -> Period {
+        s.parse().unwrap()
+    }
+
+    fn quarterly(values: &[f64]) -> TimeSeries {
+        TimeSeries::new(values.iter().enumerate().map(|(i, value)| {
+            let months = 2016 * 12 + 2 + 3 * i as i64;
+            let period = Period::new((months / 12) as i32, (months % 12) as u32 + 1).unwrap();
+            (period, *value)
+        }))
+    }
+
+    #[test]
+    fn periods_parse_like_the_sample_data() {
+        assert_eq!(period("2016.06"), Period::new(2016, 6).unwrap());
+        assert_eq!(period("2016.1"), Period::new(2016, 10).unwrap());
+        assert_eq!(period("2020-1"), Period::new(2020, 1).unwrap());
+        assert_eq!(period("2016-12").quarter(), 4);
+        assert!("2016.13".parse::<Period>().is_err());
+        assert!("June".parse::<Period>().is_err());
+        assert_eq!(period("2016.03").to_string(), "2016-03");
+    }
+
+    #[test]
+    fn rolling_windows() {
+        let series = quarterly(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+        let means: Vec<f64> = series.rolling_mean(2).points.iter().map(|p| p.1).collect();
+        assert_eq!(means, vec![1.5, 2.5, 3.5, 4.5]);
+        let stds = series.rolling_std(3);
+        assert_eq!(stds.points[0], (period("2016.09"), 1.0));
+        assert_eq!(stds.len(), 3);
+    }
+
+    #[test]
+    fn year_over_year_needs_the_previous_year() {
+        let series = quarterly(&[100.0, 1.0, 1.0, 1.0, 110.0, 1.0]);
+        let yoy = series.year_over_year();
+        assert_eq!(yoy.len(), 2);
+        assert!((yoy.get(period("2017.03")).unwrap() - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn trend_of_a_straight_line() {
+        // +1 per quarter is +4 per year
+        let series = quarterly(&[10.0, 11.0, 12.0, 13.0, 14.0]);
+        let trend = series.linear_trend().unwrap();
+        assert!((trend.slope_per_year - 4.0).abs() < 1e-9);
+        assert!((trend.intercept - 10.0).abs() < 1e-9);
+        assert!((trend.r_squared - 1.0).abs() < 1e-9);
+        assert_eq!(quarterly(&[1.0]).linear_trend(), None);
+    }
+
+    #[test]
+    fn anomalies_stand_out() {
+        let series = quarterly(&[10.0, 11.0, 10.0, 9.0, 10.0, 11.0, 10.0, 9.0, 40.0]);
+        let anomalies = series.anomalies(2.0);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].value, 40.0);
+        assert!(quarterly(&[5.0, 5.0]).anomalies(0.0).is_empty());
+    }
+
+    #[test]
+    fn reads_one_series_per_reference() {
+        let csv = "Series_reference,Period,Value\nA,2016.06,1\nA,2016.03,2\nB,2016.1,3\nB,2016.12,\nB,soon,4\n";
+        let set =
+            SeriesSet::from_reader(csv.as_bytes(), "Series_reference", "Period", "Value").unwrap();
+        assert_eq!(
+            set.series["A"].points,
+            vec![(period("2016.03"), 2.0), (period("2016.06"), 1.0)]
+        );
+        assert_eq!(set.series["B"].points, vec![(period("2016.10"), 3.0)]);
+        let lines: Vec<u64> = set.skipped.iter().map(|row| row.line).collect();
+        assert_eq!(lines, vec![5, 6]);
+    }
+}
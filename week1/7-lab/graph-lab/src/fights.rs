@@ -0,0 +1,195 @@
+use crate::fighter::Fighter;
+use chrono::NaiveDate;
+use csv::Reader;
+use graph_utils::DiGraphBuilder;
+use petgraph::graph::DiGraph;
+use petgraph::Direction;
+use serde::Deserialize;
+use std::error::Error;
+use std::fmt;
+use std::io::Read;
+
+/// How a fight ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Method {
+    #[serde(rename = "KO/TKO")]
+    Knockout,
+    Submission,
+    Decision,
+    /// Nobody won: `winner` and `loser` are just the two opponents
+    Draw,
+    /// The result was overturned, the fight does not count for the ratings
+    #[serde(rename = "No Contest")]
+    NoContest,
+}
+
+impl Method {
+    /// Score of the fighter in the `winner` column: 1 for a win, 0.5 for a draw,
+    /// `None` when the fight has no result.
+    pub fn score(&self) -> Option<f64> {
+        match self {
+            Method::Knockout | Method::Submission | Method::Decision => Some(1.0),
+            Method::Draw => Some(0.5),
+            Method::NoContest => None,
+        }
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let label = match self {
+            Method::Knockout => "KO/TKO",
+            Method::Submission => "Submission",
+            Method::Decision => "Decision",
+            Method::Draw => "Draw",
+            Method::NoContest => "No Contest",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// A single fight, as found in a row of the fights CSV file
+///
+/// The CSV header is `date,event,winner,loser,method`, dates are written as `YYYY-MM-DD`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Fight {
+    pub date: NaiveDate,
+    pub event: String,
+    pub winner: String,
+    pub loser: String,
+    pub method: Method,
+}
+
+impl fmt::Display for Fight {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let verb = match self.method {
+            Method::Draw => "drew with",
+            Method::NoContest => "no contest with",
+            _ => "def.",
+        };
+        write!(
+            f,
+            "{} ({}): {} {} {} by {}",
+            self.date, self.event, self.winner, verb, self.loser, self.method
+        )
+    }
+}
+
+/// Win/loss record of a fighter
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Record {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    pub no_contests: u32,
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}-{}", self.wins, self.losses, self.draws)?;
+        if self.no_contests > 0 {
+            write!(f, " ({} NC)", self.no_contests)?;
+        }
+        Ok(())
+    }
+}
+
+/// Fight history as a directed graph
+///
+/// Every fighter is a node, every fight is an edge going from the winner to the loser.
+///
+/// # Example
+///
+/// ```rust
+/// use graph_lab::FightHistory;
+///
+/// let csv = "date,event,winner,loser,method
+/// 2016-03-05,UFC 196,Nate Diaz,Conor McGregor,Submission
+/// 2016-08-20,UFC 202,Conor McGregor,Nate Diaz,Decision
+/// ";
+/// let history = FightHistory::from_reader(csv.as_bytes()).unwrap();
+///
+/// assert_eq!(history.graph().node_count(), 2);
+/// assert_eq!(history.record("Nate Diaz").unwrap().to_string(), "1-1-0");
+/// ```
+#[derive(Default)]
+pub struct FightHistory {
+    fights: DiGraphBuilder<String, Fighter, Fight>,
+}
+
+impl FightHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the fights from a CSV file, see [Fight] for the expected columns
+    pub fn from_csv(path: &str) -> Result<Self, Box<dyn Error>> {
+        let file = std::fs::File::open(path)?;
+        Self::from_reader(file)
+    }
+
+    /// Reads the fights from any CSV source, failing on the first malformed row
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, Box<dyn Error>> {
+        let mut history = Self::new();
+        for (line, fight) in Reader::from_reader(reader).deserialize().enumerate() {
+            let fight: Fight =
+                fight.map_err(|e| format!("Invalid fight on row {}: {}", line + 1, e))?;
+            history.add_fight(fight);
+        }
+        Ok(history)
+    }
+
+    /// Adds a fight, registering the fighters that are not known yet
+    pub fn add_fight(&mut self, fight: Fight) {
+        for name in [&fight.winner, &fight.loser] {
+            self.fights.add_node(name.clone(), Fighter::new(name));
+        }
+        let (winner, loser) = (fight.winner.clone(), fight.loser.clone());
+        self.fights
+            .add_edge(&winner, &loser, fight)
+            .expect("Both fighters have just been added");
+    }
+
+    /// The underlying graph, edges go from the winner to the loser
+    pub fn graph(&self) -> &DiGraph<Fighter, Fight> {
+        self.fights.graph()
+    }
+
+    /// All the fighters, in order of first appearance
+    pub fn fighters(&self) -> impl Iterator<Item = &Fighter> {
+        self.graph().node_weights()
+    }
+
+    /// All the fights sorted by date. Fights on the same date keep their insertion order.
+    pub fn chronological(&self) -> Vec<&Fight> {
+        let mut fights: Vec<&Fight> = self.graph().edge_weights().collect();
+        fights.sort_by_key(|fight| fight.date);
+        fights
+    }
+
+    /// Win/loss record of a fighter, `None` if the fighter is unknown
+    pub fn record(&self, name: &str) -> Option<Record> {
+        let node = self.fights.node_index(name)?;
+        let graph = self.graph();
+        let mut record = Record::default();
+
+        let fights = graph
+            .edges_directed(node, Direction::Outgoing)
+            .map(|edge| (edge.weight(), true))
+            .chain(
+                graph
+                    .edges_directed(node, Direction::Incoming)
+                    .map(|edge| (edge.weight(), false)),
+            );
+
+        for (fight, won) in fights {
+            match fight.method.score() {
+                None => record.no_contests += 1,
+                Some(score) if score < 1.0 => record.draws += 1,
+                Some(_) if won => record.wins += 1,
+                Some(_) => record.losses += 1,
+            }
+        }
+        Some(record)
+    }
+}
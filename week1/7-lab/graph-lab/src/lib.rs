@@ -0,0 +1,15 @@
+//! # Graph lab
+//!
+//! `graph-lab` models the fight history of a few MMA fighters as a directed graph
+//! (an edge goes from the winner to the loser) and ranks them by replaying the fights
+//! in chronological order through a rating system (Elo or Glicko-2).
+
+pub mod fighter;
+pub mod fights;
+pub mod rating;
+
+pub use fighter::Fighter;
+pub use fights::{Fight, FightHistory, Method, Record};
+pub use rating::elo::Elo;
+pub use rating::glicko2::Glicko2;
+pub use rating::{RatingSystem, Ratings};
@@ -1,82 +1,68 @@
-use petgraph::graph::{NodeIndex, UnGraph};
-use petgraph::Direction;
-use std::fmt;
+use clap::Parser;
+use graph_lab::{Elo, FightHistory, Glicko2, Ratings};
+use graph_utils::render::{Colouring, Renderer};
+use std::process;
 
-#[derive(Debug)]
-struct Fighter {
-    name: String,
-}
-/*
-This is a bit like the following Python code:
-
-class Fighter:
-    def __init__(self, name):
-        self.name = name
-*/
-impl Fighter {
-    fn new(name: &str) -> Self {
-        Self {
-            name: name.to_string(),
-        }
-    }
-}
-
-impl fmt::Display for Fighter {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.name)
-    }
-}
+/// Rank MMA fighters by replaying their fight history
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// CSV file with the fights (date,event,winner,loser,method)
+    #[arg(default_value = "data/fights.csv")]
+    fights: String,
 
-fn add_edge(graph: &mut UnGraph<&Fighter, f32>, nodes: &[NodeIndex], a: usize, b: usize) {
-    graph.add_edge(nodes[a], nodes[b], 1.0);
+    /// Write the fight graph to this file (.svg or .dot)
+    #[arg(long)]
+    render: Option<String>,
 }
 
 fn main() {
-    let mut graph = UnGraph::new_undirected();
+    let args = Args::parse();
+    let history = FightHistory::from_csv(&args.fights).unwrap_or_else(|e| {
+        eprintln!("Could not load fights from {}: {}", args.fights, e);
+        process::exit(1);
+    });
 
-    let fighters = [
-        Fighter::new("Dustin Poirier"),
-        Fighter::new("Khabib Nurmagomedov"),
-        Fighter::new("Jose Aldo"),
-        Fighter::new("Conor McGregor"),
-        Fighter::new("Nate Diaz"),
-    ];
+    println!("Fights");
+    for fight in history.chronological() {
+        println!("- {}", fight);
+    }
 
-    let fighter_nodes: Vec<NodeIndex> = fighters
-        .iter()
-        .map(|fighter| graph.add_node(fighter))
-        .collect();
+    println!("\nRecords (W-L-D)");
+    for fighter in history.fighters() {
+        let record = history.record(&fighter.name).unwrap_or_default();
+        println!("- {}: {}", fighter, record);
+    }
 
-    add_edge(&mut graph, &fighter_nodes, 0, 1); // Dustin Poirier vs. Khabib Nurmagomedov
-    add_edge(&mut graph, &fighter_nodes, 1, 3); // Khabib Nurmagomedov vs. Conor McGregor
-    add_edge(&mut graph, &fighter_nodes, 3, 0); // Conor McGregor vs. Dustin Poirier
-    add_edge(&mut graph, &fighter_nodes, 3, 2); // Conor McGregor vs. Jose Aldo
-    add_edge(&mut graph, &fighter_nodes, 3, 4); // Conor McGregor vs. Nate Diaz
-    add_edge(&mut graph, &fighter_nodes, 0, 4); // Dustin Poirier vs. Nate Diaz
-    add_edge(&mut graph, &fighter_nodes, 2, 4); // Jose Aldo vs. Nate Diaz
+    let elo = Elo::default();
+    let elo_ratings = Ratings::replay(&history, &elo);
+    println!("\nElo ranking\n{}", elo_ratings.ranking_report(&elo));
 
-    for (i, &node) in fighter_nodes.iter().enumerate() {
-        let name = &fighters[i].name;
-        let degree = graph.edges_directed(node, Direction::Outgoing).count() as f32;
-        let closeness = 1.0 / degree;
-        println!("The closeness centrality of {} is {:.2}", name, closeness);
+    let glicko = Glicko2::default();
+    let glicko_ratings = Ratings::replay(&history, &glicko);
+    println!(
+        "Glicko-2 ranking (by rating - 2 deviations)\n{}",
+        glicko_ratings.ranking_report(&glicko)
+    );
 
-        // Explanation
-        match name.as_str() {
-            "Conor McGregor" => println!(
-                "{} has the lowest centrality because he has fought with all other fighters in the network. In this context, a lower centrality value means a higher number of fights.",
-                name
-            ),
-            "Dustin Poirier" | "Nate Diaz" => println!(
-                "{} has a centrality of {:.2}, implying they had less fights compared to Conor McGregor but more than Khabib Nurmagomedov and Jose Aldo.",
-                name, closeness
-            ),
-            "Khabib Nurmagomedov" | "Jose Aldo" => println!(
-                "{} has the highest centrality of {:.2} as they have fought with the least number of fighters.",
-                name, closeness
-            ),
-            _ => {}
+    println!("Rating history (Elo / Glicko-2)");
+    for fighter in history.fighters() {
+        println!("{}", fighter);
+        let elo_history = elo_ratings.history(&fighter.name);
+        let glicko_history = glicko_ratings.history(&fighter.name);
+        for ((date, elo), (_, glicko)) in elo_history.iter().zip(glicko_history) {
+            println!("  {}  {:>5}  {}", date, elo, glicko);
         }
-        println!("-----------------");
     }
-}
\ No newline at end of file
+
+    if let Some(path) = args.render {
+        let renderer = Renderer::new(history.graph())
+            .edge_label(|fight| format!("{} {}", fight.event, fight.method))
+            .colouring(Colouring::Communities);
+        if let Err(e) = renderer.write(&path) {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+        println!("\nFight graph rendered to {}", path);
+    }
+}
@@ -0,0 +1,84 @@
+use crate::rating::RatingSystem;
+use std::fmt;
+
+/// An Elo rating, displayed rounded to the unit
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct EloRating(pub f64);
+
+impl fmt::Display for EloRating {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.0}", self.0)
+    }
+}
+
+/// The classic Elo rating system
+///
+/// The expected score of `a` against `b` is `1 / (1 + 10^((b - a) / 400))`
+/// and after each fight both ratings move by `k * (score - expected)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Elo {
+    pub initial: f64,
+    pub k: f64,
+}
+
+impl Default for Elo {
+    fn default() -> Self {
+        Self {
+            initial: 1500.0,
+            k: 32.0,
+        }
+    }
+}
+
+impl Elo {
+    /// Expected score of a fighter rated `a` against a fighter rated `b`
+    pub fn expected_score(a: f64, b: f64) -> f64 {
+        1.0 / (1.0 + 10f64.powf((b - a) / 400.0))
+    }
+}
+
+impl RatingSystem for Elo {
+    type Rating = EloRating;
+
+    fn initial_rating(&self) -> EloRating {
+        EloRating(self.initial)
+    }
+
+    fn rate(&self, a: EloRating, b: EloRating, score: f64) -> (EloRating, EloRating) {
+        let delta = self.k * (score - Self::expected_score(a.0, b.0));
+        (EloRating(a.0 + delta), EloRating(b.0 - delta))
+    }
+
+    fn strength(&self, rating: &EloRating) -> f64 {
+        rating.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn even_fight_moves_half_k() {
+        let elo = Elo::default();
+        let (a, b) = elo.rate(EloRating(1500.0), EloRating(1500.0), 1.0);
+        assert_eq!(a, EloRating(1516.0));
+        assert_eq!(b, EloRating(1484.0));
+    }
+
+    #[test]
+    fn draw_between_equals_changes_nothing() {
+        let elo = Elo::default();
+        let even = EloRating(1500.0);
+        assert_eq!(elo.rate(even, even, 0.5), (even, even));
+    }
+
+    #[test]
+    fn upsets_are_worth_more() {
+        let elo = Elo::default();
+        let (low, high) = (EloRating(1400.0), EloRating(1600.0));
+        let (underdog, _) = elo.rate(low, high, 1.0);
+        let (favourite, _) = elo.rate(high, low, 1.0);
+        assert!(underdog.0 - low.0 > favourite.0 - high.0);
+    }
+}
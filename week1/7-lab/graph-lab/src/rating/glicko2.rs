@@ -0,0 +1,220 @@
+use crate::rating::RatingSystem;
+use std::f64::consts::PI;
+use std::fmt;
+
+/// Conversion factor between the Glicko and the Glicko-2 scale
+const SCALE: f64 = 173.7178;
+
+/// Convergence tolerance of the volatility iteration
+const EPSILON: f64 = 0.000001;
+
+/// A Glicko-2 rating, on the familiar Glicko scale (1500 ± 350 for a newcomer)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glicko2Rating {
+    pub rating: f64,
+    /// Rating deviation: how uncertain we are about `rating`
+    pub deviation: f64,
+    /// How erratic the performances of the fighter are
+    pub volatility: f64,
+}
+
+impl fmt::Display for Glicko2Rating {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.0} ± {:.0}", self.rating, self.deviation)
+    }
+}
+
+/// The [Glicko-2](http://www.glicko.net/glicko/glicko2.pdf) rating system by Mark Glickman
+///
+/// Fighters rarely fight more than a few times a year, so every fight is treated
+/// as its own rating period with a single game.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glicko2 {
+    pub initial: Glicko2Rating,
+    /// System constant constraining the change in volatility, usually between 0.3 and 1.2
+    pub tau: f64,
+}
+
+impl Default for Glicko2 {
+    fn default() -> Self {
+        Self {
+            initial: Glicko2Rating {
+                rating: 1500.0,
+                deviation: 350.0,
+                volatility: 0.06,
+            },
+            tau: 0.5,
+        }
+    }
+}
+
+fn g(phi: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * phi.powi(2) / PI.powi(2)).sqrt()
+}
+
+fn expected(mu: f64, mu_j: f64, phi_j: f64) -> f64 {
+    1.0 / (1.0 + (-g(phi_j) * (mu - mu_j)).exp())
+}
+
+impl Glicko2 {
+    /// Updates `player` after a rating period against the given `(opponent, score)` results.
+    ///
+    /// This follows the steps of Glickman's paper; an empty period only increases the deviation.
+    pub fn update(&self, player: Glicko2Rating, results: &[(Glicko2Rating, f64)]) -> Glicko2Rating {
+        // Step 2: convert to the Glicko-2 scale
+        let mu = (player.rating - 1500.0) / SCALE;
+        let phi = player.deviation / SCALE;
+        let sigma = player.volatility;
+
+        if results.is_empty() {
+            let phi_star = (phi.powi(2) + sigma.powi(2)).sqrt();
+            return Glicko2Rating {
+                deviation: phi_star * SCALE,
+                ..player
+            };
+        }
+
+        let opponents: Vec<(f64, f64, f64)> = results
+            .iter()
+            .map(|(opponent, score)| {
+                let mu_j = (opponent.rating - 1500.0) / SCALE;
+                let phi_j = opponent.deviation / SCALE;
+                (mu_j, phi_j, *score)
+            })
+            .collect();
+
+        // Step 3 and 4: estimated variance and improvement
+        let v = 1.0
+            / opponents
+                .iter()
+                .map(|&(mu_j, phi_j, _)| {
+                    let e = expected(mu, mu_j, phi_j);
+                    g(phi_j).powi(2) * e * (1.0 - e)
+                })
+                .sum::<f64>();
+        let improvement: f64 = opponents
+            .iter()
+            .map(|&(mu_j, phi_j, score)| g(phi_j) * (score - expected(mu, mu_j, phi_j)))
+            .sum();
+        let delta = v * improvement;
+
+        // Step 5: new volatility
+        let sigma = self.volatility(phi, sigma, v, delta);
+
+        // Step 6 and 7: new deviation and rating
+        let phi_star = (phi.powi(2) + sigma.powi(2)).sqrt();
+        let phi = 1.0 / (1.0 / phi_star.powi(2) + 1.0 / v).sqrt();
+        let mu = mu + phi.powi(2) * improvement;
+
+        // Step 8: back to the Glicko scale
+        Glicko2Rating {
+            rating: mu * SCALE + 1500.0,
+            deviation: phi * SCALE,
+            volatility: sigma,
+        }
+    }
+
+    /// Solves for the new volatility with the Illinois algorithm (step 5 of the paper)
+    fn volatility(&self, phi: f64, sigma: f64, v: f64, delta: f64) -> f64 {
+        let a = sigma.powi(2).ln();
+        let tau = self.tau;
+        let f = |x: f64| {
+            let ex = x.exp();
+            let d = phi.powi(2) + v + ex;
+            ex * (delta.powi(2) - d) / (2.0 * d.powi(2)) - (x - a) / tau.powi(2)
+        };
+
+        let mut big_a = a;
+        let mut big_b = if delta.powi(2) > phi.powi(2) + v {
+            (delta.powi(2) - phi.powi(2) - v).ln()
+        } else {
+            let mut k = 1.0;
+            while f(a - k * tau) < 0.0 {
+                k += 1.0;
+            }
+            a - k * tau
+        };
+
+        let mut f_a = f(big_a);
+        let mut f_b = f(big_b);
+        while (big_b - big_a).abs() > EPSILON {
+            let big_c = big_a + (big_a - big_b) * f_a / (f_b - f_a);
+            let f_c = f(big_c);
+            if f_c * f_b <= 0.0 {
+                big_a = big_b;
+                f_a = f_b;
+            } else {
+                f_a /= 2.0;
+            }
+            big_b = big_c;
+            f_b = f_c;
+        }
+
+        (big_a / 2.0).exp()
+    }
+}
+
+impl RatingSystem for Glicko2 {
+    type Rating = Glicko2Rating;
+
+    fn initial_rating(&self) -> Glicko2Rating {
+        self.initial
+    }
+
+    fn rate(
+        &self,
+        a: Glicko2Rating,
+        b: Glicko2Rating,
+        score: f64,
+    ) -> (Glicko2Rating, Glicko2Rating) {
+        (
+            self.update(a, &[(b, score)]),
+            self.update(b, &[(a, 1.0 - score)]),
+        )
+    }
+
+    /// Conservative estimate: the rating minus two deviations,
+    /// so that a fighter with a single lucky win does not top the ranking
+    fn strength(&self, rating: &Glicko2Rating) -> f64 {
+        rating.rating - 2.0 * rating.deviation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rating(rating: f64, deviation: f64) -> Glicko2Rating {
+        Glicko2Rating {
+            rating,
+            deviation,
+            volatility: 0.06,
+        }
+    }
+
+    /// The worked example at the end of Glickman's paper
+    #[test]
+    fn matches_the_paper_example() {
+        let glicko = Glicko2::default();
+        let player = rating(1500.0, 200.0);
+        let results = [
+            (rating(1400.0, 30.0), 1.0),
+            (rating(1550.0, 100.0), 0.0),
+            (rating(1700.0, 300.0), 0.0),
+        ];
+
+        let updated = glicko.update(player, &results);
+        assert!((updated.rating - 1464.06).abs() < 0.01);
+        assert!((updated.deviation - 151.52).abs() < 0.01);
+        assert!((updated.volatility - 0.05999).abs() < 0.00001);
+    }
+
+    #[test]
+    fn inactivity_increases_deviation() {
+        let glicko = Glicko2::default();
+        let player = rating(1500.0, 200.0);
+        let updated = glicko.update(player, &[]);
+        assert_eq!(updated.rating, 1500.0);
+        assert!(updated.deviation > 200.0);
+    }
+}
@@ -0,0 +1,143 @@
+//! Rating systems for the fight history
+//!
+//! A [RatingSystem] knows how to update the ratings of two opponents after a single fight.
+//! [Ratings::replay] feeds it every fight of a [FightHistory] in chronological order
+//! and keeps both the final rating and the rating-over-time of each fighter.
+
+pub mod elo;
+pub mod glicko2;
+
+use crate::fights::FightHistory;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::fmt::{Display, Write};
+
+pub trait RatingSystem {
+    type Rating: Copy + Display;
+
+    /// Rating of a fighter before their first fight
+    fn initial_rating(&self) -> Self::Rating;
+
+    /// Rates a single fight between `a` and `b`.
+    ///
+    /// `score` is the score of `a`: 1 for a win, 0.5 for a draw, 0 for a loss.
+    /// Returns the new ratings of `a` and `b`.
+    fn rate(&self, a: Self::Rating, b: Self::Rating, score: f64) -> (Self::Rating, Self::Rating);
+
+    /// Number used to rank the fighters, the higher the better
+    fn strength(&self, rating: &Self::Rating) -> f64;
+}
+
+/// Ratings of every fighter after replaying a fight history
+#[derive(Debug, Clone)]
+pub struct Ratings<R> {
+    current: HashMap<String, R>,
+    history: HashMap<String, Vec<(NaiveDate, R)>>,
+}
+
+impl<R: Copy + Display> Ratings<R> {
+    /// Replays all the fights in chronological order. No contests are skipped.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use graph_lab::{Elo, FightHistory, Ratings};
+    ///
+    /// let csv = "date,event,winner,loser,method
+    /// 2014-09-27,UFC 178,Conor McGregor,Dustin Poirier,KO/TKO
+    /// 2021-01-24,UFC 257,Dustin Poirier,Conor McGregor,KO/TKO
+    /// 2021-07-10,UFC 264,Dustin Poirier,Conor McGregor,KO/TKO
+    /// ";
+    /// let history = FightHistory::from_reader(csv.as_bytes()).unwrap();
+    /// let ratings = Ratings::replay(&history, &Elo::default());
+    ///
+    /// assert_eq!(ratings.ranking(&Elo::default())[0].0, "Dustin Poirier");
+    /// assert_eq!(ratings.history("Conor McGregor").len(), 3);
+    /// ```
+    pub fn replay<S>(fights: &FightHistory, system: &S) -> Self
+    where
+        S: RatingSystem<Rating = R>,
+    {
+        let mut current: HashMap<String, R> = fights
+            .fighters()
+            .map(|fighter| (fighter.name.clone(), system.initial_rating()))
+            .collect();
+        let mut history: HashMap<String, Vec<(NaiveDate, R)>> = HashMap::new();
+
+        for fight in fights.chronological() {
+            let Some(score) = fight.method.score() else {
+                continue;
+            };
+            let (winner, loser) = system.rate(current[&fight.winner], current[&fight.loser], score);
+
+            for (name, rating) in [(&fight.winner, winner), (&fight.loser, loser)] {
+                current.insert(name.clone(), rating);
+                history
+                    .entry(name.clone())
+                    .or_default()
+                    .push((fight.date, rating));
+            }
+        }
+
+        Self { current, history }
+    }
+
+    /// Current rating of a fighter
+    pub fn rating(&self, name: &str) -> Option<R> {
+        self.current.get(name).copied()
+    }
+
+    /// Rating of a fighter after each of their fights, oldest first
+    pub fn history(&self, name: &str) -> &[(NaiveDate, R)] {
+        self.history
+            .get(name)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Fighters sorted from the strongest to the weakest, ties are sorted by name
+    pub fn ranking<S>(&self, system: &S) -> Vec<(&str, R)>
+    where
+        S: RatingSystem<Rating = R>,
+    {
+        let mut ranking: Vec<(&str, R)> = self
+            .current
+            .iter()
+            .map(|(name, rating)| (name.as_str(), *rating))
+            .collect();
+        ranking.sort_by(|(name_a, a), (name_b, b)| {
+            system
+                .strength(b)
+                .total_cmp(&system.strength(a))
+                .then_with(|| name_a.cmp(name_b))
+        });
+        ranking
+    }
+
+    /// Markdown table with the ranking, as printed by the CLI
+    pub fn ranking_report<S>(&self, system: &S) -> String
+    where
+        S: RatingSystem<Rating = R>,
+    {
+        let mut report = String::new();
+        let _ = writeln!(
+            report,
+            "| {:<4} | {:<22} | {:<16} |",
+            "#", "Fighter", "Rating"
+        );
+        let _ = writeln!(
+            report,
+            "|------|------------------------|------------------|"
+        );
+        for (position, (name, rating)) in self.ranking(system).iter().enumerate() {
+            let _ = writeln!(
+                report,
+                "| {:<4} | {:<22} | {:<16} |",
+                position + 1,
+                name,
+                rating.to_string()
+            );
+        }
+        report
+    }
+}
@@ -0,0 +1,249 @@
+use petgraph::graph::{EdgeIndex, Graph, NodeIndex};
+use petgraph::{Directed, EdgeType, Undirected};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Debug, Display};
+use std::hash::Hash;
+
+/// Lookup table from a node key to its index in the built graph
+pub type NodeLookup<K> = HashMap<K, NodeIndex>;
+
+/// Builder for directed graphs
+pub type DiGraphBuilder<K, N, E> = GraphBuilder<K, N, E, Directed>;
+
+/// Builder for undirected graphs
+pub type UnGraphBuilder<K, N, E> = GraphBuilder<K, N, E, Undirected>;
+
+/// Errors raised while building a graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError {
+    /// An edge referenced a key that was never added as a node
+    UnknownNode(String),
+}
+
+impl Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::UnknownNode(key) => write!(f, "Unknown node {}", key),
+        }
+    }
+}
+
+impl Error for GraphError {}
+
+/// Builds a petgraph `Graph` addressing nodes by key instead of by `NodeIndex`.
+///
+/// Every node is registered under a key (any `Eq + Hash` type, e.g. a name).
+/// Adding the same key twice returns the index of the first node, so nodes are never duplicated.
+/// Edges are then added between keys, and the edge weight can be any type.
+///
+/// Whether the graph is directed or not depends on the `Ty` parameter,
+/// see [DiGraphBuilder] and [UnGraphBuilder].
+///
+/// # Example
+///
+/// ```rust
+/// use graph_utils::UnGraphBuilder;
+///
+/// let mut builder = UnGraphBuilder::<&str, &str, f32>::new();
+/// builder.add_node("dante", "Dante");
+/// builder.add_node("virgil", "Virgil");
+/// builder.add_node("dante", "Dante again"); // ignored, the key already exists
+/// builder.add_edge("dante", "virgil", 10.0).unwrap();
+///
+/// let (graph, lookup) = builder.build();
+/// assert_eq!(graph.node_count(), 2);
+/// assert_eq!(graph.edge_count(), 1);
+/// assert_eq!(graph[lookup["dante"]], "Dante");
+/// ```
+#[derive(Clone)]
+pub struct GraphBuilder<K, N, E, Ty = Directed> {
+    graph: Graph<N, E, Ty>,
+    lookup: NodeLookup<K>,
+}
+
+impl<K, N, E, Ty> Default for GraphBuilder<K, N, E, Ty>
+where
+    K: Eq + Hash,
+    Ty: EdgeType,
+{
+    fn default() -> Self {
+        Self {
+            graph: Graph::default(),
+            lookup: HashMap::new(),
+        }
+    }
+}
+
+impl<K, N, E, Ty> GraphBuilder<K, N, E, Ty>
+where
+    K: Eq + Hash,
+    Ty: EdgeType,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a node with the given key and weight and returns its index.
+    ///
+    /// If the key is already known, the graph is left untouched, `weight` is dropped
+    /// and the index of the existing node is returned.
+    pub fn add_node(&mut self, key: K, weight: N) -> NodeIndex {
+        if let Some(&index) = self.lookup.get(&key) {
+            return index;
+        }
+        let index = self.graph.add_node(weight);
+        self.lookup.insert(key, index);
+        index
+    }
+
+    /// Returns the index of the node registered under `key`, if any
+    pub fn node_index<Q>(&self, key: &Q) -> Option<NodeIndex>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.lookup.get(key).copied()
+    }
+
+    /// Adds an edge from `a` to `b` (or between them, for undirected graphs).
+    ///
+    /// Both keys must have been added with [add_node](Self::add_node) before,
+    /// otherwise [GraphError::UnknownNode] is returned.
+    pub fn add_edge<Q>(&mut self, a: &Q, b: &Q, weight: E) -> Result<EdgeIndex, GraphError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + Debug + ?Sized,
+    {
+        let a = self.require(a)?;
+        let b = self.require(b)?;
+        Ok(self.graph.add_edge(a, b, weight))
+    }
+
+    /// Number of distinct nodes added so far
+    pub fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    /// Number of edges added so far
+    pub fn edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    /// Read-only access to the graph being built
+    pub fn graph(&self) -> &Graph<N, E, Ty> {
+        &self.graph
+    }
+
+    /// Consumes the builder, returning the graph and the lookup from key to `NodeIndex`
+    pub fn build(self) -> (Graph<N, E, Ty>, NodeLookup<K>) {
+        (self.graph, self.lookup)
+    }
+
+    fn require<Q>(&self, key: &Q) -> Result<NodeIndex, GraphError>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + Debug + ?Sized,
+    {
+        self.node_index(key)
+            .ok_or_else(|| GraphError::UnknownNode(format!("{:?}", key)))
+    }
+}
+
+/// Shortcuts for graphs where the node weight is the key itself
+impl<K, E, Ty> GraphBuilder<K, K, E, Ty>
+where
+    K: Eq + Hash + Clone,
+    Ty: EdgeType,
+{
+    /// Adds a node whose weight is its own key
+    pub fn add_key(&mut self, key: K) -> NodeIndex {
+        self.add_node(key.clone(), key)
+    }
+
+    /// Adds an edge between two keys, creating the nodes that are missing.
+    ///
+    /// ```rust
+    /// use graph_utils::DiGraphBuilder;
+    ///
+    /// let mut builder = DiGraphBuilder::<&str, &str, ()>::new();
+    /// builder.connect("core", "utils", ());
+    /// builder.connect("cli", "core", ());
+    ///
+    /// let (graph, lookup) = builder.build();
+    /// assert_eq!(graph.node_count(), 3);
+    /// assert!(graph.contains_edge(lookup["cli"], lookup["core"]));
+    /// assert!(!graph.contains_edge(lookup["core"], lookup["cli"]));
+    /// ```
+    pub fn connect(&mut self, a: K, b: K, weight: E) -> EdgeIndex {
+        let a = self.add_key(a);
+        let b = self.add_key(b);
+        self.graph.add_edge(a, b, weight)
+    }
+}
+
+impl<K, E, Ty> Extend<(K, K, E)> for GraphBuilder<K, K, E, Ty>
+where
+    K: Eq + Hash + Clone,
+    Ty: EdgeType,
+{
+    fn extend<I: IntoIterator<Item = (K, K, E)>>(&mut self, edges: I) {
+        for (a, b, weight) in edges {
+            self.connect(a, b, weight);
+        }
+    }
+}
+
+impl<K, E, Ty> FromIterator<(K, K, E)> for GraphBuilder<K, K, E, Ty>
+where
+    K: Eq + Hash + Clone,
+    Ty: EdgeType,
+{
+    fn from_iter<I: IntoIterator<Item = (K, K, E)>>(edges: I) -> Self {
+        let mut builder = Self::new();
+        builder.extend(edges);
+        builder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deduplicates_nodes() {
+        let mut builder = DiGraphBuilder::<String, u32, ()>::new();
+        let first = builder.add_node("a".to_string(), 1);
+        let second = builder.add_node("a".to_string(), 2);
+
+        assert_eq!(first, second);
+        assert_eq!(builder.node_count(), 1);
+        assert_eq!(builder.graph()[first], 1);
+        assert_eq!(builder.node_index("a"), Some(first));
+    }
+
+    #[test]
+    fn unknown_nodes_are_reported() {
+        let mut builder = UnGraphBuilder::<&str, (), f32>::new();
+        builder.add_node("a", ());
+
+        let err = builder.add_edge("a", "b", 1.0).unwrap_err();
+        assert_eq!(err, GraphError::UnknownNode("\"b\"".to_string()));
+        assert_eq!(builder.edge_count(), 0);
+    }
+
+    #[test]
+    fn direction_follows_the_edge_type() {
+        let directed: DiGraphBuilder<u8, u8, ()> = vec![(1, 2, ())].into_iter().collect();
+        let undirected: UnGraphBuilder<u8, u8, ()> = vec![(1, 2, ())].into_iter().collect();
+
+        let (graph, lookup) = directed.build();
+        assert!(graph.is_directed());
+        assert!(graph.find_edge(lookup[&2], lookup[&1]).is_none());
+
+        let (graph, lookup) = undirected.build();
+        assert!(!graph.is_directed());
+        assert!(graph.find_edge(lookup[&2], lookup[&1]).is_some());
+    }
+}
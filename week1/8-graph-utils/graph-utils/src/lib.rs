@@ -0,0 +1,14 @@
+//! # Graph utils
+//!
+//! `graph-utils` collects the graph helpers shared by the week 1 graph crates
+//! (`graph-demo`, `graph-lab` and `week1-challenge`).
+//!
+//! The main entry point is the [GraphBuilder](builder::GraphBuilder), which lets you
+//! describe a petgraph `Graph` in terms of your own node keys instead of raw `NodeIndex` values.
+//! The [Renderer](render::Renderer) exports any petgraph `Graph` as a styled DOT or SVG file.
+
+pub mod builder;
+pub mod metrics;
+pub mod render;
+
+pub use builder::{DiGraphBuilder, GraphBuilder, GraphError, UnGraphBuilder};
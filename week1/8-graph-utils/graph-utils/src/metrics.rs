@@ -0,0 +1,192 @@
+//! Node metrics used to style the rendered graphs
+//!
+//! Every function returns one value per node, indexed by `NodeIndex::index()`.
+
+use petgraph::graph::Graph;
+use petgraph::unionfind::UnionFind;
+use petgraph::visit::EdgeRef;
+use petgraph::{Direction, EdgeType};
+use std::collections::{HashMap, VecDeque};
+
+/// Degree centrality: the share of the other nodes a node is linked to.
+///
+/// For directed graphs both incoming and outgoing edges are counted,
+/// so the value can go up to 2 when a node is linked both ways to everybody.
+///
+/// # Example
+///
+/// ```rust
+/// use graph_utils::metrics::degree_centrality;
+/// use petgraph::graph::UnGraph;
+///
+/// // a star: the centre is linked to everybody, the leaves only to the centre
+/// let graph = UnGraph::<(), ()>::from_edges([(0, 1), (0, 2), (0, 3)]);
+/// assert_eq!(degree_centrality(&graph), vec![1.0, 1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0]);
+/// ```
+pub fn degree_centrality<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Vec<f64> {
+    let others = graph.node_count().saturating_sub(1).max(1) as f64;
+    graph
+        .node_indices()
+        .map(|node| {
+            let mut degree = graph.edges_directed(node, Direction::Outgoing).count();
+            if graph.is_directed() {
+                degree += graph.edges_directed(node, Direction::Incoming).count();
+            }
+            degree as f64 / others
+        })
+        .collect()
+}
+
+/// Harmonic closeness centrality, ignoring edge directions and weights.
+///
+/// The closeness of a node is the average of `1 / distance` to every other node,
+/// unreachable nodes contribute 0, so disconnected graphs are handled as well.
+pub fn closeness_centrality<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Vec<f64> {
+    let others = graph.node_count().saturating_sub(1).max(1) as f64;
+    graph
+        .node_indices()
+        .map(|start| {
+            let mut distances = vec![None; graph.node_count()];
+            let mut queue = VecDeque::from([start]);
+            distances[start.index()] = Some(0usize);
+            let mut closeness = 0.0;
+
+            while let Some(node) = queue.pop_front() {
+                let distance = distances[node.index()].unwrap_or_default();
+                if distance > 0 {
+                    closeness += 1.0 / distance as f64;
+                }
+                for next in graph.neighbors_undirected(node) {
+                    if distances[next.index()].is_none() {
+                        distances[next.index()] = Some(distance + 1);
+                        queue.push_back(next);
+                    }
+                }
+            }
+            closeness / others
+        })
+        .collect()
+}
+
+/// Weakly connected components: nodes linked by a path, ignoring directions, share a label.
+///
+/// Labels are numbered from 0 in order of their first node.
+pub fn components<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Vec<usize> {
+    let mut sets = UnionFind::new(graph.node_count());
+    for edge in graph.edge_references() {
+        sets.union(edge.source().index(), edge.target().index());
+    }
+    let roots: Vec<usize> = (0..graph.node_count()).map(|i| sets.find(i)).collect();
+    relabel(&roots)
+}
+
+/// Communities found by greedy modularity maximisation, ignoring edge directions and weights.
+///
+/// Every node starts in its own community, then the two communities whose merge increases
+/// the modularity the most are merged, until no merge improves it
+/// (Clauset, Newman and Moore). This is cubic in the number of nodes,
+/// which is fine for the small graphs of the course.
+/// Labels are numbered from 0 in order of their first node.
+pub fn communities<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Vec<usize> {
+    let n = graph.node_count();
+    let mut labels: Vec<usize> = (0..n).collect();
+    let links = graph
+        .edge_references()
+        .filter(|edge| edge.source() != edge.target())
+        .count();
+    if links == 0 {
+        return labels;
+    }
+
+    // e[i][j]: fraction of edge ends linking community i to community j
+    // a[i]: fraction of edge ends attached to community i
+    let end_weight = 1.0 / (2 * links) as f64;
+    let mut e = vec![vec![0.0; n]; n];
+    let mut a = vec![0.0; n];
+    for edge in graph.edge_references() {
+        let (u, v) = (edge.source().index(), edge.target().index());
+        if u != v {
+            e[u][v] += end_weight;
+            e[v][u] += end_weight;
+            a[u] += end_weight;
+            a[v] += end_weight;
+        }
+    }
+
+    let mut alive = vec![true; n];
+    loop {
+        let mut best: Option<(f64, usize, usize)> = None;
+        for i in (0..n).filter(|&i| alive[i]) {
+            for j in (i + 1..n).filter(|&j| alive[j] && e[i][j] > 0.0) {
+                let gain = 2.0 * (e[i][j] - a[i] * a[j]);
+                if gain > best.map_or(0.0, |(best_gain, _, _)| best_gain) {
+                    best = Some((gain, i, j));
+                }
+            }
+        }
+        let Some((_, keep, merged)) = best else {
+            break;
+        };
+
+        // fold community `merged` into `keep`
+        let merged_row = std::mem::replace(&mut e[merged], vec![0.0; n]);
+        for (value, merged_value) in e[keep].iter_mut().zip(merged_row) {
+            *value += merged_value;
+        }
+        for row in e.iter_mut() {
+            row[keep] += row[merged];
+            row[merged] = 0.0;
+        }
+        a[keep] += a[merged];
+        alive[merged] = false;
+        for label in labels.iter_mut().filter(|label| **label == merged) {
+            *label = keep;
+        }
+    }
+
+    relabel(&labels)
+}
+
+/// Renumbers arbitrary labels as 0, 1, 2... in order of first appearance
+fn relabel(labels: &[usize]) -> Vec<usize> {
+    let mut mapping = HashMap::new();
+    labels
+        .iter()
+        .map(|label| {
+            let next = mapping.len();
+            *mapping.entry(*label).or_insert(next)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::graph::{DiGraph, UnGraph};
+
+    #[test]
+    fn components_ignore_direction() {
+        let graph = DiGraph::<(), ()>::from_edges([(0, 1), (2, 1), (3, 4)]);
+        assert_eq!(components(&graph), vec![0, 0, 0, 1, 1]);
+    }
+
+    #[test]
+    fn communities_split_two_cliques() {
+        // two triangles joined by a single edge
+        let graph =
+            UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3), (2, 3)]);
+        let labels = communities(&graph);
+
+        assert_eq!(labels[0], labels[1]);
+        assert_eq!(labels[1], labels[2]);
+        assert_eq!(labels[3], labels[4]);
+        assert_eq!(labels[4], labels[5]);
+        assert_ne!(labels[0], labels[5]);
+    }
+
+    #[test]
+    fn closeness_of_a_path() {
+        let graph = UnGraph::<(), ()>::from_edges([(0, 1), (1, 2)]);
+        assert_eq!(closeness_centrality(&graph), vec![0.75, 1.0, 0.75]);
+    }
+}
@@ -0,0 +1,302 @@
+//! Graph rendering to DOT and SVG
+//!
+//! The [Renderer] turns a petgraph `Graph` into a styled DOT document:
+//!
+//! - node size (font size) grows with the node centrality,
+//! - edge width grows with the edge weight,
+//! - nodes are coloured by connected component or by community.
+//!
+//! The DOT output can be fed to Graphviz, but the SVG is produced directly
+//! with the pure-Rust [layout-rs](https://crates.io/crates/layout-rs) engine,
+//! so nothing needs to be installed.
+
+use crate::metrics::{closeness_centrality, communities, components, degree_centrality};
+use layout::backends::svg::SVGWriter;
+use layout::gv::{DotParser, GraphBuilder};
+use petgraph::graph::Graph;
+use petgraph::visit::EdgeRef;
+use petgraph::EdgeType;
+use std::error::Error;
+use std::fmt::{self, Display, Write};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Colour-blind friendly palette, cycled through when there are more groups than colours
+const PALETTE: [&str; 8] = [
+    "#8dd3c7", "#ffffb3", "#bebada", "#fb8072", "#80b1d3", "#fdb462", "#b3de69", "#fccde5",
+];
+
+const MIN_FONT_SIZE: f64 = 10.0;
+const MAX_FONT_SIZE: f64 = 24.0;
+const MIN_PEN_WIDTH: f64 = 1.0;
+const MAX_PEN_WIDTH: f64 = 6.0;
+
+/// Centrality measure used to size the nodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Centrality {
+    #[default]
+    Degree,
+    Closeness,
+}
+
+/// How nodes are grouped into colours
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Colouring {
+    /// Every node is white
+    None,
+    /// One colour per weakly connected component
+    #[default]
+    Components,
+    /// One colour per community, see [communities]
+    Communities,
+}
+
+/// Errors raised while rendering a graph
+#[derive(Debug)]
+pub enum RenderError {
+    Io(io::Error),
+    /// The layout engine rejected the generated DOT document
+    Layout(String),
+    /// The output file extension is neither `.dot` nor `.svg`
+    UnsupportedFormat(String),
+}
+
+impl Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderError::Io(e) => write!(f, "Could not write the graph: {}", e),
+            RenderError::Layout(e) => write!(f, "Could not lay out the graph: {}", e),
+            RenderError::UnsupportedFormat(path) => {
+                write!(
+                    f,
+                    "Unsupported output format for {}, use .svg or .dot",
+                    path
+                )
+            }
+        }
+    }
+}
+
+impl Error for RenderError {}
+
+impl From<io::Error> for RenderError {
+    fn from(e: io::Error) -> Self {
+        RenderError::Io(e)
+    }
+}
+
+type Label<'a, T> = Box<dyn Fn(&T) -> String + 'a>;
+type Weight<'a, T> = Box<dyn Fn(&T) -> f64 + 'a>;
+
+/// Renders a graph as DOT or SVG
+///
+/// # Example
+///
+/// ```rust
+/// use graph_utils::render::{Colouring, Renderer};
+/// use petgraph::graph::DiGraph;
+///
+/// let mut graph = DiGraph::<&str, f32>::new();
+/// let core = graph.add_node("core");
+/// let cli = graph.add_node("cli");
+/// graph.add_edge(cli, core, 2.0);
+///
+/// let renderer = Renderer::new(&graph)
+///     .edge_weight(|w| *w as f64)
+///     .colouring(Colouring::Communities);
+///
+/// let dot = renderer.to_dot();
+/// assert!(dot.starts_with("digraph {"));
+/// assert!(dot.contains("n1 -> n0"));
+///
+/// let svg = renderer.to_svg().unwrap();
+/// assert!(svg.contains("<svg"));
+/// ```
+pub struct Renderer<'a, N, E, Ty: EdgeType> {
+    graph: &'a Graph<N, E, Ty>,
+    node_label: Label<'a, N>,
+    edge_label: Option<Label<'a, E>>,
+    edge_weight: Option<Weight<'a, E>>,
+    centrality: Centrality,
+    colouring: Colouring,
+}
+
+impl<'a, N: Display, E, Ty: EdgeType> Renderer<'a, N, E, Ty> {
+    /// Renderer labelling the nodes with their `Display` representation
+    pub fn new(graph: &'a Graph<N, E, Ty>) -> Self {
+        Self::with_node_label(graph, |node| node.to_string())
+    }
+}
+
+impl<'a, N, E, Ty: EdgeType> Renderer<'a, N, E, Ty> {
+    /// Renderer labelling the nodes with the given function
+    pub fn with_node_label(graph: &'a Graph<N, E, Ty>, label: impl Fn(&N) -> String + 'a) -> Self {
+        Self {
+            graph,
+            node_label: Box::new(label),
+            edge_label: None,
+            edge_weight: None,
+            centrality: Centrality::default(),
+            colouring: Colouring::default(),
+        }
+    }
+
+    /// Labels the edges, by default edges have no label
+    pub fn edge_label(mut self, label: impl Fn(&E) -> String + 'a) -> Self {
+        self.edge_label = Some(Box::new(label));
+        self
+    }
+
+    /// Numeric weight of the edges, used for their width. By default all edges are thin.
+    pub fn edge_weight(mut self, weight: impl Fn(&E) -> f64 + 'a) -> Self {
+        self.edge_weight = Some(Box::new(weight));
+        self
+    }
+
+    pub fn centrality(mut self, centrality: Centrality) -> Self {
+        self.centrality = centrality;
+        self
+    }
+
+    pub fn colouring(mut self, colouring: Colouring) -> Self {
+        self.colouring = colouring;
+        self
+    }
+
+    /// The styled DOT document
+    pub fn to_dot(&self) -> String {
+        let graph = self.graph;
+        let (kind, arrow) = if graph.is_directed() {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+
+        let centrality = match self.centrality {
+            Centrality::Degree => degree_centrality(graph),
+            Centrality::Closeness => closeness_centrality(graph),
+        };
+        let groups = match self.colouring {
+            Colouring::None => None,
+            Colouring::Components => Some(components(graph)),
+            Colouring::Communities => Some(communities(graph)),
+        };
+        let weights: Option<Vec<f64>> = self
+            .edge_weight
+            .as_ref()
+            .map(|weight| graph.edge_weights().map(weight).collect());
+
+        let mut dot = String::new();
+        let _ = writeln!(dot, "{} {{", kind);
+        for node in graph.node_indices() {
+            let font_size = scale(&centrality, node.index(), MIN_FONT_SIZE, MAX_FONT_SIZE);
+            let fill = groups.as_ref().map_or("white", |groups| {
+                PALETTE[groups[node.index()] % PALETTE.len()]
+            });
+            let _ = writeln!(
+                dot,
+                "    n{} [label=\"{}\", fontsize={}, style=filled, fillcolor=\"{}\"];",
+                node.index(),
+                escape(&(self.node_label)(&graph[node])),
+                font_size,
+                fill
+            );
+        }
+        for edge in graph.edge_references() {
+            let mut attributes = Vec::new();
+            if let Some(label) = &self.edge_label {
+                attributes.push(format!("label=\"{}\"", escape(&label(edge.weight()))));
+            }
+            if let Some(weights) = &weights {
+                let width = scale(weights, edge.id().index(), MIN_PEN_WIDTH, MAX_PEN_WIDTH);
+                attributes.push(format!("penwidth={}", width));
+            }
+            let _ = write!(
+                dot,
+                "    n{} {} n{}",
+                edge.source().index(),
+                arrow,
+                edge.target().index()
+            );
+            if !attributes.is_empty() {
+                let _ = write!(dot, " [{}]", attributes.join(", "));
+            }
+            let _ = writeln!(dot, ";");
+        }
+        let _ = writeln!(dot, "}}");
+        dot
+    }
+
+    /// The graph laid out and drawn as an SVG document
+    pub fn to_svg(&self) -> Result<String, RenderError> {
+        let dot = self.to_dot();
+        let mut parser = DotParser::new(&dot);
+        let ast = parser.process().map_err(RenderError::Layout)?;
+
+        let mut builder = GraphBuilder::new();
+        builder.visit_graph(&ast);
+        let mut visual_graph = builder.get();
+
+        let mut svg = SVGWriter::new();
+        visual_graph.do_it(false, false, false, &mut svg);
+        Ok(svg.finalize())
+    }
+
+    /// Writes the graph to `path`, as DOT or SVG depending on the file extension
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), RenderError> {
+        let path = path.as_ref();
+        let content = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("svg") => self.to_svg()?,
+            Some("dot") | Some("gv") => self.to_dot(),
+            _ => return Err(RenderError::UnsupportedFormat(path.display().to_string())),
+        };
+        fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+/// Maps `values[index]` linearly from the range of `values` to `min..=max`, rounded.
+///
+/// Integers are used because the layout engine ignores fractional sizes.
+fn scale(values: &[f64], index: usize, min: f64, max: f64) -> usize {
+    let lowest = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let highest = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let ratio = if highest > lowest {
+        (values[index] - lowest) / (highest - lowest)
+    } else {
+        0.0
+    };
+    (min + ratio * (max - min)).round() as usize
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::graph::UnGraph;
+
+    #[test]
+    fn styles_follow_centrality_and_weight() {
+        let graph = UnGraph::<&str, f64>::from_edges([(0, 1, 1.0), (0, 2, 5.0)]);
+        let dot = Renderer::with_node_label(&graph, |_| "x\"y".to_string())
+            .edge_weight(|w| *w)
+            .to_dot();
+
+        assert!(dot.starts_with("graph {"));
+        assert!(dot.contains("n0 [label=\"x\\\"y\", fontsize=24"));
+        assert!(dot.contains("n1 [label=\"x\\\"y\", fontsize=10"));
+        assert!(dot.contains("n0 -- n1 [penwidth=1]"));
+        assert!(dot.contains("n0 -- n2 [penwidth=6]"));
+    }
+
+    #[test]
+    fn unknown_extensions_are_rejected() {
+        let graph = UnGraph::<&str, ()>::new_undirected();
+        let err = Renderer::new(&graph).write("graph.png").unwrap_err();
+        assert!(matches!(err, RenderError::UnsupportedFormat(_)));
+    }
+}
@@ -0,0 +1,42 @@
+//! Horizontal bar charts
+
+use crate::horizontal_bar;
+use std::fmt::{Display, Write};
+
+/// One line per item: the label, a bar scaled to the largest value and the value.
+/// Negative values get an empty bar.
+///
+/// ```rust
+/// use term_charts::bar_chart;
+///
+/// let chart = bar_chart(&[("holmes", 4), ("watson", 2)], 4);
+/// assert_eq!(chart, "holmes ████ 4\nwatson ██   2\n");
+/// ```
+pub fn bar_chart<L: Display, V: Copy + Into<f64> + Display>(
+    items: &[(L, V)],
+    width: usize,
+) -> String {
+    let labels: Vec<String> = items.iter().map(|(label, _)| label.to_string()).collect();
+    let label_width = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let max = items
+        .iter()
+        .map(|(_, value)| (*value).into())
+        .fold(0.0, f64::max);
+
+    let mut out = String::new();
+    for (label, (_, value)) in labels.iter().zip(items) {
+        let fraction = if max > 0.0 {
+            (*value).into() / max
+        } else {
+            0.0
+        };
+        let _ = writeln!(
+            out,
+            "{:<label_width$} {:<width$} {}",
+            label,
+            horizontal_bar(fraction, width),
+            value
+        );
+    }
+    out
+}
@@ -0,0 +1,137 @@
+//! Box plots
+
+use crate::histogram::format_number;
+use crate::{quantile, sorted_finite};
+
+/// Five-number summary with Tukey whiskers: they reach the furthest values within
+/// 1.5 IQR of the box, values beyond are outliers
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoxPlot {
+    pub min: f64,
+    pub lower_whisker: f64,
+    pub q1: f64,
+    pub median: f64,
+    pub q3: f64,
+    pub upper_whisker: f64,
+    pub max: f64,
+    pub outliers: Vec<f64>,
+}
+
+impl BoxPlot {
+    /// Box plot of the finite values, `None` when there are none
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use term_charts::BoxPlot;
+    ///
+    /// let plot = BoxPlot::new(&[1.0, 2.0, 3.0, 4.0, 5.0, 100.0]).unwrap();
+    /// assert_eq!(plot.median, 3.5);
+    /// assert_eq!(plot.upper_whisker, 5.0);
+    /// assert_eq!(plot.outliers, vec![100.0]);
+    /// ```
+    pub fn new(values: &[f64]) -> Option<Self> {
+        let sorted = sorted_finite(values);
+        if sorted.is_empty() {
+            return None;
+        }
+        let (q1, median, q3) = (
+            quantile(&sorted, 0.25),
+            quantile(&sorted, 0.5),
+            quantile(&sorted, 0.75),
+        );
+        let fence = 1.5 * (q3 - q1);
+        let inside = |x: &&f64| **x >= q1 - fence && **x <= q3 + fence;
+        let lower_whisker = *sorted.iter().find(inside).unwrap_or(&q1);
+        let upper_whisker = *sorted.iter().rev().find(inside).unwrap_or(&q3);
+        Some(Self {
+            min: sorted[0],
+            lower_whisker,
+            q1,
+            median,
+            q3,
+            upper_whisker,
+            max: sorted[sorted.len() - 1],
+            outliers: sorted.iter().copied().filter(|x| !inside(&x)).collect(),
+        })
+    }
+
+    /// Two lines: the plot scaled from min to max over `width` characters, then the axis labels
+    ///
+    /// ```text
+    /// ├───[████│██████]──────┤   ∘
+    /// 1                          100
+    /// ```
+    pub fn render(&self, width: usize) -> String {
+        let width = width.max(8);
+        let range = self.max - self.min;
+        let column = |x: f64| {
+            if range > 0.0 {
+                ((x - self.min) / range * (width - 1) as f64).round() as usize
+            } else {
+                0
+            }
+        };
+
+        let mut line = vec![' '; width];
+        let (lw, q1, median, q3, uw) = (
+            column(self.lower_whisker),
+            column(self.q1),
+            column(self.median),
+            column(self.q3),
+            column(self.upper_whisker),
+        );
+        for cell in line.iter_mut().take(uw + 1).skip(lw) {
+            *cell = '─';
+        }
+        for cell in line.iter_mut().take(q3 + 1).skip(q1) {
+            *cell = '█';
+        }
+        line[lw] = '├';
+        line[uw] = '┤';
+        if q3 > q1 {
+            line[q1] = '[';
+            line[q3] = ']';
+        }
+        line[median] = '│';
+        for outlier in &self.outliers {
+            line[column(*outlier)] = '∘';
+        }
+
+        let min = format_number(self.min);
+        let max = format_number(self.max);
+        let gap = width.saturating_sub(min.chars().count() + max.chars().count());
+        format!(
+            "{}\n{}{}{}\n",
+            line.into_iter().collect::<String>().trim_end(),
+            min,
+            " ".repeat(gap.max(1)),
+            max
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_box_whiskers_and_outliers() {
+        let values: Vec<f64> = (0..=10).map(f64::from).chain([30.0]).collect();
+        let plot = BoxPlot::new(&values).unwrap();
+        assert_eq!(plot.outliers, vec![30.0]);
+        let rendered = plot.render(31);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert!(lines[0].starts_with('├'));
+        assert!(lines[0].ends_with('∘'));
+        assert!(lines[0].contains('│'));
+        assert!(lines[1].starts_with('0') && lines[1].ends_with("30"));
+    }
+
+    #[test]
+    fn a_single_value_is_a_plot_too() {
+        let plot = BoxPlot::new(&[5.0]).unwrap();
+        assert!(plot.outliers.is_empty());
+        assert!(plot.render(10).starts_with('│'));
+    }
+}
@@ -0,0 +1,185 @@
+//! Histograms with automatic binning
+
+use crate::{horizontal_bar, quantile, sorted_finite};
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// How many bins a histogram gets
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Binning {
+    /// `⌈log2(n)⌉ + 1` bins, good for roughly normal data
+    #[default]
+    Sturges,
+    /// Bins `2 · IQR / ∛n` wide, robust to outliers and long tails
+    FreedmanDiaconis,
+    /// This many bins
+    Count(usize),
+}
+
+impl Binning {
+    /// Number of bins for the sorted values, at least 1
+    fn bins(&self, sorted: &[f64]) -> usize {
+        let n = sorted.len() as f64;
+        let range = sorted[sorted.len() - 1] - sorted[0];
+        let bins = match self {
+            Binning::Sturges => n.log2().ceil() + 1.0,
+            Binning::FreedmanDiaconis => {
+                let iqr = quantile(sorted, 0.75) - quantile(sorted, 0.25);
+                let width = 2.0 * iqr / n.cbrt();
+                if width > 0.0 {
+                    (range / width).ceil()
+                } else {
+                    // more than half the values are equal: fall back
+                    n.log2().ceil() + 1.0
+                }
+            }
+            Binning::Count(bins) => *bins as f64,
+        };
+        // keep absurd bin counts (e.g. a huge range with a tiny IQR) printable
+        (bins as usize).clamp(1, 200)
+    }
+}
+
+impl FromStr for Binning {
+    type Err = String;
+
+    /// `sturges`, `freedman-diaconis` (or `fd`), or a number of bins
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sturges" => Ok(Binning::Sturges),
+            "freedman-diaconis" | "fd" => Ok(Binning::FreedmanDiaconis),
+            _ => match s.parse() {
+                Ok(bins) if bins > 0 => Ok(Binning::Count(bins)),
+                _ => Err(format!(
+                    "expected sturges, freedman-diaconis or a positive number of bins, not {}",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+/// Counts of values in equally wide bins
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    /// `bins + 1` increasing edges; every bin includes its lower edge, the last one also its upper edge
+    pub edges: Vec<f64>,
+    pub counts: Vec<usize>,
+}
+
+impl Histogram {
+    /// Histogram of the finite values, `None` when there are none
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use term_charts::{Binning, Histogram};
+    ///
+    /// let histogram = Histogram::new(&[1.0, 2.0, 2.5, 4.0], Binning::Count(3)).unwrap();
+    /// assert_eq!(histogram.edges, vec![1.0, 2.0, 3.0, 4.0]);
+    /// assert_eq!(histogram.counts, vec![1, 2, 1]);
+    /// ```
+    pub fn new(values: &[f64], binning: Binning) -> Option<Self> {
+        let sorted = sorted_finite(values);
+        if sorted.is_empty() {
+            return None;
+        }
+        let bins = binning.bins(&sorted);
+        let (min, max) = (sorted[0], sorted[sorted.len() - 1]);
+        // a single distinct value still gets a bin of width 1
+        let width = if max > min {
+            (max - min) / bins as f64
+        } else {
+            1.0
+        };
+
+        let edges: Vec<f64> = (0..=bins).map(|i| min + width * i as f64).collect();
+        let mut counts = vec![0; bins];
+        for x in sorted {
+            let bin = (((x - min) / width) as usize).min(bins - 1);
+            counts[bin] += 1;
+        }
+        Some(Self { edges, counts })
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// One line per bin: its range, a bar up to `width` characters and the count
+    pub fn render(&self, width: usize) -> String {
+        let max = self.counts.iter().copied().max().unwrap_or(0).max(1);
+        let labels: Vec<(String, String)> = self
+            .edges
+            .windows(2)
+            .map(|edges| (format_number(edges[0]), format_number(edges[1])))
+            .collect();
+        let left = labels.iter().map(|(a, _)| a.len()).max().unwrap_or(0);
+        let right = labels.iter().map(|(_, b)| b.len()).max().unwrap_or(0);
+
+        let mut out = String::new();
+        for (i, ((from, to), count)) in labels.iter().zip(&self.counts).enumerate() {
+            let close = if i + 1 == self.counts.len() { ']' } else { ')' };
+            let bar = horizontal_bar(*count as f64 / max as f64, width);
+            let _ = writeln!(
+                out,
+                "[{:>left$}, {:>right$}{} {:<width$} {}",
+                from, to, close, bar, count
+            );
+        }
+        out
+    }
+}
+
+/// Short representation of an axis value
+pub(crate) fn format_number(x: f64) -> String {
+    if x != 0.0 && (x.abs() >= 1e6 || x.abs() < 1e-3) {
+        format!("{:.2e}", x)
+    } else if x.fract() == 0.0 {
+        format!("{}", x)
+    } else {
+        format!("{:.2}", x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sturges_and_freedman_diaconis() {
+        let values: Vec<f64> = (0..100).map(f64::from).collect();
+        let sturges = Histogram::new(&values, Binning::Sturges).unwrap();
+        assert_eq!(sturges.counts.len(), 8);
+        assert_eq!(sturges.total(), 100);
+
+        // IQR 49.5, width 2 · 49.5 / ∛100 ≈ 21.3, range 99: 5 bins
+        let fd = Histogram::new(&values, Binning::FreedmanDiaconis).unwrap();
+        assert_eq!(fd.counts.len(), 5);
+        assert_eq!(fd.total(), 100);
+    }
+
+    #[test]
+    fn binning_from_the_command_line() {
+        assert_eq!("fd".parse(), Ok(Binning::FreedmanDiaconis));
+        assert_eq!("12".parse(), Ok(Binning::Count(12)));
+        assert!("0".parse::<Binning>().is_err());
+    }
+
+    #[test]
+    fn degenerate_inputs() {
+        assert_eq!(Histogram::new(&[f64::NAN], Binning::Sturges), None);
+        let constant = Histogram::new(&[3.0; 10], Binning::FreedmanDiaconis).unwrap();
+        assert_eq!(constant.total(), 10);
+        assert_eq!(
+            Histogram::new(&[1.0], Binning::Count(0)).unwrap().counts,
+            vec![1]
+        );
+    }
+
+    #[test]
+    fn renders_one_line_per_bin() {
+        let histogram = Histogram::new(&[0.0, 1.0, 1.0, 2.0], Binning::Count(2)).unwrap();
+        assert_eq!(histogram.render(4), "[0, 1) █▍   1\n[1, 2] ████ 3\n");
+    }
+}
@@ -0,0 +1,63 @@
+//! # Term charts
+//!
+//! `term-charts` draws small charts with Unicode block characters, to be printed next to the
+//! statistics of the CLIs (`week1-challenge`, `count-freq` and `eda_polars_cli`):
+//!
+//! - [Histogram](histogram::Histogram), with Sturges, Freedman–Diaconis or a fixed number of bins
+//! - [BoxPlot](boxplot::BoxPlot), with Tukey whiskers and outliers
+//! - [sparkline](sparkline::sparkline) for time series
+//! - [bar_chart](bar::bar_chart) for counts, e.g. word frequencies
+//!
+//! The same charts can be written as SVG with the [svg] module.
+
+pub mod bar;
+pub mod boxplot;
+pub mod histogram;
+pub mod sparkline;
+pub mod svg;
+
+pub use bar::bar_chart;
+pub use boxplot::BoxPlot;
+pub use histogram::{Binning, Histogram};
+pub use sparkline::sparkline;
+
+/// Eighths of a block, from empty to full
+const EIGHTHS: [char; 9] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
+
+/// A horizontal bar `fraction` of `width` characters long, with eighth-of-a-character precision
+pub(crate) fn horizontal_bar(fraction: f64, width: usize) -> String {
+    let eighths = (fraction.clamp(0.0, 1.0) * width as f64 * 8.0).round() as usize;
+    let mut bar = "█".repeat(eighths / 8);
+    if !eighths.is_multiple_of(8) {
+        bar.push(EIGHTHS[eighths % 8]);
+    }
+    bar
+}
+
+/// Sorted values without NaN
+pub(crate) fn sorted_finite(values: &[f64]) -> Vec<f64> {
+    let mut sorted: Vec<f64> = values.iter().copied().filter(|x| x.is_finite()).collect();
+    sorted.sort_by(f64::total_cmp);
+    sorted
+}
+
+/// The `q`-quantile of sorted, non-empty values, interpolating linearly between the closest ranks
+pub(crate) fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let below = position.floor() as usize;
+    let above = position.ceil() as usize;
+    sorted[below] + (sorted[above] - sorted[below]) * (position - below as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bars_have_sub_character_precision() {
+        assert_eq!(horizontal_bar(1.0, 4), "████");
+        assert_eq!(horizontal_bar(0.5, 3), "█▌");
+        assert_eq!(horizontal_bar(0.0, 10), "");
+        assert_eq!(horizontal_bar(2.0, 2), "██");
+    }
+}
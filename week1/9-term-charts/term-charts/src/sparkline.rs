@@ -0,0 +1,31 @@
+//! Sparklines: a time series in a single line of text
+
+const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// One character per value, from `▁` for the minimum to `█` for the maximum.
+/// NaN and infinite values are left blank.
+///
+/// ```rust
+/// use term_charts::sparkline;
+///
+/// assert_eq!(sparkline(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]), "▁▂▃▄▅▆▇█");
+/// assert_eq!(sparkline(&[1.0, f64::NAN, 1.0]), "▄ ▄");
+/// ```
+pub fn sparkline(values: &[f64]) -> String {
+    let finite = values.iter().copied().filter(|x| x.is_finite());
+    let min = finite.clone().fold(f64::INFINITY, f64::min);
+    let max = finite.fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|&x| {
+            if !x.is_finite() {
+                ' '
+            } else if max > min {
+                LEVELS[((x - min) / (max - min) * 7.0).round() as usize]
+            } else {
+                // a flat line sits in the middle
+                LEVELS[3]
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,259 @@
+//! The charts as standalone SVG documents, e.g. for reports
+//!
+//! Every function returns the whole document, ready to be written to a `.svg` file.
+
+use crate::histogram::format_number;
+use crate::{BoxPlot, Histogram};
+use std::fmt::{Display, Write};
+
+const WIDTH: f64 = 640.0;
+const HEIGHT: f64 = 320.0;
+const MARGIN: f64 = 40.0;
+const FILL: &str = "#4c78a8";
+
+/// Histogram bars with the range of the values below
+pub fn histogram(histogram: &Histogram, title: &str) -> String {
+    let mut svg = Document::new(title);
+    let max = histogram.counts.iter().copied().max().unwrap_or(0).max(1) as f64;
+    let bar_width = svg.plot_width() / histogram.counts.len() as f64;
+    for (i, count) in histogram.counts.iter().enumerate() {
+        let height = *count as f64 / max * svg.plot_height();
+        svg.rect(
+            MARGIN + i as f64 * bar_width,
+            HEIGHT - MARGIN - height,
+            bar_width - 1.0,
+            height,
+            &format!("{}", count),
+        );
+    }
+    svg.axis_labels(
+        histogram.edges[0],
+        histogram.edges[histogram.edges.len() - 1],
+    );
+    svg.finish()
+}
+
+/// Horizontal box plot, outliers drawn as circles
+pub fn box_plot(plot: &BoxPlot, title: &str) -> String {
+    let mut svg = Document::new(title);
+    let range = plot.max - plot.min;
+    let x = |value: f64| {
+        if range > 0.0 {
+            MARGIN + (value - plot.min) / range * (WIDTH - 2.0 * MARGIN)
+        } else {
+            WIDTH / 2.0
+        }
+    };
+    let middle = HEIGHT / 2.0;
+    let half = HEIGHT / 6.0;
+
+    svg.line(x(plot.lower_whisker), middle, x(plot.q1), middle);
+    svg.line(x(plot.q3), middle, x(plot.upper_whisker), middle);
+    for whisker in [plot.lower_whisker, plot.upper_whisker] {
+        svg.line(
+            x(whisker),
+            middle - half / 2.0,
+            x(whisker),
+            middle + half / 2.0,
+        );
+    }
+    svg.rect(
+        x(plot.q1),
+        middle - half,
+        x(plot.q3) - x(plot.q1),
+        2.0 * half,
+        &format!("Q1 {} / Q3 {}", plot.q1, plot.q3),
+    );
+    svg.line(x(plot.median), middle - half, x(plot.median), middle + half);
+    for outlier in &plot.outliers {
+        let _ = writeln!(
+            svg.body,
+            r#"<circle cx="{:.1}" cy="{:.1}" r="3" fill="none" stroke="black"><title>{}</title></circle>"#,
+            x(*outlier),
+            middle,
+            outlier
+        );
+    }
+    svg.axis_labels(plot.min, plot.max);
+    svg.finish()
+}
+
+/// Line chart of a time series, NaN values leave a gap
+pub fn sparkline(values: &[f64], title: &str) -> String {
+    let mut svg = Document::new(title);
+    let finite = values.iter().copied().filter(|x| x.is_finite());
+    let min = finite.clone().fold(f64::INFINITY, f64::min);
+    let max = finite.fold(f64::NEG_INFINITY, f64::max);
+    let step = svg.plot_width() / (values.len().max(2) - 1) as f64;
+    let y = |value: f64| {
+        if max > min {
+            HEIGHT - MARGIN - (value - min) / (max - min) * svg.plot_height()
+        } else {
+            HEIGHT / 2.0
+        }
+    };
+
+    let mut segments: Vec<Vec<String>> = vec![Vec::new()];
+    for (i, value) in values.iter().enumerate() {
+        if value.is_finite() {
+            let point = format!("{:.1},{:.1}", MARGIN + i as f64 * step, y(*value));
+            segments.last_mut().expect("never empty").push(point);
+        } else {
+            segments.push(Vec::new());
+        }
+    }
+    for segment in segments.iter().filter(|segment| !segment.is_empty()) {
+        let _ = writeln!(
+            svg.body,
+            r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="2"/>"#,
+            segment.join(" "),
+            FILL
+        );
+    }
+    if min.is_finite() {
+        svg.axis_labels(min, max);
+    }
+    svg.finish()
+}
+
+/// Horizontal bars, one per item, labelled on the left
+pub fn bar_chart<L: Display, V: Copy + Into<f64> + Display>(
+    items: &[(L, V)],
+    title: &str,
+) -> String {
+    let mut svg = Document::new(title);
+    let max = items
+        .iter()
+        .map(|(_, value)| (*value).into())
+        .fold(0.0, f64::max);
+    let row = svg.plot_height() / items.len().max(1) as f64;
+    let label_width = 120.0;
+    for (i, (label, value)) in items.iter().enumerate() {
+        let top = MARGIN + i as f64 * row;
+        let fraction = if max > 0.0 {
+            (*value).into() / max
+        } else {
+            0.0
+        };
+        svg.text(
+            MARGIN + label_width - 6.0,
+            top + row * 0.7,
+            "end",
+            &label.to_string(),
+        );
+        svg.rect(
+            MARGIN + label_width,
+            top + 1.0,
+            fraction.max(0.0) * (svg.plot_width() - label_width),
+            row - 2.0,
+            &value.to_string(),
+        );
+    }
+    svg.finish()
+}
+
+/// An SVG document being written
+struct Document {
+    body: String,
+}
+
+impl Document {
+    fn new(title: &str) -> Self {
+        let mut document = Self {
+            body: format!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif" font-size="12">
+<rect width="{w}" height="{h}" fill="white"/>
+"#,
+                w = WIDTH,
+                h = HEIGHT
+            ),
+        };
+        document.text(WIDTH / 2.0, MARGIN / 2.0, "middle", title);
+        document
+    }
+
+    fn plot_width(&self) -> f64 {
+        WIDTH - 2.0 * MARGIN
+    }
+
+    fn plot_height(&self) -> f64 {
+        HEIGHT - 2.0 * MARGIN
+    }
+
+    /// A filled rectangle with a tooltip
+    fn rect(&mut self, x: f64, y: f64, width: f64, height: f64, tooltip: &str) {
+        let _ = writeln!(
+            self.body,
+            r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{}"><title>{}</title></rect>"#,
+            x,
+            y,
+            width.max(0.0),
+            height.max(0.0),
+            FILL,
+            escape(tooltip)
+        );
+    }
+
+    fn line(&mut self, x1: f64, y1: f64, x2: f64, y2: f64) {
+        let _ = writeln!(
+            self.body,
+            r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="black"/>"#,
+            x1, y1, x2, y2
+        );
+    }
+
+    fn text(&mut self, x: f64, y: f64, anchor: &str, text: &str) {
+        let _ = writeln!(
+            self.body,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="{}">{}</text>"#,
+            x,
+            y,
+            anchor,
+            escape(text)
+        );
+    }
+
+    /// The smallest and largest value, below the left and right end of the plot
+    fn axis_labels(&mut self, min: f64, max: f64) {
+        let y = HEIGHT - MARGIN / 2.0;
+        self.text(MARGIN, y, "start", &format_number(min));
+        self.text(WIDTH - MARGIN, y, "end", &format_number(max));
+    }
+
+    fn finish(mut self) -> String {
+        self.body.push_str("</svg>\n");
+        self.body
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Binning;
+
+    #[test]
+    fn documents_are_complete_and_escaped() {
+        let values = [1.0, 2.0, 2.0, 3.0, 10.0];
+        let charts = [
+            histogram(&Histogram::new(&values, Binning::Sturges).unwrap(), "a < b"),
+            box_plot(&BoxPlot::new(&values).unwrap(), "box"),
+            sparkline(&values, "line"),
+            bar_chart(&[("x & y", 3), ("z", 1)], "bars"),
+        ];
+        for chart in &charts {
+            assert!(chart.starts_with("<svg"));
+            assert!(chart.ends_with("</svg>\n"));
+        }
+        assert!(charts[0].contains("a &lt; b"));
+        assert!(charts[1].contains("<circle"));
+        assert!(charts[2].contains("<polyline"));
+        assert!(charts[3].contains("x &amp; y"));
+    }
+}
@@ -0,0 +1,516 @@
+//! A salad that remembers how it was made
+//!
+//! Every change to a [SaladHistory] is a [Command], applied to the items and recorded
+//! together with what is needed to revert it. The recorded commands can be undone and
+//! redone, any earlier version can be rebuilt by replaying them on the initial items, and
+//! the whole history can be saved as JSON and loaded back.
+
+use crate::vec_ops;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Debug, Display};
+
+/// A change of the salad
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command<T> {
+    Push {
+        item: T,
+    },
+    Pop,
+    Insert {
+        index: usize,
+        item: T,
+    },
+    Remove {
+        index: usize,
+    },
+    /// Removes the first item equal to this one
+    RemoveFirst {
+        item: T,
+    },
+    /// Shuffles with a seeded random generator, so that replaying gives the same order
+    Shuffle {
+        seed: u64,
+    },
+    Sort,
+}
+
+impl<T: Display> Display for Command<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::Push { item } => write!(f, "push {}", item),
+            Command::Pop => write!(f, "pop"),
+            Command::Insert { index, item } => write!(f, "insert {} at {}", item, index),
+            Command::Remove { index } => write!(f, "remove at {}", index),
+            Command::RemoveFirst { item } => write!(f, "remove {}", item),
+            Command::Shuffle { seed } => write!(f, "shuffle (seed {})", seed),
+            Command::Sort => write!(f, "sort"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum HistoryError {
+    /// Pop from an empty salad
+    Empty,
+    IndexOutOfBounds {
+        index: usize,
+        len: usize,
+    },
+    /// RemoveFirst of an item that is not in the salad
+    NotFound(String),
+    /// A version after the last recorded command
+    UnknownVersion(usize),
+    Json(serde_json::Error),
+}
+
+impl Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryError::Empty => write!(f, "The salad is empty"),
+            HistoryError::IndexOutOfBounds { index, len } => {
+                write!(f, "Index {} is out of bounds for {} items", index, len)
+            }
+            HistoryError::NotFound(item) => write!(f, "{} is not in the salad", item),
+            HistoryError::UnknownVersion(version) => write!(f, "There is no version {}", version),
+            HistoryError::Json(e) => write!(f, "Invalid history: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for HistoryError {}
+
+impl From<serde_json::Error> for HistoryError {
+    fn from(e: serde_json::Error) -> Self {
+        HistoryError::Json(e)
+    }
+}
+
+/// What a command did, enough to revert it
+#[derive(Debug, Clone)]
+enum Revert<T> {
+    Remove {
+        index: usize,
+    },
+    Insert {
+        index: usize,
+        item: T,
+    },
+    /// `items[k]` was at `positions[k]` before the command
+    Reorder {
+        positions: Vec<usize>,
+    },
+}
+
+/// One difference between two versions, see [diff]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "change", content = "item", rename_all = "snake_case")]
+pub enum Change<T> {
+    Kept(T),
+    Added(T),
+    Removed(T),
+}
+
+/// A vector that records its changes
+///
+/// # Example
+///
+/// ```rust
+/// use mutable_salad::history::SaladHistory;
+///
+/// let mut salad = SaladHistory::new(vec!["apple", "banana"]);
+/// salad.push("figs");
+/// salad.remove_first("apple").unwrap();
+/// assert_eq!(salad.items(), &["banana", "figs"]);
+///
+/// salad.undo();
+/// assert_eq!(salad.items(), &["apple", "banana", "figs"]);
+/// salad.redo();
+/// assert_eq!(salad.version(), 2);
+/// assert_eq!(salad.at_version(1).unwrap(), vec!["apple", "banana", "figs"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SaladHistory<T> {
+    initial: Vec<T>,
+    items: Vec<T>,
+    done: Vec<(Command<T>, Revert<T>)>,
+    /// Undone commands, the next one to redo last
+    undone: Vec<Command<T>>,
+}
+
+/// The saved form of a [SaladHistory]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot<T> {
+    pub initial: Vec<T>,
+    pub commands: Vec<Command<T>>,
+    pub undone: Vec<Command<T>>,
+    /// The current items, saved for readers of the file; loading replays the commands
+    pub items: Vec<T>,
+}
+
+impl<T: Clone + Ord + Debug> SaladHistory<T> {
+    pub fn new(initial: Vec<T>) -> Self {
+        Self {
+            items: initial.clone(),
+            initial,
+            done: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Number of commands applied since the initial items
+    pub fn version(&self) -> usize {
+        self.done.len()
+    }
+
+    /// The applied commands, oldest first
+    pub fn commands(&self) -> impl Iterator<Item = &Command<T>> {
+        self.done.iter().map(|(command, _)| command)
+    }
+
+    /// Applies and records a command; the undone commands can no longer be redone
+    pub fn apply(&mut self, command: Command<T>) -> Result<(), HistoryError> {
+        self.execute(command)?;
+        self.undone.clear();
+        Ok(())
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.apply(Command::Push { item })
+            .expect("Pushing cannot fail");
+    }
+
+    pub fn pop(&mut self) -> Result<(), HistoryError> {
+        self.apply(Command::Pop)
+    }
+
+    pub fn insert(&mut self, index: usize, item: T) -> Result<(), HistoryError> {
+        self.apply(Command::Insert { index, item })
+    }
+
+    pub fn remove(&mut self, index: usize) -> Result<(), HistoryError> {
+        self.apply(Command::Remove { index })
+    }
+
+    pub fn remove_first(&mut self, item: T) -> Result<(), HistoryError> {
+        self.apply(Command::RemoveFirst { item })
+    }
+
+    pub fn shuffle(&mut self, seed: u64) {
+        self.apply(Command::Shuffle { seed })
+            .expect("Shuffling cannot fail");
+    }
+
+    pub fn sort(&mut self) {
+        self.apply(Command::Sort).expect("Sorting cannot fail");
+    }
+
+    /// Reverts the last command, returns it or `None` when there is nothing to undo
+    pub fn undo(&mut self) -> Option<&Command<T>> {
+        let (command, revert) = self.done.pop()?;
+        match revert {
+            Revert::Remove { index } => {
+                self.items.remove(index);
+            }
+            Revert::Insert { index, item } => self.items.insert(index, item),
+            Revert::Reorder { positions } => {
+                let mut previous: Vec<Option<T>> = vec![None; self.items.len()];
+                for (item, position) in self.items.drain(..).zip(positions) {
+                    previous[position] = Some(item);
+                }
+                self.items = previous.into_iter().flatten().collect();
+            }
+        }
+        self.undone.push(command);
+        self.undone.last()
+    }
+
+    /// Applies the last undone command again, returns it or `None` when there is nothing to redo
+    pub fn redo(&mut self) -> Option<&Command<T>> {
+        let command = self.undone.pop()?;
+        self.execute(command)
+            .expect("An undone command applies to the state it was undone from");
+        self.done.last().map(|(command, _)| command)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    /// The items after the first `version` commands
+    pub fn at_version(&self, version: usize) -> Result<Vec<T>, HistoryError> {
+        if version > self.version() {
+            return Err(HistoryError::UnknownVersion(version));
+        }
+        let mut replay = Self::new(self.initial.clone());
+        for command in self.commands().take(version) {
+            replay.execute(command.clone())?;
+        }
+        Ok(replay.items)
+    }
+
+    /// How to go from one version to another
+    pub fn diff(&self, from: usize, to: usize) -> Result<Vec<Change<T>>, HistoryError> {
+        Ok(diff(&self.at_version(from)?, &self.at_version(to)?))
+    }
+
+    pub fn snapshot(&self) -> Snapshot<T> {
+        Snapshot {
+            initial: self.initial.clone(),
+            commands: self.commands().cloned().collect(),
+            undone: self.undone.clone(),
+            items: self.items.clone(),
+        }
+    }
+
+    /// Rebuilds a history by replaying the commands of a snapshot
+    ///
+    /// The undone commands are redone on a copy of the items, so that a snapshot whose
+    /// undone commands cannot be redone is an error instead of a failing [SaladHistory::redo].
+    pub fn from_snapshot(snapshot: Snapshot<T>) -> Result<Self, HistoryError> {
+        let mut history = Self::new(snapshot.initial);
+        for command in snapshot.commands {
+            history.execute(command)?;
+        }
+        let mut redone = Self::new(history.items.clone());
+        for command in snapshot.undone.iter().rev() {
+            redone.execute(command.clone())?;
+        }
+        history.undone = snapshot.undone;
+        Ok(history)
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String>
+    where
+        T: Serialize,
+    {
+        serde_json::to_string_pretty(&self.snapshot())
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, HistoryError>
+    where
+        T: DeserializeOwned,
+    {
+        Self::from_snapshot(serde_json::from_str(json)?)
+    }
+
+    /// Applies a command and records it, without touching the undone commands
+    fn execute(&mut self, command: Command<T>) -> Result<(), HistoryError> {
+        let len = self.items.len();
+        let revert = match &command {
+            Command::Push { item } => {
+                self.items.push(item.clone());
+                Revert::Remove { index: len }
+            }
+            Command::Pop => {
+                let item = self.items.pop().ok_or(HistoryError::Empty)?;
+                Revert::Insert {
+                    index: len - 1,
+                    item,
+                }
+            }
+            Command::Insert { index, item } => {
+                if *index > len {
+                    return Err(HistoryError::IndexOutOfBounds { index: *index, len });
+                }
+                self.items.insert(*index, item.clone());
+                Revert::Remove { index: *index }
+            }
+            Command::Remove { index } => {
+                if *index >= len {
+                    return Err(HistoryError::IndexOutOfBounds { index: *index, len });
+                }
+                let item = self.items.remove(*index);
+                Revert::Insert {
+                    index: *index,
+                    item,
+                }
+            }
+            Command::RemoveFirst { item } => {
+                let index = self
+                    .items
+                    .iter()
+                    .position(|x| x == item)
+                    .ok_or_else(|| HistoryError::NotFound(format!("{:?}", item)))?;
+                let item = self.items.remove(index);
+                Revert::Insert { index, item }
+            }
+            Command::Shuffle { seed } => {
+                let mut positions: Vec<usize> = (0..len).collect();
+                positions.shuffle(&mut StdRng::seed_from_u64(*seed));
+                self.reorder(positions)
+            }
+            Command::Sort => {
+                let mut positions: Vec<usize> = (0..len).collect();
+                vec_ops::sort_by_key_stable(
+                    &mut positions,
+                    |&i| self.items[i].clone(),
+                    vec_ops::SortOrder::Ascending,
+                );
+                self.reorder(positions)
+            }
+        };
+        self.done.push((command, revert));
+        Ok(())
+    }
+
+    /// Puts the item at `positions[k]` in place `k`
+    fn reorder(&mut self, positions: Vec<usize>) -> Revert<T> {
+        self.items = positions.iter().map(|&i| self.items[i].clone()).collect();
+        Revert::Reorder { positions }
+    }
+}
+
+/// The changes turning `from` into `to`, keeping their longest common subsequence
+///
+/// ```rust
+/// use mutable_salad::history::{diff, Change};
+///
+/// let changes = diff(&["apple", "banana", "cherry"], &["banana", "cherry", "figs"]);
+/// assert_eq!(changes, vec![
+///     Change::Removed("apple"),
+///     Change::Kept("banana"),
+///     Change::Kept("cherry"),
+///     Change::Added("figs"),
+/// ]);
+/// ```
+pub fn diff<T: Clone + PartialEq>(from: &[T], to: &[T]) -> Vec<Change<T>> {
+    // common[i][j]: length of the longest common subsequence of from[i..] and to[j..]
+    let mut common = vec![vec![0usize; to.len() + 1]; from.len() + 1];
+    for i in (0..from.len()).rev() {
+        for j in (0..to.len()).rev() {
+            common[i][j] = if from[i] == to[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = Vec::with_capacity(from.len().max(to.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < from.len() || j < to.len() {
+        if i < from.len() && j < to.len() && from[i] == to[j] {
+            changes.push(Change::Kept(from[i].clone()));
+            i += 1;
+            j += 1;
+        } else if j == to.len() || (i < from.len() && common[i + 1][j] >= common[i][j + 1]) {
+            changes.push(Change::Removed(from[i].clone()));
+            i += 1;
+        } else {
+            changes.push(Change::Added(to[j].clone()));
+            j += 1;
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn salad() -> SaladHistory<String> {
+        let mut salad = SaladHistory::new(["apple", "banana", "cherry"].map(String::from).to_vec());
+        salad.push("figs".to_string());
+        salad.shuffle(42);
+        salad.remove(1).unwrap();
+        salad.sort();
+        salad.insert(0, "kiwi".to_string()).unwrap();
+        salad
+    }
+
+    #[test]
+    fn undoing_everything_gives_back_every_version() {
+        let mut salad = salad();
+        let versions: Vec<Vec<String>> = (0..=salad.version())
+            .map(|version| salad.at_version(version).unwrap())
+            .collect();
+        while salad.can_undo() {
+            assert_eq!(salad.items(), versions[salad.version()].as_slice());
+            salad.undo();
+        }
+        assert_eq!(salad.items(), versions[0].as_slice());
+        while salad.redo().is_some() {
+            assert_eq!(salad.items(), versions[salad.version()].as_slice());
+        }
+        assert_eq!(salad.version(), 5);
+    }
+
+    #[test]
+    fn failed_commands_are_not_recorded() {
+        let mut salad = SaladHistory::new(vec![1]);
+        salad.pop().unwrap();
+        assert!(matches!(salad.pop(), Err(HistoryError::Empty)));
+        assert!(matches!(
+            salad.insert(2, 5),
+            Err(HistoryError::IndexOutOfBounds { index: 2, len: 0 })
+        ));
+        assert!(matches!(
+            salad.remove_first(7),
+            Err(HistoryError::NotFound(_))
+        ));
+        assert_eq!(salad.version(), 1);
+        // a new command drops the redo list
+        salad.undo();
+        salad.push(3);
+        assert!(!salad.can_redo());
+    }
+
+    #[test]
+    fn json_round_trip_replays_the_commands() {
+        let mut salad = salad();
+        salad.undo();
+        let json = salad.to_json().unwrap();
+        assert!(json.contains(r#""command": "shuffle""#));
+
+        let mut loaded = SaladHistory::<String>::from_json(&json).unwrap();
+        assert_eq!(loaded.items(), salad.items());
+        assert_eq!(loaded.snapshot(), salad.snapshot());
+        loaded.redo();
+        assert_eq!(loaded.items()[0], "kiwi");
+        assert!(SaladHistory::<String>::from_json(r#"{"initial": []}"#).is_err());
+    }
+
+    #[test]
+    fn undone_commands_that_cannot_be_redone_are_refused() {
+        let json =
+            r#"{"initial": [], "commands": [], "undone": [{"command": "pop"}], "items": []}"#;
+        assert!(matches!(
+            SaladHistory::<String>::from_json(json),
+            Err(HistoryError::Empty)
+        ));
+        // redone last to first: the push comes before the pop
+        let json = r#"{"initial": [], "commands": [],
+            "undone": [{"command": "pop"}, {"command": "push", "item": "kiwi"}], "items": []}"#;
+        let mut loaded = SaladHistory::<String>::from_json(json).unwrap();
+        loaded.redo();
+        loaded.redo();
+        assert!(loaded.items().is_empty());
+    }
+
+    #[test]
+    fn diff_between_versions() {
+        let salad = salad();
+        let changes = salad.diff(0, 1).unwrap();
+        assert_eq!(changes.last(), Some(&Change::Added("figs".to_string())));
+        assert!(changes[..3]
+            .iter()
+            .all(|change| matches!(change, Change::Kept(_))));
+        assert!(matches!(
+            salad.diff(0, 9),
+            Err(HistoryError::UnknownVersion(9))
+        ));
+    }
+}
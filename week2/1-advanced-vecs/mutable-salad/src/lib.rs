@@ -0,0 +1,11 @@
+//! # Mutable salad
+//!
+//! Generic helpers for vectors, grown from the fruit salad exercises:
+//!
+//! - [vec_ops]: removing, sorting, deduplicating, chunking and windowing
+//! - [multiset]: counting items, with the most common ones first
+//! - [history]: a salad that records its changes, with undo, redo and JSON snapshots
+
+pub mod history;
+pub mod multiset;
+pub mod vec_ops;
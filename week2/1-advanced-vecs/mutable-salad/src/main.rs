@@ -1,4 +1,6 @@
-use std::collections::HashMap;
+use mutable_salad::history::{Change, SaladHistory};
+use mutable_salad::multiset::Multiset;
+use mutable_salad::vec_ops::{self, SortOrder};
 use rand::seq::SliceRandom;
 
 fn main() {
@@ -25,43 +27,56 @@ fn main() {
     }
 
     // Remove an element from the vector
-    remove_from_vec("banana", &mut fruit_salad);
-    println!("Fruit salad after removing banana: {:?}", fruit_salad);
-
-
-    // Sort the vector
-    sort_vec(&mut fruit_salad);
-
-
-    // Count the occurrences of each fruit in the vector
-    let count = count_fruits_occurrences(&fruit_salad);
-    println!("Fruit salad count: {:?}", count);
-}
+    match vec_ops::remove_first(&mut fruit_salad, &"banana") {
+        Some(fruit) => println!("Fruit salad after removing {}: {:?}", fruit, fruit_salad),
+        None => println!("There was no banana in the fruit salad"),
+    }
 
+    // Sort the vector alphabetically
+    vec_ops::sort_stable(&mut fruit_salad, SortOrder::Ascending);
+    println!("Fruit salad sorted: {:?}", fruit_salad);
 
-fn sort_vec(vec: &mut Vec<&str>) {
-    // Sort alphabetically
-    vec.sort();
-    println!("Fruit salad sorted: {:?}", vec);
-}
+    // Count the occurrences of each fruit in a bigger salad
+    fruit_salad.extend(["cherry", "apple", "cherry"]);
+    let count: Multiset<&str> = fruit_salad.iter().copied().collect();
+    println!("Fruit salad count: {}", count);
+    println!("Most common: {:?}", count.most_common(2));
 
+    // Keep one of each, then serve it in two bowls
+    let repeated = vec_ops::dedup_preserving_order(&mut fruit_salad);
+    println!("Without the repeated {:?}: {:?}", repeated, fruit_salad);
+    for (i, bowl) in vec_ops::split_evenly(&fruit_salad, 2).iter().enumerate() {
+        println!("Bowl {}: {:?}", i + 1, bowl);
+    }
 
-fn remove_from_vec(element: &str, vec: &mut Vec<&str>) {
-    let index = vec.iter().position(|&x| x == element);
-    match index {
-        Some(i) => {
-            vec.remove(i);
-            println!("Element {} removed from vector", element);
-        },
-        None => println!("Element {} not found in vector", element),
+    // The same steps, recorded so that they can be undone and audited
+    let mut history = SaladHistory::new(vec!["apple", "banana", "cherry", "dates", "elderberries"]);
+    history.push("figs");
+    history.shuffle(rand::random());
+    history.pop().expect("The salad is not empty");
+    history.remove_first("banana").ok();
+    history.sort();
+    for (version, command) in history.commands().enumerate() {
+        println!("Version {}: {}", version + 1, command);
     }
-}
+    history.undo();
+    println!("Undone the sort: {:?}", history.items());
+    history.redo();
 
-fn count_fruits_occurrences(vec: &Vec<&str>) -> HashMap<String, usize> {
-    let mut count = HashMap::new();
-    for f in vec.iter() {
-        let c = count.entry(f.to_string()).or_insert(0);
-        *c += 1;
+    let changes = history
+        .diff(0, history.version())
+        .expect("Both versions exist");
+    let summary: Vec<String> = changes
+        .iter()
+        .filter_map(|change| match change {
+            Change::Added(fruit) => Some(format!("+{}", fruit)),
+            Change::Removed(fruit) => Some(format!("-{}", fruit)),
+            Change::Kept(_) => None,
+        })
+        .collect();
+    println!("Changes since the start: {}", summary.join(" "));
+    match history.to_json() {
+        Ok(json) => println!("Snapshot:\n{}", json),
+        Err(e) => eprintln!("Could not save the history: {}", e),
     }
-    count
-}
\ No newline at end of file
+}
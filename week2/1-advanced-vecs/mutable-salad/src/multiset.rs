@@ -0,0 +1,187 @@
+//! A sorted multiset: every distinct item with the number of times it was added
+
+use std::cmp::Reverse;
+use std::collections::btree_map::{self, BTreeMap};
+use std::fmt::{self, Display};
+
+/// Items with their counts, iterated in the order of the items
+///
+/// # Example
+///
+/// ```rust
+/// use mutable_salad::multiset::Multiset;
+///
+/// let counts: Multiset<&str> = ["kiwi", "apple", "kiwi", "fig", "kiwi", "apple"].into_iter().collect();
+/// assert_eq!(counts.count(&"kiwi"), 3);
+/// assert_eq!(counts.len(), 6);
+/// assert_eq!(counts.distinct_len(), 3);
+/// assert_eq!(counts.most_common(2), vec![(&"kiwi", 3), (&"apple", 2)]);
+/// assert_eq!(counts.iter().map(|(fruit, _)| *fruit).collect::<Vec<_>>(), vec!["apple", "fig", "kiwi"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Multiset<T> {
+    counts: BTreeMap<T, usize>,
+    len: usize,
+}
+
+impl<T: Ord> Multiset<T> {
+    pub fn new() -> Self {
+        Self {
+            counts: BTreeMap::new(),
+            len: 0,
+        }
+    }
+
+    pub fn insert(&mut self, item: T) {
+        self.insert_n(item, 1);
+    }
+
+    /// Adds `n` copies of `item`
+    pub fn insert_n(&mut self, item: T, n: usize) {
+        if n > 0 {
+            *self.counts.entry(item).or_insert(0) += n;
+            self.len += n;
+        }
+    }
+
+    /// Removes one copy of `item`, returns whether there was one
+    pub fn remove(&mut self, item: &T) -> bool {
+        match self.counts.get_mut(item) {
+            Some(count) => {
+                *count -= 1;
+                if *count == 0 {
+                    self.counts.remove(item);
+                }
+                self.len -= 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes every copy of `item`, returns how many there were
+    pub fn remove_all(&mut self, item: &T) -> usize {
+        let count = self.counts.remove(item).unwrap_or(0);
+        self.len -= count;
+        count
+    }
+
+    pub fn count(&self, item: &T) -> usize {
+        self.counts.get(item).copied().unwrap_or(0)
+    }
+
+    pub fn contains(&self, item: &T) -> bool {
+        self.counts.contains_key(item)
+    }
+
+    /// Number of items, copies included
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Number of distinct items
+    pub fn distinct_len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Distinct items with their counts, in the order of the items
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter(self.counts.iter())
+    }
+
+    /// The `n` items with the largest counts, from the most common; equal counts are in the
+    /// order of the items
+    pub fn most_common(&self, n: usize) -> Vec<(&T, usize)> {
+        let mut items: Vec<(&T, usize)> = self.iter().collect();
+        // the sort is stable, items with the same count stay sorted
+        items.sort_by_key(|(_, count)| Reverse(*count));
+        items.truncate(n);
+        items
+    }
+
+    /// The `n` items with the smallest counts, from the least common
+    pub fn least_common(&self, n: usize) -> Vec<(&T, usize)> {
+        let mut items: Vec<(&T, usize)> = self.iter().collect();
+        items.sort_by_key(|(_, count)| *count);
+        items.truncate(n);
+        items
+    }
+}
+
+impl<T: Ord> Default for Multiset<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for Multiset<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(items: I) -> Self {
+        let mut multiset = Self::new();
+        multiset.extend(items);
+        multiset
+    }
+}
+
+impl<T: Ord> Extend<T> for Multiset<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, items: I) {
+        for item in items {
+            self.insert(item);
+        }
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a Multiset<T> {
+    type Item = (&'a T, usize);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+/// Distinct items with their counts, see [Multiset::iter]
+pub struct Iter<'a, T>(btree_map::Iter<'a, T, usize>);
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (&'a T, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(item, count)| (item, *count))
+    }
+}
+
+/// `{apple: 2, kiwi: 3}`
+impl<T: Display> Display for Multiset<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{")?;
+        for (i, (item, count)) in self.counts.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}: {}", item, count)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removing_keeps_counts_consistent() {
+        let mut counts: Multiset<char> = "banana".chars().collect();
+        assert_eq!(counts.to_string(), "{a: 3, b: 1, n: 2}");
+        assert!(counts.remove(&'b'));
+        assert!(!counts.remove(&'b'));
+        assert!(!counts.contains(&'b'));
+        assert_eq!(counts.remove_all(&'a'), 3);
+        assert_eq!((counts.len(), counts.distinct_len()), (2, 1));
+        counts.insert_n('z', 0);
+        assert_eq!(counts.least_common(5), vec![(&'n', 2)]);
+    }
+}
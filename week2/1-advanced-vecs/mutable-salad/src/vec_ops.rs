@@ -0,0 +1,195 @@
+//! Operations on vectors and slices that return what they did instead of printing it
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::HashSet;
+use std::hash::Hash;
+
+/// Removes the first item equal to `item` and returns it, `None` when there is none
+///
+/// ```rust
+/// use mutable_salad::vec_ops::remove_first;
+///
+/// let mut salad = vec!["apple", "banana", "cherry", "banana"];
+/// assert_eq!(remove_first(&mut salad, &"banana"), Some("banana"));
+/// assert_eq!(salad, vec!["apple", "cherry", "banana"]);
+/// assert_eq!(remove_first(&mut salad, &"kiwi"), None);
+/// ```
+pub fn remove_first<T: PartialEq>(vec: &mut Vec<T>, item: &T) -> Option<T> {
+    let index = vec.iter().position(|x| x == item)?;
+    Some(vec.remove(index))
+}
+
+/// Removes every item equal to `item` and returns them, the others keep their order
+///
+/// ```rust
+/// use mutable_salad::vec_ops::remove_all;
+///
+/// let mut salad = vec!["banana", "apple", "banana", "cherry"];
+/// assert_eq!(remove_all(&mut salad, &"banana"), vec!["banana", "banana"]);
+/// assert_eq!(salad, vec!["apple", "cherry"]);
+/// ```
+pub fn remove_all<T: PartialEq>(vec: &mut Vec<T>, item: &T) -> Vec<T> {
+    remove_where(vec, |x| x == item)
+}
+
+/// Removes the items matching `predicate` and returns them, both in their original order
+///
+/// ```rust
+/// use mutable_salad::vec_ops::remove_where;
+///
+/// let mut salad = vec!["apple", "banana", "cherry", "blueberry"];
+/// assert_eq!(remove_where(&mut salad, |fruit| fruit.starts_with('b')), vec!["banana", "blueberry"]);
+/// assert_eq!(salad, vec!["apple", "cherry"]);
+/// ```
+pub fn remove_where<T, F: FnMut(&T) -> bool>(vec: &mut Vec<T>, mut predicate: F) -> Vec<T> {
+    let (removed, kept) = std::mem::take(vec).into_iter().partition(|x| predicate(x));
+    *vec = kept;
+    removed
+}
+
+/// Direction of a sort
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+/// Sorts the items, equal items keep their order
+///
+/// ```rust
+/// use mutable_salad::vec_ops::{sort_stable, SortOrder};
+///
+/// let mut salad = vec!["cherry", "apple", "banana"];
+/// sort_stable(&mut salad, SortOrder::Descending);
+/// assert_eq!(salad, vec!["cherry", "banana", "apple"]);
+/// ```
+pub fn sort_stable<T: Ord>(items: &mut [T], order: SortOrder) {
+    match order {
+        SortOrder::Ascending => items.sort(),
+        SortOrder::Descending => items.sort_by(|a, b| b.cmp(a)),
+    }
+}
+
+/// Sorts the items by a key, computed once per item; items with equal keys keep their
+/// order, also when sorting in descending order
+///
+/// ```rust
+/// use mutable_salad::vec_ops::{sort_by_key_stable, SortOrder};
+///
+/// let mut salad = vec!["fig", "banana", "kiwi", "apple", "date"];
+/// sort_by_key_stable(&mut salad, |fruit| fruit.len(), SortOrder::Descending);
+/// assert_eq!(salad, vec!["banana", "apple", "kiwi", "date", "fig"]);
+/// ```
+pub fn sort_by_key_stable<T, K, F>(items: &mut [T], key: F, order: SortOrder)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    match order {
+        SortOrder::Ascending => items.sort_by_cached_key(key),
+        SortOrder::Descending => {
+            let mut key = key;
+            items.sort_by_cached_key(|item| Reverse(key(item)))
+        }
+    }
+}
+
+/// Sorts the items with a comparison, equal items keep their order
+///
+/// ```rust
+/// use mutable_salad::vec_ops::sort_by_stable;
+///
+/// let mut salad = vec!["Banana", "apple", "Cherry"];
+/// sort_by_stable(&mut salad, |a, b| a.to_lowercase().cmp(&b.to_lowercase()));
+/// assert_eq!(salad, vec!["apple", "Banana", "Cherry"]);
+/// ```
+pub fn sort_by_stable<T, F: FnMut(&T, &T) -> Ordering>(items: &mut [T], compare: F) {
+    items.sort_by(compare);
+}
+
+/// Removes the repeated items, wherever they are, and returns them; the first occurrence of
+/// every item stays where it was
+///
+/// `Vec::dedup` only removes consecutive repeats.
+///
+/// ```rust
+/// use mutable_salad::vec_ops::dedup_preserving_order;
+///
+/// let mut salad = vec!["kiwi", "apple", "kiwi", "fig", "apple"];
+/// assert_eq!(dedup_preserving_order(&mut salad), vec!["kiwi", "apple"]);
+/// assert_eq!(salad, vec!["kiwi", "apple", "fig"]);
+/// ```
+pub fn dedup_preserving_order<T: Eq + Hash + Clone>(vec: &mut Vec<T>) -> Vec<T> {
+    let mut seen = HashSet::new();
+    remove_where(vec, |x| !seen.insert(x.clone()))
+}
+
+/// Applies `f` to consecutive chunks of `size` items, the last one may be shorter
+///
+/// # Panics
+///
+/// When `size` is 0.
+///
+/// ```rust
+/// use mutable_salad::vec_ops::chunked;
+///
+/// let bowls = chunked(&["apple", "banana", "cherry", "dates", "figs"], 2, |bowl| bowl.join("+"));
+/// assert_eq!(bowls, vec!["apple+banana", "cherry+dates", "figs"]);
+/// ```
+pub fn chunked<T, R, F: FnMut(&[T]) -> R>(items: &[T], size: usize, f: F) -> Vec<R> {
+    items.chunks(size).map(f).collect()
+}
+
+/// Splits the items into `parts` consecutive slices whose lengths differ by at most one,
+/// the longer ones first
+///
+/// # Panics
+///
+/// When `parts` is 0.
+///
+/// ```rust
+/// use mutable_salad::vec_ops::split_evenly;
+///
+/// let bowls = split_evenly(&[1, 2, 3, 4, 5, 6, 7], 3);
+/// assert_eq!(bowls, vec![&[1, 2, 3][..], &[4, 5], &[6, 7]]);
+/// ```
+pub fn split_evenly<T>(items: &[T], parts: usize) -> Vec<&[T]> {
+    assert!(parts > 0, "Cannot split into 0 parts");
+    let (size, longer) = (items.len() / parts, items.len() % parts);
+    let mut rest = items;
+    (0..parts)
+        .map(|part| {
+            let (chunk, tail) = rest.split_at(size + usize::from(part < longer));
+            rest = tail;
+            chunk
+        })
+        .collect()
+}
+
+/// Applies `f` to every window of `size` consecutive items, nothing when there are fewer
+///
+/// # Panics
+///
+/// When `size` is 0.
+///
+/// ```rust
+/// use mutable_salad::vec_ops::windowed;
+///
+/// let sums = windowed(&[1, 2, 3, 4, 5], 3, |window| window.iter().sum::<i32>());
+/// assert_eq!(sums, vec![6, 9, 12]);
+/// ```
+pub fn windowed<T, R, F: FnMut(&[T]) -> R>(items: &[T], size: usize, f: F) -> Vec<R> {
+    items.windows(size).map(f).collect()
+}
+
+/// Pairs of neighbouring items
+///
+/// ```rust
+/// use mutable_salad::vec_ops::pairwise;
+///
+/// assert_eq!(pairwise(&["a", "b", "c"]), vec![(&"a", &"b"), (&"b", &"c")]);
+/// ```
+pub fn pairwise<T>(items: &[T]) -> Vec<(&T, &T)> {
+    items.windows(2).map(|pair| (&pair[0], &pair[1])).collect()
+}
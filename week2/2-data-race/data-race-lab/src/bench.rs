@@ -0,0 +1,532 @@
+//! Benchmarks of shared counters behind different synchronisation primitives
+//!
+//! Every thread performs a mix of reads and writes on a table of counters, picking the
+//! counter and the kind of operation at random. The same workload runs on:
+//!
+//! - [Primitive::Mutex]: one `Mutex<Vec<u64>>`
+//! - [Primitive::RwLock]: one `RwLock<Vec<u64>>`, reads share the lock
+//! - [Primitive::Atomic]: one `AtomicU64` per counter, no lock at all
+//! - [Primitive::Sharded]: the counters split between several mutexes by key
+//! - [Primitive::Channel]: a single thread owns the counters, the others send it requests
+//!   over `std::sync::mpsc` channels (lock-free queues)
+//!
+//! Each operation is timed, the results report the throughput of all threads together and
+//! percentiles of the latency of single operations.
+
+use clap::ValueEnum;
+use std::fmt::{self, Display};
+use std::hint::black_box;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The synchronisation primitive protecting the counters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Primitive {
+    Mutex,
+    RwLock,
+    Atomic,
+    Sharded,
+    Channel,
+}
+
+impl Primitive {
+    pub const ALL: [Primitive; 5] = [
+        Primitive::Mutex,
+        Primitive::RwLock,
+        Primitive::Atomic,
+        Primitive::Sharded,
+        Primitive::Channel,
+    ];
+}
+
+impl Display for Primitive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Primitive::Mutex => "Mutex",
+            Primitive::RwLock => "RwLock",
+            Primitive::Atomic => "Atomic",
+            Primitive::Sharded => "Sharded",
+            Primitive::Channel => "Channel",
+        };
+        f.pad(name)
+    }
+}
+
+/// The workload of a benchmark run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchConfig {
+    pub threads: usize,
+    pub operations_per_thread: usize,
+    /// Share of the operations that are reads, between 0 and 1
+    pub read_ratio: f64,
+    /// Number of counters
+    pub keys: usize,
+    /// Number of locks of [Primitive::Sharded]
+    pub shards: usize,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            threads: 4,
+            operations_per_thread: 100_000,
+            read_ratio: 0.9,
+            keys: 64,
+            shards: 16,
+        }
+    }
+}
+
+/// Measurements of one run
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchResult {
+    pub primitive: Primitive,
+    pub threads: usize,
+    pub read_ratio: f64,
+    pub operations: usize,
+    pub elapsed: Duration,
+    /// Operations per second, all threads together
+    pub throughput: f64,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+    /// Sum of the counters at the end, the number of writes
+    pub total: u64,
+}
+
+/// Shared counters, accessed by every thread through its own [Worker]
+trait Counters: Sync {
+    type Worker<'a>: Worker + Send
+    where
+        Self: 'a;
+
+    fn worker(&self) -> Self::Worker<'_>;
+
+    fn total(&self) -> u64;
+}
+
+trait Worker {
+    fn read(&mut self, key: usize) -> u64;
+
+    fn write(&mut self, key: usize);
+}
+
+impl Counters for Mutex<Vec<u64>> {
+    type Worker<'a> = &'a Self;
+
+    fn worker(&self) -> &Self {
+        self
+    }
+
+    fn total(&self) -> u64 {
+        self.lock().unwrap().iter().sum()
+    }
+}
+
+impl Worker for &Mutex<Vec<u64>> {
+    fn read(&mut self, key: usize) -> u64 {
+        self.lock().unwrap()[key]
+    }
+
+    fn write(&mut self, key: usize) {
+        self.lock().unwrap()[key] += 1;
+    }
+}
+
+impl Counters for RwLock<Vec<u64>> {
+    type Worker<'a> = &'a Self;
+
+    fn worker(&self) -> &Self {
+        self
+    }
+
+    fn total(&self) -> u64 {
+        self.read().unwrap().iter().sum()
+    }
+}
+
+impl Worker for &RwLock<Vec<u64>> {
+    fn read(&mut self, key: usize) -> u64 {
+        RwLock::read(self).unwrap()[key]
+    }
+
+    fn write(&mut self, key: usize) {
+        RwLock::write(self).unwrap()[key] += 1;
+    }
+}
+
+impl Counters for Vec<AtomicU64> {
+    type Worker<'a> = &'a Self;
+
+    fn worker(&self) -> &Self {
+        self
+    }
+
+    fn total(&self) -> u64 {
+        self.iter()
+            .map(|counter| counter.load(Ordering::SeqCst))
+            .sum()
+    }
+}
+
+impl Worker for &Vec<AtomicU64> {
+    fn read(&mut self, key: usize) -> u64 {
+        self[key].load(Ordering::Acquire)
+    }
+
+    fn write(&mut self, key: usize) {
+        self[key].fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+/// Counter `key` is number `key / shards` of shard `key % shards`
+struct Sharded {
+    shards: Vec<Mutex<Vec<u64>>>,
+}
+
+impl Sharded {
+    fn new(keys: usize, shards: usize) -> Self {
+        let shards = shards.clamp(1, keys.max(1));
+        Self {
+            shards: (0..shards)
+                .map(|_| Mutex::new(vec![0; keys.div_ceil(shards)]))
+                .collect(),
+        }
+    }
+
+    fn slot(&self, key: usize) -> (&Mutex<Vec<u64>>, usize) {
+        let n = self.shards.len();
+        (&self.shards[key % n], key / n)
+    }
+}
+
+impl Counters for Sharded {
+    type Worker<'a> = &'a Self;
+
+    fn worker(&self) -> &Self {
+        self
+    }
+
+    fn total(&self) -> u64 {
+        self.shards
+            .iter()
+            .map(|shard| shard.lock().unwrap().iter().sum::<u64>())
+            .sum()
+    }
+}
+
+impl Worker for &Sharded {
+    fn read(&mut self, key: usize) -> u64 {
+        let (shard, index) = self.slot(key);
+        shard.lock().unwrap()[index]
+    }
+
+    fn write(&mut self, key: usize) {
+        let (shard, index) = self.slot(key);
+        shard.lock().unwrap()[index] += 1;
+    }
+}
+
+enum Request {
+    Read { key: usize, reply: Sender<u64> },
+    Write { key: usize },
+    Total { reply: Sender<u64> },
+}
+
+/// The counters live in a server thread, which stops when the last sender is dropped
+struct ChannelCounters {
+    requests: Sender<Request>,
+}
+
+impl ChannelCounters {
+    fn new(keys: usize) -> Self {
+        let (requests, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut counters = vec![0u64; keys];
+            for request in receiver {
+                match request {
+                    Request::Read { key, reply } => {
+                        reply.send(counters[key]).ok();
+                    }
+                    Request::Write { key } => counters[key] += 1,
+                    Request::Total { reply } => {
+                        reply.send(counters.iter().sum()).ok();
+                    }
+                }
+            }
+        });
+        Self { requests }
+    }
+
+    /// Sends a request that wants an answer
+    fn ask(requests: &Sender<Request>, request: impl FnOnce(Sender<u64>) -> Request) -> u64 {
+        let (reply, answer) = mpsc::channel();
+        requests
+            .send(request(reply))
+            .expect("The server runs as long as there are senders");
+        answer.recv().expect("The server answers every request")
+    }
+}
+
+struct ChannelWorker {
+    requests: Sender<Request>,
+}
+
+impl Counters for ChannelCounters {
+    type Worker<'a> = ChannelWorker;
+
+    fn worker(&self) -> ChannelWorker {
+        ChannelWorker {
+            requests: self.requests.clone(),
+        }
+    }
+
+    fn total(&self) -> u64 {
+        Self::ask(&self.requests, |reply| Request::Total { reply })
+    }
+}
+
+impl Worker for ChannelWorker {
+    fn read(&mut self, key: usize) -> u64 {
+        ChannelCounters::ask(&self.requests, |reply| Request::Read { key, reply })
+    }
+
+    /// Writes do not wait for the server
+    fn write(&mut self, key: usize) {
+        self.requests
+            .send(Request::Write { key })
+            .expect("The server runs as long as there are senders");
+    }
+}
+
+/// A small xorshift generator, so that every thread has its own cheap random sequence
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Uniform in `[0, 1)`
+    fn next_f64(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Runs the workload of `config` on the counters of `primitive`
+///
+/// # Example
+///
+/// ```rust
+/// use data_race_lab::bench::{run, BenchConfig, Primitive};
+///
+/// let config = BenchConfig { threads: 2, operations_per_thread: 1000, read_ratio: 0.5, ..BenchConfig::default() };
+/// let result = run(Primitive::Atomic, &config);
+/// assert_eq!(result.operations, 2000);
+/// assert!(result.p50 <= result.p99);
+/// ```
+pub fn run(primitive: Primitive, config: &BenchConfig) -> BenchResult {
+    let keys = config.keys.max(1);
+    match primitive {
+        Primitive::Mutex => measure(primitive, &Mutex::new(vec![0; keys]), config),
+        Primitive::RwLock => measure(primitive, &RwLock::new(vec![0; keys]), config),
+        Primitive::Atomic => measure(
+            primitive,
+            &(0..keys).map(|_| AtomicU64::new(0)).collect::<Vec<_>>(),
+            config,
+        ),
+        Primitive::Sharded => measure(primitive, &Sharded::new(keys, config.shards), config),
+        Primitive::Channel => measure(primitive, &ChannelCounters::new(keys), config),
+    }
+}
+
+/// Runs every primitive with every configuration, one configuration after the other
+pub fn run_all(primitives: &[Primitive], configs: &[BenchConfig]) -> Vec<BenchResult> {
+    let mut results = Vec::new();
+    for config in configs {
+        for &primitive in primitives {
+            results.push(run(primitive, config));
+        }
+    }
+    results
+}
+
+fn measure<C: Counters>(primitive: Primitive, counters: &C, config: &BenchConfig) -> BenchResult {
+    let keys = config.keys.max(1);
+    let start = Instant::now();
+    let mut latencies: Vec<Duration> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..config.threads)
+            .map(|thread| {
+                let mut worker = counters.worker();
+                scope.spawn(move || {
+                    let mut rng = XorShift(0x9E37_79B9_7F4A_7C15 ^ (thread as u64 + 1));
+                    let mut latencies = Vec::with_capacity(config.operations_per_thread);
+                    for _ in 0..config.operations_per_thread {
+                        let key = (rng.next() % keys as u64) as usize;
+                        let read = rng.next_f64() < config.read_ratio;
+                        let operation = Instant::now();
+                        if read {
+                            black_box(worker.read(key));
+                        } else {
+                            worker.write(key);
+                        }
+                        latencies.push(operation.elapsed());
+                    }
+                    latencies
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("Benchmark threads do not panic"))
+            .collect()
+    });
+    let elapsed = start.elapsed();
+    latencies.sort_unstable();
+
+    let operations = latencies.len();
+    BenchResult {
+        primitive,
+        threads: config.threads,
+        read_ratio: config.read_ratio,
+        operations,
+        elapsed,
+        throughput: operations as f64 / elapsed.as_secs_f64().max(f64::MIN_POSITIVE),
+        p50: percentile(&latencies, 0.50),
+        p90: percentile(&latencies, 0.90),
+        p99: percentile(&latencies, 0.99),
+        max: latencies.last().copied().unwrap_or_default(),
+        total: counters.total(),
+    }
+}
+
+/// Nearest-rank percentile of sorted values, zero when there are none
+fn percentile(sorted: &[Duration], q: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (q * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Nanoseconds, for the table and the CSV
+fn nanos(duration: Duration) -> u128 {
+    duration.as_nanos()
+}
+
+/// The results as a Markdown table
+pub fn to_table(results: &[BenchResult]) -> String {
+    let mut table = String::from(
+        "| Primitive | Threads | Reads | Operations | Throughput (ops/s) | p50 (ns) | p90 (ns) | p99 (ns) | Max (ns) |\n",
+    );
+    table.push_str("|---|---:|---:|---:|---:|---:|---:|---:|---:|\n");
+    for r in results {
+        table.push_str(&format!(
+            "| {} | {} | {:.0}% | {} | {:.0} | {} | {} | {} | {} |\n",
+            r.primitive,
+            r.threads,
+            r.read_ratio * 100.0,
+            r.operations,
+            r.throughput,
+            nanos(r.p50),
+            nanos(r.p90),
+            nanos(r.p99),
+            nanos(r.max),
+        ));
+    }
+    table
+}
+
+/// The results as CSV, latencies in nanoseconds
+pub fn write_csv<W: Write>(results: &[BenchResult], mut writer: W) -> io::Result<()> {
+    writeln!(
+        writer,
+        "primitive,threads,read_ratio,operations,elapsed_ns,throughput,p50_ns,p90_ns,p99_ns,max_ns"
+    )?;
+    for r in results {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{:.1},{},{},{},{}",
+            r.primitive,
+            r.threads,
+            r.read_ratio,
+            r.operations,
+            nanos(r.elapsed),
+            r.throughput,
+            nanos(r.p50),
+            nanos(r.p90),
+            nanos(r.p99),
+            nanos(r.max),
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_primitive_counts_every_write() {
+        for read_ratio in [0.0, 0.5, 1.0] {
+            let configs = [1, 3].map(|threads| BenchConfig {
+                threads,
+                operations_per_thread: 500,
+                read_ratio,
+                keys: 10,
+                shards: 4,
+            });
+            let results = run_all(&Primitive::ALL, &configs);
+            assert_eq!(results.len(), 10);
+            // the same seeds give the same writes, whatever the primitive
+            for pair in results.chunks(5) {
+                let totals: Vec<u64> = pair.iter().map(|r| r.total).collect();
+                assert!(
+                    totals.iter().all(|&total| total == totals[0]),
+                    "{:?}",
+                    totals
+                );
+            }
+            if read_ratio == 0.0 {
+                assert_eq!(results[5].total, 1500);
+            }
+            if read_ratio == 1.0 {
+                assert!(results.iter().all(|r| r.total == 0));
+            }
+        }
+    }
+
+    #[test]
+    fn percentiles_use_the_nearest_rank() {
+        let values: Vec<Duration> = (1..=100).map(Duration::from_nanos).collect();
+        assert_eq!(percentile(&values, 0.5), Duration::from_nanos(50));
+        assert_eq!(percentile(&values, 0.99), Duration::from_nanos(99));
+        assert_eq!(percentile(&values, 0.0), Duration::from_nanos(1));
+        assert_eq!(percentile(&[], 0.5), Duration::ZERO);
+    }
+
+    #[test]
+    fn table_and_csv_have_a_row_per_result() {
+        let config = BenchConfig {
+            threads: 1,
+            operations_per_thread: 10,
+            ..BenchConfig::default()
+        };
+        let results = run_all(&[Primitive::Mutex, Primitive::Channel], &[config]);
+        assert_eq!(to_table(&results).lines().count(), 4);
+
+        let mut csv = Vec::new();
+        write_csv(&results, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv.lines().nth(2).unwrap().starts_with("Channel,1,0.9,10,"));
+    }
+}
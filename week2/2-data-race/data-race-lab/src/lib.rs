@@ -0,0 +1,15 @@
+//! # Data race lab
+//!
+//! Experiments with the synchronisation primitives of `std::sync`:
+//!
+//! - [bench]: throughput and latency of shared counters behind mutexes, read-write locks,
+//!   atomics, sharded locks and channels
+//! - [lock_order]: mutexes that refuse to be locked in an order that can deadlock
+//! - [pipeline]: stages of worker threads connected by bounded channels, with back-pressure
+//!   and cancellation
+//! - [scenarios]: the four scenarios of the lesson, checked under the loom model checker
+
+pub mod bench;
+pub mod lock_order;
+pub mod pipeline;
+pub mod scenarios;
@@ -0,0 +1,521 @@
+//! Lock-order checking, to find deadlocks before they happen
+//!
+//! Two threads deadlock when each holds a lock the other one waits for, which is only
+//! possible if they take the same locks in different orders. A [TrackedMutex] records, every
+//! time it is locked, an edge from each lock the thread already holds to itself in a
+//! [LockOrderGraph]. An edge that closes a cycle means that some threads take the locks in
+//! conflicting orders: the lock is refused with a [LockOrderError::Cycle] describing the
+//! locks and threads involved, instead of waiting for a deadlock that may or may not happen
+//! on this run.
+//!
+//! ```rust
+//! use data_race_lab::lock_order::{LockOrderGraph, TrackedMutex};
+//! use std::sync::Arc;
+//!
+//! let graph = Arc::new(LockOrderGraph::new());
+//! let a = TrackedMutex::with_graph("a", 1, &graph);
+//! let b = TrackedMutex::with_graph("b", 2, &graph);
+//! {
+//!     let _a = a.lock().unwrap();
+//!     let _b = b.lock().unwrap();
+//! }
+//! let _b = b.lock().unwrap();
+//! let cycle = a.lock().unwrap_err();
+//! assert_eq!(cycle.to_string(), format!("Lock order cycle: {}", graph.cycles()[0]));
+//! ```
+
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Display};
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::thread;
+
+type LockId = usize;
+
+thread_local! {
+    /// Locks held by the current thread, in the order they were taken
+    static HELD: RefCell<Vec<LockId>> = const { RefCell::new(Vec::new()) };
+}
+
+fn next_lock_id() -> LockId {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Name of the current thread, or its id for unnamed threads
+fn current_thread() -> String {
+    let thread = thread::current();
+    match thread.name() {
+        Some(name) => name.to_string(),
+        None => format!("{:?}", thread.id()),
+    }
+}
+
+/// `thread` took lock `to` while holding lock `from`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderEdge {
+    pub from: String,
+    pub to: String,
+    pub thread: String,
+}
+
+/// Locks taken in a circular order, every edge by the first thread that took it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    pub edges: Vec<OrderEdge>,
+}
+
+impl Cycle {
+    /// The threads involved, without repetitions
+    pub fn threads(&self) -> Vec<&str> {
+        let mut threads: Vec<&str> = Vec::new();
+        for edge in &self.edges {
+            if !threads.contains(&edge.thread.as_str()) {
+                threads.push(&edge.thread);
+            }
+        }
+        threads
+    }
+
+    /// The locks of the cycle, in order
+    pub fn locks(&self) -> Vec<&str> {
+        self.edges.iter().map(|edge| edge.from.as_str()).collect()
+    }
+}
+
+/// `data1 -> data2 (worker-0), data2 -> data1 (worker-1)`
+impl Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, edge) in self.edges.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{} -> {} ({})", edge.from, edge.to, edge.thread)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockOrderError {
+    /// Taking the lock would close this cycle, the lock was not taken
+    Cycle(Cycle),
+    /// A thread panicked while holding the lock
+    Poisoned(String),
+}
+
+impl Display for LockOrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockOrderError::Cycle(cycle) => write!(f, "Lock order cycle: {}", cycle),
+            LockOrderError::Poisoned(name) => write!(f, "Lock {} is poisoned", name),
+        }
+    }
+}
+
+impl std::error::Error for LockOrderError {}
+
+#[derive(Debug, Default)]
+struct Graph {
+    names: HashMap<LockId, String>,
+    /// `edges[from][to]`: the first thread that took `to` while holding `from`
+    edges: HashMap<LockId, HashMap<LockId, String>>,
+    cycles: Vec<Cycle>,
+}
+
+impl Graph {
+    /// Shortest path of edges from `start` to `end`
+    fn path(&self, start: LockId, end: LockId) -> Option<Vec<LockId>> {
+        let mut previous: HashMap<LockId, LockId> = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+        while let Some(lock) = queue.pop_front() {
+            if lock == end {
+                let mut path = vec![end];
+                let mut lock = end;
+                while lock != start {
+                    lock = previous[&lock];
+                    path.push(lock);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for &next in self.edges.get(&lock).into_iter().flat_map(HashMap::keys) {
+                if next != start && !previous.contains_key(&next) {
+                    previous.insert(next, lock);
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    fn edge(&self, from: LockId, to: LockId, thread: &str) -> OrderEdge {
+        OrderEdge {
+            from: self.names[&from].clone(),
+            to: self.names[&to].clone(),
+            thread: self
+                .edges
+                .get(&from)
+                .and_then(|targets| targets.get(&to))
+                .map_or_else(|| thread.to_string(), Clone::clone),
+        }
+    }
+}
+
+/// The order in which locks have been taken together, by all threads
+#[derive(Debug, Default)]
+pub struct LockOrderGraph {
+    graph: Mutex<Graph>,
+}
+
+impl LockOrderGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The graph shared by the locks created with [TrackedMutex::new]
+    pub fn global() -> &'static Arc<LockOrderGraph> {
+        static GLOBAL: OnceLock<Arc<LockOrderGraph>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(LockOrderGraph::new()))
+    }
+
+    /// Every cycle found so far
+    pub fn cycles(&self) -> Vec<Cycle> {
+        self.lock_graph().cycles.clone()
+    }
+
+    /// Every recorded ordering, sorted
+    pub fn edges(&self) -> Vec<OrderEdge> {
+        let graph = self.lock_graph();
+        let mut edges: Vec<OrderEdge> = graph
+            .edges
+            .iter()
+            .flat_map(|(&from, targets)| {
+                targets.iter().map(move |(&to, thread)| (from, to, thread))
+            })
+            .map(|(from, to, thread)| graph.edge(from, to, thread))
+            .collect();
+        edges.sort_by(|a, b| (&a.from, &a.to).cmp(&(&b.from, &b.to)));
+        edges
+    }
+
+    fn lock_graph(&self) -> MutexGuard<'_, Graph> {
+        // the graph is consistent after every update, a panic elsewhere does not matter
+        self.graph.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn register(&self, id: LockId, name: &str) {
+        self.lock_graph().names.insert(id, name.to_string());
+    }
+
+    /// Forgets a dropped lock and every order it was part of
+    fn unregister(&self, id: LockId) {
+        let mut graph = self.lock_graph();
+        graph.names.remove(&id);
+        graph.edges.remove(&id);
+        graph.edges.retain(|_, targets| {
+            targets.remove(&id);
+            !targets.is_empty()
+        });
+    }
+
+    /// Records that the current thread takes `lock` while holding `held`,
+    /// unless it would close a cycle
+    fn acquire(&self, held: &[LockId], lock: LockId) -> Result<(), Cycle> {
+        let thread = current_thread();
+        let mut graph = self.lock_graph();
+        for &from in held {
+            if from == lock || !graph.names.contains_key(&from) {
+                // locks of other graphs are not tracked here
+                continue;
+            }
+            if let Some(path) = graph.path(lock, from) {
+                let mut edges: Vec<OrderEdge> = path
+                    .windows(2)
+                    .map(|step| graph.edge(step[0], step[1], &thread))
+                    .collect();
+                edges.insert(0, graph.edge(from, lock, &thread));
+                let cycle = Cycle { edges };
+                graph.cycles.push(cycle.clone());
+                return Err(cycle);
+            }
+        }
+        for &from in held {
+            if from != lock && graph.names.contains_key(&from) {
+                graph
+                    .edges
+                    .entry(from)
+                    .or_default()
+                    .entry(lock)
+                    .or_insert_with(|| thread.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A `Mutex` that checks the order it is locked in, see the [module](self) documentation
+#[derive(Debug)]
+pub struct TrackedMutex<T> {
+    id: LockId,
+    name: String,
+    graph: Arc<LockOrderGraph>,
+    /// Only `None` once [TrackedMutex::into_inner] took it
+    mutex: Option<Mutex<T>>,
+}
+
+impl<T> TrackedMutex<T> {
+    /// A mutex recorded in the [global](LockOrderGraph::global) graph
+    pub fn new(name: &str, value: T) -> Self {
+        Self::with_graph(name, value, LockOrderGraph::global())
+    }
+
+    pub fn with_graph(name: &str, value: T, graph: &Arc<LockOrderGraph>) -> Self {
+        let id = next_lock_id();
+        graph.register(id, name);
+        Self {
+            id,
+            name: name.to_string(),
+            graph: Arc::clone(graph),
+            mutex: Some(Mutex::new(value)),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Waits for the lock, unless taking it while holding the current locks would close
+    /// a cycle in the lock order
+    pub fn lock(&self) -> Result<TrackedMutexGuard<'_, T>, LockOrderError> {
+        let held = HELD.with(|held| held.borrow().clone());
+        self.graph
+            .acquire(&held, self.id)
+            .map_err(LockOrderError::Cycle)?;
+        let guard = self
+            .mutex
+            .as_ref()
+            .expect("The mutex is only taken by into_inner")
+            .lock()
+            .map_err(|_| LockOrderError::Poisoned(self.name.clone()))?;
+        HELD.with(|held| held.borrow_mut().push(self.id));
+        Ok(TrackedMutexGuard { id: self.id, guard })
+    }
+
+    pub fn into_inner(mut self) -> Result<T, LockOrderError> {
+        self.mutex
+            .take()
+            .expect("The mutex is only taken by into_inner")
+            .into_inner()
+            .map_err(|_| LockOrderError::Poisoned(self.name.clone()))
+    }
+}
+
+/// Removes the mutex from its graph, a new mutex never reuses its id
+impl<T> Drop for TrackedMutex<T> {
+    fn drop(&mut self) {
+        self.graph.unregister(self.id);
+    }
+}
+
+/// Unlocks the [TrackedMutex] when dropped
+#[derive(Debug)]
+pub struct TrackedMutexGuard<'a, T> {
+    id: LockId,
+    guard: MutexGuard<'a, T>,
+}
+
+impl<T> Deref for TrackedMutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for TrackedMutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T> Drop for TrackedMutexGuard<'_, T> {
+    fn drop(&mut self) {
+        HELD.with(|held| {
+            let mut held = held.borrow_mut();
+            if let Some(position) = held.iter().rposition(|&id| id == self.id) {
+                held.remove(position);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Barrier;
+
+    type Data = Arc<TrackedMutex<Vec<i32>>>;
+
+    fn locks() -> (Arc<LockOrderGraph>, Data, Data) {
+        let graph = Arc::new(LockOrderGraph::new());
+        let data1 = Arc::new(TrackedMutex::with_graph("data1", vec![1, 2, 3], &graph));
+        let data2 = Arc::new(TrackedMutex::with_graph("data2", vec![4, 5, 6], &graph));
+        (graph, data1, data2)
+    }
+
+    /// The locking of the original `deadlock_handling`: even threads take data1 first,
+    /// odd threads data2 first
+    fn opposite_orders(
+        i: usize,
+        data1: &TrackedMutex<Vec<i32>>,
+        data2: &TrackedMutex<Vec<i32>>,
+    ) -> Result<(), LockOrderError> {
+        let (mut lock1, mut lock2) = if i.is_multiple_of(2) {
+            (data1.lock()?, data2.lock()?)
+        } else {
+            (data2.lock()?, data1.lock()?)
+        };
+        lock1[i] += 1;
+        lock2[i] += 1;
+        Ok(())
+    }
+
+    fn spawn_named<F: FnOnce() -> R + Send + 'static, R: Send + 'static>(
+        name: &str,
+        f: F,
+    ) -> thread::JoinHandle<R> {
+        thread::Builder::new()
+            .name(name.to_string())
+            .spawn(f)
+            .unwrap()
+    }
+
+    #[test]
+    fn opposite_orders_are_caught_even_without_a_deadlock() {
+        let (graph, data1, data2) = locks();
+        // one thread after the other: they never actually wait for each other
+        let results: Vec<Result<(), LockOrderError>> = (0..3)
+            .map(|i| {
+                let (data1, data2) = (Arc::clone(&data1), Arc::clone(&data2));
+                spawn_named(&format!("worker-{}", i), move || {
+                    opposite_orders(i, &data1, &data2)
+                })
+                .join()
+                .unwrap()
+            })
+            .collect();
+
+        assert!(results[0].is_ok());
+        let Err(LockOrderError::Cycle(cycle)) = &results[1] else {
+            panic!("expected a cycle, got {:?}", results[1]);
+        };
+        assert_eq!(cycle.locks(), vec!["data2", "data1"]);
+        assert_eq!(cycle.threads(), vec!["worker-1", "worker-0"]);
+        assert_eq!(
+            cycle.to_string(),
+            "data2 -> data1 (worker-1), data1 -> data2 (worker-0)"
+        );
+        // the order data1 -> data2 is established, thread 2 follows it
+        assert!(results[2].is_ok());
+        assert_eq!(graph.cycles(), vec![cycle.clone()]);
+    }
+
+    #[test]
+    fn a_real_deadlock_is_refused_instead_of_hanging() {
+        let (graph, data1, data2) = locks();
+        let barrier = Arc::new(Barrier::new(2));
+        let handles: Vec<_> = (0..2)
+            .map(|i| {
+                let (data1, data2) = (Arc::clone(&data1), Arc::clone(&data2));
+                let barrier = Arc::clone(&barrier);
+                spawn_named(&format!("worker-{}", i), move || {
+                    let (first, second) = if i == 0 {
+                        (&data1, &data2)
+                    } else {
+                        (&data2, &data1)
+                    };
+                    let _first = first.lock().unwrap();
+                    // both threads hold their first lock before asking for the second
+                    barrier.wait();
+                    second.lock().map(|_| ())
+                })
+            })
+            .collect();
+        let results: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        // exactly one of them is refused, which lets the other one go on
+        assert_eq!(results.iter().filter(|r| r.is_err()).count(), 1);
+        let cycle = &graph.cycles()[0];
+        let mut threads = cycle.threads();
+        threads.sort();
+        assert_eq!(threads, vec!["worker-0", "worker-1"]);
+    }
+
+    #[test]
+    fn consistent_orders_have_no_cycle() {
+        let (graph, data1, data2) = locks();
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let (data1, data2) = (Arc::clone(&data1), Arc::clone(&data2));
+                thread::spawn(move || {
+                    // always data1 first, whichever is modified first
+                    let mut lock1 = data1.lock().unwrap();
+                    let mut lock2 = data2.lock().unwrap();
+                    lock1[i % 3] += 1;
+                    lock2[i % 3] += 1;
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert!(graph.cycles().is_empty());
+        let edges = graph.edges();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(
+            (edges[0].from.as_str(), edges[0].to.as_str()),
+            ("data1", "data2")
+        );
+        assert_eq!(*data1.lock().unwrap(), vec![3, 3, 4]);
+    }
+
+    #[test]
+    fn longer_cycles_are_reported_in_full() {
+        let graph = Arc::new(LockOrderGraph::new());
+        let [a, b, c] = ["a", "b", "c"].map(|name| TrackedMutex::with_graph(name, (), &graph));
+        for (first, second) in [(&a, &b), (&b, &c)] {
+            let _first = first.lock().unwrap();
+            let _second = second.lock().unwrap();
+        }
+        let _c = c.lock().unwrap();
+        let Err(LockOrderError::Cycle(cycle)) = a.lock() else {
+            panic!("c -> a closes a -> b -> c");
+        };
+        assert_eq!(cycle.locks(), vec!["c", "a", "b"]);
+    }
+
+    #[test]
+    fn dropped_mutexes_leave_the_graph() {
+        let graph = Arc::new(LockOrderGraph::new());
+        let [a, b, c] = ["a", "b", "c"].map(|name| TrackedMutex::with_graph(name, (), &graph));
+        for (first, second) in [(&a, &b), (&b, &c), (&a, &c)] {
+            let _first = first.lock().unwrap();
+            let _second = second.lock().unwrap();
+        }
+        assert_eq!(graph.edges().len(), 3);
+
+        drop(b);
+        let edges: Vec<_> = graph
+            .edges()
+            .into_iter()
+            .map(|edge| (edge.from, edge.to))
+            .collect();
+        assert_eq!(edges, vec![("a".to_string(), "c".to_string())]);
+        assert_eq!(c.into_inner(), Ok(()));
+        assert!(graph.edges().is_empty());
+        assert_eq!(graph.lock_graph().names.len(), 1);
+    }
+}
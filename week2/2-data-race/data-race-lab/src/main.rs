@@ -1,140 +1,150 @@
-// Mutex that protects the data vector, and then we spawn three threads
-//that each acquire a lock on the mutex and modify an element of the vector.
-use std::sync::{Arc, Condvar, Mutex, RwLock};
+use clap::{Args, Parser, Subcommand};
+use data_race_lab::bench::{self, BenchConfig, Primitive};
+use data_race_lab::pipeline::{self, Pipeline};
+use data_race_lab::scenarios;
+use std::fs::File;
+use std::path::PathBuf;
+use std::process;
 use std::thread;
+use std::time::Duration;
 
-/// This function demonstrates the usage of `Mutex` and `Arc` in a multi-threaded environment.
-/// It creates a vector protected by a `Mutex`, then spawns multiple threads to modify the vector
-/// concurrently. The `Arc` (Atomic Reference Counted) is used to share ownership of the `Mutex`
-/// across multiple threads safely. Each thread locks the `Mutex`, modifies the vector, and then
-/// releases the lock. The main thread waits for all spawned threads to complete before printing
-/// the modified vector.
-fn main() {
-    default();
-    rwlock_replacement();
-    deadlock_handling();
-    conditional_synchronization();
+#[derive(Parser, Debug)]
+#[command(version, about = "Mutex, RwLock and Condvar experiments")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-fn default() {
-    // Use Arc to share ownership of the Mutex across threads
-    let data = Arc::new(Mutex::new(vec![1, 2, 3]));
-
-    // Create a vector to hold the handles of the spawned threads
-    let handles: Vec<_> = (0..3).map(|i| {
-        // Clone the Arc to increase the reference count
-        let data = Arc::clone(&data);
-        // Spawn a new thread
-        thread::spawn(move || {
-            // Lock the Mutex to get access to the data
-            let mut data = data.lock().unwrap();
-            // Modify the data at index i
-            data[i] += 1;
-        })
-    }).collect();
-
-    // Wait for all threads to complete
-    for handle in handles {
-        handle.join().unwrap();
-    }
-
-    // Print the modified vector
-    println!("{:?}", data.lock().unwrap());
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run the four scenarios (the default)
+    Scenarios,
+    /// Compare synchronisation primitives on shared counters
+    Bench(BenchArgs),
+    /// Run a demo pipeline of worker threads connected by bounded channels
+    Pipeline(PipelineArgs),
 }
 
+#[derive(Args, Debug)]
+struct BenchArgs {
+    /// Primitives to compare, all by default
+    #[arg(short, long, value_enum, value_delimiter = ',')]
+    primitive: Vec<Primitive>,
+    /// Thread counts to run with
+    #[arg(short, long, value_delimiter = ',', default_value = "1,2,4,8")]
+    threads: Vec<usize>,
+    /// Operations of every thread
+    #[arg(short, long, default_value_t = 100_000)]
+    operations: usize,
+    /// Share of reads, between 0 and 1
+    #[arg(short, long, default_value_t = 0.9, value_parser = parse_ratio)]
+    read_ratio: f64,
+    /// Number of counters
+    #[arg(short, long, default_value_t = 64)]
+    keys: usize,
+    /// Number of locks of the sharded counters
+    #[arg(long, default_value_t = 16)]
+    shards: usize,
+    /// Also write the results to this CSV file
+    #[arg(long)]
+    csv: Option<PathBuf>,
+}
 
-/// Challenge 1 - Use RwLock instead of Mutex
-fn rwlock_replacement() {
-    // Use Arc to share ownership of the RwLock across threads
-    // RwLock allows multiple threads to read the data concurrently, it's used
-    // for read-heavy workloads where the data is read more often than it's written.
-    let data = Arc::new(RwLock::new(vec![1, 2, 3]));
-
-    // Create a vector to hold the handles of the spawned threads
-    let handles: Vec<_> = (0..3).map(|i| {
-        // Clone the Arc to increase the reference count
-        let data = Arc::clone(&data);
-        // Spawn a new thread
-        thread::spawn(move || {
-            // Lock the RwLock for writing to get access to the data
-            let mut data = data.write().unwrap();
-            // Modify the data at index i
-            data[i] += 1;
-        })
-    }).collect();
+#[derive(Args, Debug)]
+struct PipelineArgs {
+    /// Numbers produced by the source
+    #[arg(short, long, default_value_t = 100_000)]
+    items: u64,
+    /// Workers of the squaring stage
+    #[arg(short, long, default_value_t = 4)]
+    workers: usize,
+    /// Items every channel can hold
+    #[arg(short, long, default_value_t = 16, value_parser = clap::value_parser!(u64).range(1..))]
+    capacity: u64,
+    /// Cancel the pipeline after this many milliseconds
+    #[arg(long)]
+    cancel_after: Option<u64>,
+}
 
-    // Wait for all threads to complete
-    for handle in handles {
-        handle.join().unwrap();
+fn parse_ratio(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(ratio) if (0.0..=1.0).contains(&ratio) => Ok(ratio),
+        Ok(ratio) => Err(format!("{} is not between 0 and 1", ratio)),
+        Err(e) => Err(e.to_string()),
     }
-
-    // Print the modified vector
-    println!("{:?}", data.read().unwrap());
 }
 
+/// Runs the scenarios, the benchmark or the pipeline chosen on the command line
+fn main() {
+    match Cli::parse().command {
+        None | Some(Command::Scenarios) => {
+            println!("{:?}", scenarios::mutex_increment(vec![1, 2, 3]));
+            // Challenge 1 - Use RwLock instead of Mutex
+            println!("{:?}", scenarios::rwlock_increment(vec![1, 2, 3]));
+            // Challenge 2 - Handle potential deadlocks with a consistent lock order
+            let (data1, data2) = scenarios::ordered_locking(vec![1, 2, 3], vec![4, 5, 6]);
+            println!("{:?}", data1);
+            println!("{:?}", data2);
+            // Challenge 4 - Synchronize the threads with a Condvar
+            println!("{:?}", scenarios::conditional_synchronization(vec![1, 2, 3]).data);
+        }
+        Some(Command::Bench(args)) => run_bench(args),
+        Some(Command::Pipeline(args)) => run_pipeline(args),
+    }
+}
 
-/// Challenge 2 - Handle potential deaklocks in a more complex way
-fn deadlock_handling() {
-    let data1 = Arc::new(Mutex::new(vec![1, 2, 3]));
-    let data2 = Arc::new(Mutex::new(vec![4, 5, 6]));
-
-    let handles: Vec<_> = (0..3).map(|i| {
-        let data1 = Arc::clone(&data1);
-        let data2 = Arc::clone(&data2);
-        thread::spawn(move || {
-            // Ensure consistent lock ordering
-            let (mut lock1, mut lock2) = if i % 2 == 0 {
-                (data1.lock().unwrap(), data2.lock().unwrap())
-            } else {
-                (data2.lock().unwrap(), data1.lock().unwrap())
-            };
-            // Modify the data
-            lock1[i] += 1;
-            lock2[i] += 1;
+fn run_bench(args: BenchArgs) {
+    let primitives = if args.primitive.is_empty() {
+        Primitive::ALL.to_vec()
+    } else {
+        args.primitive
+    };
+    let configs: Vec<BenchConfig> = args
+        .threads
+        .iter()
+        .map(|&threads| BenchConfig {
+            threads,
+            operations_per_thread: args.operations,
+            read_ratio: args.read_ratio,
+            keys: args.keys,
+            shards: args.shards,
         })
-    }).collect();
-
-    for handle in handles {
-        handle.join().unwrap();
+        .collect();
+    let results = bench::run_all(&primitives, &configs);
+    print!("{}", bench::to_table(&results));
+
+    if let Some(path) = args.csv {
+        let written = File::create(&path).and_then(|file| bench::write_csv(&results, file));
+        if let Err(e) = written {
+            eprintln!("Could not write {}: {}", path.display(), e);
+            process::exit(1);
+        }
     }
-
-    println!("{:?}", data1.lock().unwrap());
-    println!("{:?}", data2.lock().unwrap());
 }
 
+/// Squares the numbers up to `items` and sums the odd squares
+fn run_pipeline(args: PipelineArgs) {
+    let pipeline = Pipeline::source("numbers", args.capacity as usize, 1..=args.items)
+        .map("square", args.workers, |n| n * n)
+        .filter_map("odd", 2, |n| (n % 2 == 1).then_some(n));
 
-/// Challenge 4 - Extending the code to use conditional variables to synchronize thread execution
-fn conditional_synchronization() {
-    let data = Arc::new((Mutex::new(vec![1, 2, 3]), Condvar::new()));
-    let mut handles = vec![];
-
-    for i in 0..3 {
-        let data = Arc::clone(&data);
-        let handle = thread::spawn(move || {
-            let (lock, cvar) = &*data;
-            let mut data = lock.lock().unwrap();
-            while data[i] == 1 {
-                data = cvar.wait(data).unwrap();
-            }
-            data[i] += 1;
-            cvar.notify_one();
+    if let Some(millis) = args.cancel_after {
+        let cancel = pipeline.cancel_handle();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(millis));
+            cancel.cancel();
         });
-        handles.push(handle);
     }
 
-    {
-        let (lock, cvar) = &*data;
-        let mut data = lock.lock().unwrap();
-        for i in 0..3 {
-            data[i] += 4;
+    match pipeline.collect() {
+        Ok(completed) => {
+            let sum: u64 = completed.items.iter().fold(0, |sum, n| sum.wrapping_add(*n));
+            println!("{} odd squares, summing to {}", completed.items.len(), sum);
+            print!("{}", pipeline::to_table(&completed.stages));
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
         }
-        cvar.notify_all();
-    }
-
-    for handle in handles {
-        handle.join().unwrap();
     }
-
-    let (lock, _) = &*data;
-    println!("{:?}", lock.lock().unwrap());
-}
\ No newline at end of file
+}
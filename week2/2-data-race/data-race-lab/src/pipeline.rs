@@ -0,0 +1,721 @@
+//! Multi-stage pipelines of threads connected by bounded channels
+//!
+//! A [Pipeline] starts from a source iterator and goes through stages, each run by one or more
+//! worker threads. The workers of a stage share the channel they read from (fan-out) and all
+//! write into the channel of the next stage (fan-in), so items come out in no particular order.
+//!
+//! The channels of [bounded] hold at most `capacity` items, built on a `Mutex` and two
+//! `Condvar`s: a fast stage blocks on a full channel until the slower stage after it catches
+//! up (back-pressure), so a pipeline never holds more than `capacity` items per channel plus
+//! one item per worker. A [CancelHandle] closes every channel of the pipeline at once, which
+//! stops all the workers, and a worker that panics cancels its pipeline.
+//!
+//! ```rust
+//! use data_race_lab::pipeline::Pipeline;
+//!
+//! let completed = Pipeline::source("numbers", 4, 1..=100u64)
+//!     .map("square", 3, |n| n * n)
+//!     .filter_map("even", 2, |n| (n % 2 == 0).then_some(n))
+//!     .collect()
+//!     .unwrap();
+//! assert_eq!(completed.items.len(), 50);
+//! assert_eq!(completed.items.iter().sum::<u64>(), (1..=50).map(|n| 4 * n * n).sum());
+//! assert_eq!(completed.stages[1].received, 100);
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt::{self, Debug, Display};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+struct State<T> {
+    queue: VecDeque<T>,
+    senders: usize,
+    receivers: usize,
+    closed: bool,
+}
+
+struct Channel<T> {
+    capacity: usize,
+    state: Mutex<State<T>>,
+    /// Signalled when an item is queued, or there is nothing left to wait for
+    not_empty: Condvar,
+    /// Signalled when an item is taken, or there is nothing left to wait for
+    not_full: Condvar,
+}
+
+impl<T> Channel<T> {
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        // the state is consistent after every update, a panicking item type does not matter
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A channel that can be closed without knowing its item type
+trait Close: Send + Sync {
+    fn close(&self);
+}
+
+impl<T: Send> Close for Channel<T> {
+    fn close(&self) {
+        let mut state = self.lock();
+        state.closed = true;
+        state.queue.clear();
+        self.not_empty.notify_all();
+        self.not_full.notify_all();
+    }
+}
+
+/// The item could not be sent, because the channel is closed or has no receivers left
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+impl<T> Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Sending on a closed channel")
+    }
+}
+
+impl<T: Debug> std::error::Error for SendError<T> {}
+
+/// Sending half of a [bounded] channel, clone it for more producers
+pub struct Sender<T> {
+    channel: Arc<Channel<T>>,
+}
+
+/// Receiving half of a [bounded] channel, clone it for more consumers
+pub struct Receiver<T> {
+    channel: Arc<Channel<T>>,
+}
+
+/// A channel holding at most `capacity` items, for any number of producers and consumers
+///
+/// # Panics
+///
+/// If `capacity` is 0.
+///
+/// ```rust
+/// use data_race_lab::pipeline::bounded;
+/// use std::thread;
+///
+/// let (sender, receiver) = bounded(2);
+/// let producer = thread::spawn(move || {
+///     for n in 0..10 {
+///         // blocks while 2 items are waiting
+///         sender.send(n).unwrap();
+///     }
+/// });
+/// let received: Vec<i32> = std::iter::from_fn(|| receiver.recv()).collect();
+/// producer.join().unwrap();
+/// assert_eq!(received, (0..10).collect::<Vec<_>>());
+/// ```
+pub fn bounded<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "A bounded channel needs room for one item");
+    let channel = Arc::new(Channel {
+        capacity,
+        state: Mutex::new(State {
+            queue: VecDeque::with_capacity(capacity),
+            senders: 1,
+            receivers: 1,
+            closed: false,
+        }),
+        not_empty: Condvar::new(),
+        not_full: Condvar::new(),
+    });
+    (
+        Sender {
+            channel: Arc::clone(&channel),
+        },
+        Receiver { channel },
+    )
+}
+
+impl<T> Sender<T> {
+    /// Queues `item`, waiting for room while the channel is full
+    pub fn send(&self, item: T) -> Result<(), SendError<T>> {
+        let mut state = self.channel.lock();
+        loop {
+            if state.closed || state.receivers == 0 {
+                return Err(SendError(item));
+            }
+            if state.queue.len() < self.channel.capacity {
+                state.queue.push_back(item);
+                self.channel.not_empty.notify_one();
+                return Ok(());
+            }
+            state = self
+                .channel
+                .not_full
+                .wait(state)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    /// Closes the channel: pending items are dropped, every `send` and `recv` fails from now on
+    pub fn close(&self)
+    where
+        T: Send,
+    {
+        self.channel.close();
+    }
+}
+
+impl<T> Receiver<T> {
+    /// The next item, waiting while the channel is empty
+    ///
+    /// `None` once the channel is closed, or empty with every sender dropped.
+    pub fn recv(&self) -> Option<T> {
+        let mut state = self.channel.lock();
+        loop {
+            if state.closed {
+                return None;
+            }
+            if let Some(item) = state.queue.pop_front() {
+                self.channel.not_full.notify_one();
+                return Some(item);
+            }
+            if state.senders == 0 {
+                return None;
+            }
+            state = self
+                .channel
+                .not_empty
+                .wait(state)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    /// Items waiting in the channel
+    pub fn len(&self) -> usize {
+        self.channel.lock().queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.channel.capacity
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.channel.lock().senders += 1;
+        Self {
+            channel: Arc::clone(&self.channel),
+        }
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.channel.lock().receivers += 1;
+        Self {
+            channel: Arc::clone(&self.channel),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.channel.lock();
+        state.senders -= 1;
+        if state.senders == 0 {
+            // the receivers waiting for an item will not get one
+            self.channel.not_empty.notify_all();
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.channel.lock();
+        state.receivers -= 1;
+        if state.receivers == 0 {
+            // the senders waiting for room will not get it
+            self.channel.not_full.notify_all();
+        }
+    }
+}
+
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("capacity", &self.channel.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("capacity", &self.channel.capacity)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+#[derive(Default)]
+struct Cancellation {
+    cancelled: AtomicBool,
+    channels: Mutex<Vec<Arc<dyn Close>>>,
+}
+
+/// Cancels a [Pipeline] from any thread
+#[derive(Clone, Default)]
+pub struct CancelHandle {
+    inner: Arc<Cancellation>,
+}
+
+impl CancelHandle {
+    /// Closes every channel of the pipeline: the workers stop after their current item
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        for channel in self.channels().iter() {
+            channel.close();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    fn channels(&self) -> MutexGuard<'_, Vec<Arc<dyn Close>>> {
+        self.inner
+            .channels
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn register<T: Send + 'static>(&self, sender: &Sender<T>) {
+        let channel: Arc<dyn Close> = sender.channel.clone();
+        // registered before checking the flag: either this sees it, or cancel sees the channel
+        self.channels().push(Arc::clone(&channel));
+        if self.is_cancelled() {
+            channel.close();
+        }
+    }
+}
+
+impl Debug for CancelHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancelHandle")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+/// Cancels the pipeline if the worker holding it panics
+struct CancelOnPanic(CancelHandle);
+
+impl Drop for CancelOnPanic {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            self.0.cancel();
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct StageMetrics {
+    name: String,
+    workers: usize,
+    received: AtomicU64,
+    sent: AtomicU64,
+    busy: AtomicU64,
+    waiting_for_input: AtomicU64,
+    blocked_on_output: AtomicU64,
+}
+
+fn add_time(total: &AtomicU64, since: Instant) -> Instant {
+    let now = Instant::now();
+    let nanos = now.duration_since(since).as_nanos();
+    total.fetch_add(nanos.try_into().unwrap_or(u64::MAX), Ordering::Relaxed);
+    now
+}
+
+impl StageMetrics {
+    fn report(&self) -> StageReport {
+        let load = |value: &AtomicU64| value.load(Ordering::Relaxed);
+        StageReport {
+            name: self.name.clone(),
+            workers: self.workers,
+            received: load(&self.received),
+            sent: load(&self.sent),
+            busy: Duration::from_nanos(load(&self.busy)),
+            waiting_for_input: Duration::from_nanos(load(&self.waiting_for_input)),
+            blocked_on_output: Duration::from_nanos(load(&self.blocked_on_output)),
+        }
+    }
+
+    /// Produces the items of `source` into `output`
+    fn produce<T>(&self, source: impl Iterator<Item = T>, output: Sender<T>) {
+        let mut start = Instant::now();
+        for item in source {
+            start = add_time(&self.busy, start);
+            if output.send(item).is_err() {
+                return;
+            }
+            self.sent.fetch_add(1, Ordering::Relaxed);
+            start = add_time(&self.blocked_on_output, start);
+        }
+        add_time(&self.busy, start);
+    }
+
+    /// The loop of a worker: `f` on every item of `input`, the results into `output`
+    fn work<T, U>(&self, input: Receiver<T>, output: Sender<U>, f: impl Fn(T) -> Option<U>) {
+        let mut start = Instant::now();
+        while let Some(item) = input.recv() {
+            self.received.fetch_add(1, Ordering::Relaxed);
+            start = add_time(&self.waiting_for_input, start);
+            let result = f(item);
+            start = add_time(&self.busy, start);
+            if let Some(result) = result {
+                if output.send(result).is_err() {
+                    return;
+                }
+                self.sent.fetch_add(1, Ordering::Relaxed);
+                start = add_time(&self.blocked_on_output, start);
+            }
+        }
+        add_time(&self.waiting_for_input, start);
+    }
+}
+
+/// What a stage has done so far, all workers together
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageReport {
+    pub name: String,
+    pub workers: usize,
+    /// Items taken from the previous stage, 0 for the source
+    pub received: u64,
+    /// Items passed to the next stage
+    pub sent: u64,
+    /// Time spent producing or transforming items
+    pub busy: Duration,
+    /// Time spent waiting for the previous stage
+    pub waiting_for_input: Duration,
+    /// Time spent waiting for room in the next channel, the back-pressure
+    pub blocked_on_output: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PipelineError {
+    /// The pipeline was cancelled before the source was exhausted
+    Cancelled,
+    /// A worker of this stage panicked
+    Panicked(String),
+}
+
+impl Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PipelineError::Cancelled => write!(f, "The pipeline was cancelled"),
+            PipelineError::Panicked(stage) => write!(f, "A worker of stage {} panicked", stage),
+        }
+    }
+}
+
+impl std::error::Error for PipelineError {}
+
+/// The output of a [Pipeline] that ran to the end
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completed<T> {
+    /// The items out of the last stage, in no particular order
+    pub items: Vec<T>,
+    /// The stages from the source
+    pub stages: Vec<StageReport>,
+}
+
+/// Stages running on their own threads, the last one producing `T`
+///
+/// See the [module](self) documentation.
+#[derive(Debug)]
+pub struct Pipeline<T> {
+    output: Receiver<T>,
+    capacity: usize,
+    workers: Vec<(String, JoinHandle<()>)>,
+    stages: Vec<Arc<StageMetrics>>,
+    cancel: CancelHandle,
+}
+
+impl<T: Send + 'static> Pipeline<T> {
+    /// A pipeline whose channels hold `capacity` items, starting from a thread that produces
+    /// `items`
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is 0.
+    pub fn source<I>(name: &str, capacity: usize, items: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: Send + 'static,
+    {
+        let cancel = CancelHandle::default();
+        let (sender, receiver) = bounded(capacity);
+        cancel.register(&sender);
+        let metrics = Arc::new(StageMetrics {
+            name: name.to_string(),
+            workers: 1,
+            ..StageMetrics::default()
+        });
+        let items = items.into_iter();
+        let worker = {
+            let metrics = Arc::clone(&metrics);
+            let guard = CancelOnPanic(cancel.clone());
+            spawn_worker(name, 0, move || {
+                let _guard = guard;
+                metrics.produce(items, sender);
+            })
+        };
+        Self {
+            output: receiver,
+            capacity,
+            workers: vec![(name.to_string(), worker)],
+            stages: vec![metrics],
+            cancel,
+        }
+    }
+
+    /// A stage of `workers` threads applying `f` to every item
+    pub fn map<U, F>(self, name: &str, workers: usize, f: F) -> Pipeline<U>
+    where
+        U: Send + 'static,
+        F: Fn(T) -> U + Send + Sync + 'static,
+    {
+        self.filter_map(name, workers, move |item| Some(f(item)))
+    }
+
+    /// A stage of `workers` threads applying `f` to every item, keeping only the `Some`s
+    pub fn filter_map<U, F>(self, name: &str, workers: usize, f: F) -> Pipeline<U>
+    where
+        U: Send + 'static,
+        F: Fn(T) -> Option<U> + Send + Sync + 'static,
+    {
+        let Pipeline {
+            output: input,
+            capacity,
+            workers: mut threads,
+            mut stages,
+            cancel,
+        } = self;
+        let (sender, receiver) = bounded(capacity);
+        cancel.register(&sender);
+        let workers = workers.max(1);
+        let metrics = Arc::new(StageMetrics {
+            name: name.to_string(),
+            workers,
+            ..StageMetrics::default()
+        });
+        let f = Arc::new(f);
+        for worker in 0..workers {
+            let (input, output) = (input.clone(), sender.clone());
+            let (metrics, f) = (Arc::clone(&metrics), Arc::clone(&f));
+            let guard = CancelOnPanic(cancel.clone());
+            let handle = spawn_worker(name, worker, move || {
+                let _guard = guard;
+                metrics.work(input, output, |item| f(item));
+            });
+            threads.push((name.to_string(), handle));
+        }
+        // only the workers hold the channels now, so that they end with the workers
+        drop((input, sender));
+        stages.push(metrics);
+        Pipeline {
+            output: receiver,
+            capacity,
+            workers: threads,
+            stages,
+            cancel,
+        }
+    }
+
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone()
+    }
+
+    /// What every stage has done so far, from the source
+    pub fn stages(&self) -> Vec<StageReport> {
+        self.stages.iter().map(|stage| stage.report()).collect()
+    }
+
+    /// Collects the items out of the last stage, once every worker is done
+    pub fn collect(self) -> Result<Completed<T>, PipelineError> {
+        let items: Vec<T> = std::iter::from_fn(|| self.output.recv()).collect();
+        let mut panicked = None;
+        for (stage, worker) in self.workers {
+            if worker.join().is_err() && panicked.is_none() {
+                panicked = Some(stage);
+            }
+        }
+        if let Some(stage) = panicked {
+            return Err(PipelineError::Panicked(stage));
+        }
+        if self.cancel.is_cancelled() {
+            return Err(PipelineError::Cancelled);
+        }
+        let stages = self.stages.iter().map(|stage| stage.report()).collect();
+        Ok(Completed { items, stages })
+    }
+}
+
+fn spawn_worker(stage: &str, worker: usize, f: impl FnOnce() + Send + 'static) -> JoinHandle<()> {
+    thread::Builder::new()
+        .name(format!("{}-{}", stage, worker))
+        .spawn(f)
+        .expect("Could not spawn a pipeline worker")
+}
+
+/// The reports as a Markdown table
+pub fn to_table(stages: &[StageReport]) -> String {
+    let mut table = String::from(
+        "| Stage | Workers | Received | Sent | Busy | Waiting for input | Blocked on output |\n",
+    );
+    table.push_str("|---|---:|---:|---:|---:|---:|---:|\n");
+    for s in stages {
+        table.push_str(&format!(
+            "| {} | {} | {} | {} | {:.1?} | {:.1?} | {:.1?} |\n",
+            s.name, s.workers, s.received, s.sent, s.busy, s.waiting_for_input, s.blocked_on_output,
+        ));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn many_producers_and_consumers_lose_and_duplicate_nothing() {
+        let (sender, receiver) = bounded(3);
+        let producers: Vec<_> = (0..4)
+            .map(|p| {
+                let sender = sender.clone();
+                thread::spawn(move || {
+                    for n in 0..2_000 {
+                        sender.send(p * 10_000 + n).unwrap();
+                    }
+                })
+            })
+            .collect();
+        let consumers: Vec<_> = (0..4)
+            .map(|_| {
+                let receiver = receiver.clone();
+                thread::spawn(move || std::iter::from_fn(|| receiver.recv()).collect::<Vec<_>>())
+            })
+            .collect();
+        drop((sender, receiver));
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        let mut received: Vec<i32> = consumers
+            .into_iter()
+            .flat_map(|consumer| consumer.join().unwrap())
+            .collect();
+        received.sort();
+        let expected: Vec<i32> = (0..4)
+            .flat_map(|p| (0..2_000).map(move |n| p * 10_000 + n))
+            .collect();
+        assert_eq!(received, expected);
+    }
+
+    #[test]
+    fn a_full_channel_blocks_the_producer() {
+        let capacity = 2;
+        let (sender, receiver) = bounded(capacity);
+        let sent = Arc::new(AtomicUsize::new(0));
+        let producer = {
+            let sent = Arc::clone(&sent);
+            thread::spawn(move || {
+                for n in 0..50 {
+                    sender.send(n).unwrap();
+                    sent.fetch_add(1, Ordering::SeqCst);
+                }
+            })
+        };
+        for received in 1..=50 {
+            thread::sleep(Duration::from_micros(200));
+            receiver.recv().unwrap();
+            // the producer can only be `capacity` items ahead, and one more on its way
+            assert!(sent.load(Ordering::SeqCst) <= received + capacity);
+        }
+        producer.join().unwrap();
+        assert_eq!(receiver.recv(), None);
+    }
+
+    #[test]
+    fn dropping_the_receivers_stops_the_senders() {
+        let (sender, receiver) = bounded(1);
+        sender.send(1).unwrap();
+        let blocked = thread::spawn(move || sender.send(2));
+        thread::sleep(Duration::from_millis(10));
+        drop(receiver);
+        assert_eq!(blocked.join().unwrap(), Err(SendError(2)));
+    }
+
+    #[test]
+    fn stress_pipeline_loses_and_duplicates_nothing() {
+        for capacity in [1, 2, 16] {
+            let completed = Pipeline::source("numbers", capacity, 0..20_000u64)
+                .map("tag", 4, |n| (n, n * 3))
+                .filter_map("not seven", 3, |(n, tripled)| {
+                    (n % 7 != 0).then_some((n, tripled + 1))
+                })
+                .map("check", 5, |(n, value)| {
+                    assert_eq!(value, n * 3 + 1);
+                    n
+                })
+                .collect()
+                .unwrap();
+
+            let unique: HashSet<u64> = completed.items.iter().copied().collect();
+            assert_eq!(unique.len(), completed.items.len(), "duplicated items");
+            let expected: HashSet<u64> = (0..20_000).filter(|n| n % 7 != 0).collect();
+            assert_eq!(unique, expected, "lost items");
+
+            let counts: Vec<(u64, u64)> = completed
+                .stages
+                .iter()
+                .map(|stage| (stage.received, stage.sent))
+                .collect();
+            let kept = expected.len() as u64;
+            assert_eq!(
+                counts,
+                vec![(0, 20_000), (20_000, 20_000), (20_000, kept), (kept, kept)]
+            );
+        }
+    }
+
+    #[test]
+    fn cancelling_stops_an_endless_pipeline() {
+        let pipeline = Pipeline::source("endless", 4, 0u64..)
+            .map("slow", 2, |n| {
+                thread::sleep(Duration::from_micros(100));
+                n
+            })
+            .map("identity", 2, |n| n);
+        let cancel = pipeline.cancel_handle();
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            cancel.cancel();
+        });
+        assert_eq!(pipeline.collect(), Err(PipelineError::Cancelled));
+        canceller.join().unwrap();
+    }
+
+    #[test]
+    fn a_panicking_worker_cancels_the_pipeline() {
+        let result = Pipeline::source("numbers", 2, 0u64..)
+            .map("fragile", 3, |n| {
+                assert!(n < 1_000, "too large");
+                n
+            })
+            .collect();
+        assert_eq!(result, Err(PipelineError::Panicked("fragile".to_string())));
+    }
+}
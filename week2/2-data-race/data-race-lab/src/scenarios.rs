@@ -0,0 +1,330 @@
+//! The four scenarios of the lesson, as functions returning what they computed
+//!
+//! Every scenario spawns one thread per item of `data`. The functions use the `loom` versions
+//! of `Arc`, `Mutex`, `RwLock`, `Condvar` and `thread` when compiled with `--cfg loom`, so that
+//! the tests can run them under the [loom](https://docs.rs/loom) model checker, which tries
+//! every interleaving of the threads instead of the few a normal run happens to hit:
+//!
+//! ```bash
+//! RUSTFLAGS="--cfg loom" cargo test --release --lib scenarios
+//! ```
+
+#[cfg(loom)]
+use loom::{
+    sync::{Arc, Condvar, Mutex, RwLock},
+    thread,
+};
+#[cfg(not(loom))]
+use std::{
+    sync::{Arc, Condvar, Mutex, RwLock},
+    thread,
+};
+
+/// What the threads of [conditional_synchronization] did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Handoff {
+    /// The data at the end
+    pub data: Vec<i32>,
+    /// The item every thread found before incrementing it
+    pub seen: Vec<i32>,
+}
+
+/// Every thread locks the `Mutex` and increments its own item
+///
+/// The `Arc` (Atomic Reference Counted) shares the ownership of the `Mutex` across the
+/// threads. Each thread locks the `Mutex`, modifies the vector and releases the lock when the
+/// guard is dropped, and the data is returned once every thread has completed.
+///
+/// ```rust
+/// assert_eq!(data_race_lab::scenarios::mutex_increment(vec![1, 2, 3]), vec![2, 3, 4]);
+/// ```
+pub fn mutex_increment(data: Vec<i32>) -> Vec<i32> {
+    let workers = data.len();
+    // Use Arc to share ownership of the Mutex across threads
+    let data = Arc::new(Mutex::new(data));
+
+    let handles: Vec<_> = (0..workers)
+        .map(|i| {
+            // Clone the Arc to increase the reference count
+            let data = Arc::clone(&data);
+            thread::spawn(move || {
+                // Lock the Mutex to get access to the data
+                let mut data = data.lock().unwrap();
+                data[i] += 1;
+            })
+        })
+        .collect();
+
+    // Wait for all threads to complete
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let data = data.lock().unwrap().clone();
+    data
+}
+
+/// Challenge 1 - [mutex_increment] with a `RwLock` instead of a `Mutex`
+///
+/// RwLock allows multiple threads to read the data concurrently, it's used for read-heavy
+/// workloads where the data is read more often than it's written. Here every thread writes.
+pub fn rwlock_increment(data: Vec<i32>) -> Vec<i32> {
+    let workers = data.len();
+    let data = Arc::new(RwLock::new(data));
+
+    let handles: Vec<_> = (0..workers)
+        .map(|i| {
+            let data = Arc::clone(&data);
+            thread::spawn(move || {
+                // Lock the RwLock for writing to get access to the data
+                let mut data = data.write().unwrap();
+                data[i] += 1;
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let data = data.read().unwrap().clone();
+    data
+}
+
+/// Challenge 2 - Every thread increments its item in both vectors, holding both locks
+///
+/// The threads take the locks in the same order, `data1` before `data2`: in opposite orders,
+/// two threads could each hold one lock and wait forever for the other.
+///
+/// ```rust
+/// let (data1, data2) = data_race_lab::scenarios::ordered_locking(vec![1, 2], vec![4, 5]);
+/// assert_eq!((data1, data2), (vec![2, 3], vec![5, 6]));
+/// ```
+pub fn ordered_locking(data1: Vec<i32>, data2: Vec<i32>) -> (Vec<i32>, Vec<i32>) {
+    let workers = data1.len().min(data2.len());
+    let data1 = Arc::new(Mutex::new(data1));
+    let data2 = Arc::new(Mutex::new(data2));
+
+    let handles: Vec<_> = (0..workers)
+        .map(|i| {
+            let data1 = Arc::clone(&data1);
+            let data2 = Arc::clone(&data2);
+            thread::spawn(move || {
+                let mut lock1 = data1.lock().unwrap();
+                let mut lock2 = data2.lock().unwrap();
+                lock1[i] += 1;
+                lock2[i] += 1;
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let data1 = data1.lock().unwrap().clone();
+    let data2 = data2.lock().unwrap().clone();
+    (data1, data2)
+}
+
+/// Challenge 4 - The threads wait on a `Condvar` until the main thread has added 4 to every
+/// item, then increment their own item
+///
+/// The threads wait for a `ready` flag set under the same lock as the data. Waiting for the
+/// data itself to change, as the first version of this scenario did with `data[i] == 1`, only
+/// works for items that start at 1: the other threads went on without waiting.
+///
+/// ```rust
+/// use data_race_lab::scenarios::{conditional_synchronization, Handoff};
+///
+/// assert_eq!(
+///     conditional_synchronization(vec![1, 2, 3]),
+///     Handoff { data: vec![6, 7, 8], seen: vec![5, 6, 7] }
+/// );
+/// ```
+pub fn conditional_synchronization(data: Vec<i32>) -> Handoff {
+    let workers = data.len();
+    let state = Arc::new((Mutex::new((data, false)), Condvar::new()));
+
+    let handles: Vec<_> = (0..workers)
+        .map(|i| {
+            let state = Arc::clone(&state);
+            thread::spawn(move || {
+                let (lock, cvar) = &*state;
+                let mut guard = lock.lock().unwrap();
+                // check the flag before every wait: a notification sent before this thread
+                // waits is lost, and a wait can end without any notification
+                while !guard.1 {
+                    guard = cvar.wait(guard).unwrap();
+                }
+                let (data, _) = &mut *guard;
+                let seen = data[i];
+                data[i] += 1;
+                seen
+            })
+        })
+        .collect();
+
+    {
+        let (lock, cvar) = &*state;
+        let mut guard = lock.lock().unwrap();
+        let (data, ready) = &mut *guard;
+        for item in data.iter_mut() {
+            *item += 4;
+        }
+        *ready = true;
+        cvar.notify_all();
+    }
+
+    let seen = handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect();
+    let (lock, _) = &*state;
+    let data = lock.lock().unwrap().0.clone();
+    Handoff { data, seen }
+}
+
+#[cfg(all(test, not(loom)))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scenarios_compute_the_same_on_every_run() {
+        for _ in 0..50 {
+            assert_eq!(mutex_increment(vec![1, 2, 3]), vec![2, 3, 4]);
+            assert_eq!(rwlock_increment(vec![1, 2, 3]), vec![2, 3, 4]);
+            assert_eq!(
+                ordered_locking(vec![1, 2, 3], vec![4, 5, 6]),
+                (vec![2, 3, 4], vec![5, 6, 7])
+            );
+            assert_eq!(
+                conditional_synchronization(vec![1, 2, 3]),
+                Handoff {
+                    data: vec![6, 7, 8],
+                    seen: vec![5, 6, 7]
+                }
+            );
+        }
+    }
+}
+
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use super::*;
+    use loom::sync::atomic::{AtomicBool, Ordering};
+    use std::panic::{self, AssertUnwindSafe};
+
+    /// State for the threads of a model that deadlocks
+    ///
+    /// When loom finds a deadlock it stops the model and drops the stacks of the threads, and
+    /// dropping a loom `Arc` at that point aborts the process: leaked state is never dropped.
+    fn shared<T: Sync>(state: T) -> &'static T {
+        Box::leak(Box::new(state))
+    }
+
+    /// Whether loom finds an interleaving where `model` panics or deadlocks
+    fn fails_in_some_interleaving<F: Fn() + Sync + Send + 'static>(model: F) -> bool {
+        panic::catch_unwind(AssertUnwindSafe(|| loom::model(model))).is_err()
+    }
+
+    #[test]
+    fn mutex_and_rwlock_increments_are_never_lost() {
+        loom::model(|| assert_eq!(mutex_increment(vec![1, 2]), vec![2, 3]));
+        loom::model(|| assert_eq!(rwlock_increment(vec![1, 2]), vec![2, 3]));
+    }
+
+    #[test]
+    fn ordered_locking_never_deadlocks() {
+        loom::model(|| {
+            assert_eq!(
+                ordered_locking(vec![1, 2], vec![4, 5]),
+                (vec![2, 3], vec![5, 6])
+            )
+        });
+    }
+
+    #[test]
+    fn opposite_lock_orders_deadlock() {
+        // the first version of challenge 2
+        assert!(fails_in_some_interleaving(|| {
+            let (data1, data2) = shared((Mutex::new(vec![1, 2]), Mutex::new(vec![4, 5])));
+            let handles: Vec<_> = (0..2)
+                .map(|i: usize| {
+                    thread::spawn(move || {
+                        let (mut lock1, mut lock2) = if i.is_multiple_of(2) {
+                            (data1.lock().unwrap(), data2.lock().unwrap())
+                        } else {
+                            (data2.lock().unwrap(), data1.lock().unwrap())
+                        };
+                        lock1[i] += 1;
+                        lock2[i] += 1;
+                    })
+                })
+                .collect();
+            for handle in handles {
+                handle.join().unwrap();
+            }
+        }));
+    }
+
+    #[test]
+    fn conditional_synchronization_always_waits_for_the_data() {
+        loom::model(|| {
+            assert_eq!(
+                conditional_synchronization(vec![1, 2]),
+                Handoff {
+                    data: vec![6, 7],
+                    seen: vec![5, 6]
+                }
+            )
+        });
+    }
+
+    #[test]
+    fn waiting_for_the_data_to_change_can_run_too_early() {
+        // the first version of challenge 4: the thread of the item 2 never waits
+        assert!(fails_in_some_interleaving(|| {
+            let state = Arc::new((Mutex::new(vec![1, 2]), Condvar::new()));
+            let worker = {
+                let state = Arc::clone(&state);
+                thread::spawn(move || {
+                    let (lock, cvar) = &*state;
+                    let mut data = lock.lock().unwrap();
+                    while data[1] == 1 {
+                        data = cvar.wait(data).unwrap();
+                    }
+                    assert_eq!(data[1], 6, "the worker ran before the data was ready");
+                })
+            };
+            {
+                let (lock, cvar) = &*state;
+                let mut data = lock.lock().unwrap();
+                for item in data.iter_mut() {
+                    *item += 4;
+                }
+                cvar.notify_all();
+            }
+            worker.join().unwrap();
+        }));
+    }
+
+    #[test]
+    fn checking_the_flag_outside_the_lock_loses_the_notification() {
+        assert!(fails_in_some_interleaving(|| {
+            let state = shared((Mutex::new(()), Condvar::new(), AtomicBool::new(false)));
+            let worker = thread::spawn(move || {
+                let (lock, cvar, ready) = state;
+                if !ready.load(Ordering::SeqCst) {
+                    // the main thread can notify right here, before the wait
+                    let guard = lock.lock().unwrap();
+                    drop(cvar.wait(guard).unwrap());
+                }
+            });
+            let (_, cvar, ready) = state;
+            ready.store(true, Ordering::SeqCst);
+            cvar.notify_all();
+            worker.join().unwrap();
+        }));
+    }
+}
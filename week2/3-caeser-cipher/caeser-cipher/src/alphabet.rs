@@ -0,0 +1,200 @@
+//! Alphabets for the Caesar cipher
+//!
+//! An [Alphabet] is an ordered set of characters: shifting moves every character of the text
+//! found in the alphabet by the same number of places, wrapping around at the end, and copies
+//! the other characters. Any Unicode characters can be used, so that accented letters or
+//! digits are shifted as well.
+//!
+//! An alphabet of lower case letters also shifts their upper case, keeping the case, as long
+//! as every character has a single upper case character that is not itself in the alphabet.
+//! Otherwise the characters are only matched exactly.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+/// The 26 letters of the English alphabet
+pub const LATIN: &str = "abcdefghijklmnopqrstuvwxyz";
+
+/// The English alphabet followed by the accented letters of Italian, French, German and
+/// Spanish
+pub const ACCENTED_LATIN: &str = "abcdefghijklmnopqrstuvwxyzàáâäçèéêëìíîïñòóôöùúûü";
+
+pub const DIGITS: &str = "0123456789";
+
+/// Lower and upper case letters and digits, matched exactly
+pub const ALPHANUMERIC: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlphabetError {
+    Empty,
+    /// The character appears twice
+    Duplicate(char),
+}
+
+impl Display for AlphabetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlphabetError::Empty => write!(f, "The alphabet has no characters"),
+            AlphabetError::Duplicate(c) => write!(f, "'{}' appears twice in the alphabet", c),
+        }
+    }
+}
+
+impl std::error::Error for AlphabetError {}
+
+/// Characters in the order they are shifted through
+///
+/// ```rust
+/// use caeser_cipher::alphabet::{Alphabet, ACCENTED_LATIN};
+///
+/// let accented = Alphabet::new(ACCENTED_LATIN).unwrap();
+/// assert_eq!(accented.shift("Perché zà?", 3), "Shufkì âä?");
+/// assert_eq!(accented.shift("Shufkì âä?", -3), "Perché zà?");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alphabet {
+    chars: Vec<char>,
+    /// The upper case of `chars`, if it can be shifted as well
+    upper: Option<Vec<char>>,
+    /// Position of every character, and whether it is in upper case
+    positions: HashMap<char, (usize, bool)>,
+}
+
+impl Alphabet {
+    pub fn new(chars: &str) -> Result<Self, AlphabetError> {
+        let chars: Vec<char> = chars.chars().collect();
+        if chars.is_empty() {
+            return Err(AlphabetError::Empty);
+        }
+        let mut positions = HashMap::with_capacity(chars.len() * 2);
+        for (position, &c) in chars.iter().enumerate() {
+            if positions.insert(c, (position, false)).is_some() {
+                return Err(AlphabetError::Duplicate(c));
+            }
+        }
+        let upper: Option<Vec<char>> = chars
+            .iter()
+            .map(|&c| single_upper_case(c).filter(|upper| !positions.contains_key(upper)))
+            .collect();
+        if let Some(upper) = &upper {
+            for (position, &c) in upper.iter().enumerate() {
+                positions.insert(c, (position, true));
+            }
+        }
+        Ok(Self {
+            chars,
+            upper,
+            positions,
+        })
+    }
+
+    /// The English alphabet, in both cases
+    pub fn latin() -> Self {
+        Self::new(LATIN).expect("The latin alphabet is valid")
+    }
+
+    pub fn len(&self) -> usize {
+        self.chars.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        self.positions.contains_key(&c)
+    }
+
+    /// Whether upper case characters are shifted as their lower case
+    pub fn folds_case(&self) -> bool {
+        self.upper.is_some()
+    }
+
+    /// `shift` modulo the length of the alphabet, between 0 and `len() - 1`
+    pub fn normalise(&self, shift: i64) -> usize {
+        // an alphabet cannot have more than i64::MAX characters
+        shift.rem_euclid(self.len() as i64) as usize
+    }
+
+    /// Moves every character of the alphabet in `text` by `shift` places, forward for a
+    /// positive shift
+    pub fn shift(&self, text: &str, shift: i64) -> String {
+        self.shift_by(text, self.normalise(shift))
+    }
+
+    /// [Alphabet::shift] with a shift already [normalised](Alphabet::normalise)
+    pub(crate) fn shift_by(&self, text: &str, shift: usize) -> String {
+        text.chars()
+            .map(|c| match self.positions.get(&c) {
+                Some(&(position, is_upper)) => {
+                    let shifted = (position + shift) % self.len();
+                    match &self.upper {
+                        Some(upper) if is_upper => upper[shifted],
+                        _ => self.chars[shifted],
+                    }
+                }
+                None => c,
+            })
+            .collect()
+    }
+}
+
+impl Display for Alphabet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let chars: String = self.chars.iter().collect();
+        f.pad(&chars)
+    }
+}
+
+/// The upper case of a lower case character, if it is a single different character whose
+/// lower case is `c` again
+fn single_upper_case(c: char) -> Option<char> {
+    let mut upper = c.to_uppercase();
+    let first = upper.next().filter(|_| upper.next().is_none())?;
+    let mut lower = first.to_lowercase();
+    let round_trip = lower.next() == Some(c) && lower.next().is_none();
+    (first != c && round_trip).then_some(first)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn presets_fold_case_only_when_possible() {
+        assert!(Alphabet::latin().folds_case());
+        assert!(Alphabet::new(ACCENTED_LATIN).unwrap().folds_case());
+        assert!(!Alphabet::new(DIGITS).unwrap().folds_case());
+        assert!(!Alphabet::new(ALPHANUMERIC).unwrap().folds_case());
+        // ß has no single character upper case
+        assert!(!Alphabet::new("aß").unwrap().folds_case());
+
+        let digits = Alphabet::new(DIGITS).unwrap();
+        assert_eq!(digits.shift("Call 555-0199", 5), "Call 000-5644");
+        let alphanumeric = Alphabet::new(ALPHANUMERIC).unwrap();
+        assert_eq!(alphanumeric.shift("zZ9", 1), "A0a");
+    }
+
+    #[test]
+    fn invalid_alphabets_are_errors() {
+        assert_eq!(Alphabet::new(""), Err(AlphabetError::Empty));
+        assert_eq!(Alphabet::new("abca"), Err(AlphabetError::Duplicate('a')));
+    }
+
+    proptest! {
+        #[test]
+        fn any_alphabet_and_shift_round_trip(
+            chars in prop::collection::btree_set(any::<char>(), 1..40),
+            text in any::<String>(),
+            shift in any::<i64>(),
+        ) {
+            let chars: String = chars.into_iter().collect();
+            let alphabet = Alphabet::new(&chars).unwrap();
+            let shifted = alphabet.shift(&text, shift);
+            prop_assert_eq!(shifted.chars().count(), text.chars().count());
+            let back = alphabet.len() - alphabet.normalise(shift);
+            prop_assert_eq!(alphabet.shift(&shifted, back as i64), text);
+        }
+    }
+}
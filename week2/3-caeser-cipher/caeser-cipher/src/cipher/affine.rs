@@ -0,0 +1,57 @@
+use super::{substitute_letters, Cipher, Direction, KeyError, PerChunk, Stream, LETTERS};
+
+/// Letter x becomes `a * x + b`, modulo 26
+///
+/// `a` must be coprime with 26 for the letters to stay distinct, decrypting multiplies by
+/// its inverse.
+///
+/// ```rust
+/// use caeser_cipher::cipher::{Affine, Cipher};
+///
+/// let affine = Affine::new(5, 8).unwrap();
+/// assert_eq!(affine.encrypt("AFFINE CIPHER"), "IHHWVC SWFRCP");
+/// assert!(Affine::new(4, 1).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Affine {
+    a: u8,
+    b: u8,
+    a_inverse: u8,
+}
+
+impl Affine {
+    pub fn new(a: i64, b: i64) -> Result<Self, KeyError> {
+        let modulus = LETTERS as i64;
+        let (a_reduced, b) = (a.rem_euclid(modulus) as u8, b.rem_euclid(modulus) as u8);
+        let a_inverse = (1..LETTERS)
+            .find(|inverse| (a_reduced as u32 * *inverse as u32) % LETTERS as u32 == 1)
+            .ok_or(KeyError::NotInvertible(a))?;
+        Ok(Self {
+            a: a_reduced,
+            b,
+            a_inverse,
+        })
+    }
+}
+
+impl Cipher for Affine {
+    fn encrypt(&self, text: &str) -> String {
+        substitute_letters(text, |x| {
+            ((self.a as u32 * x as u32) % LETTERS as u32) as u8 + self.b
+        })
+    }
+
+    fn decrypt(&self, text: &str) -> String {
+        substitute_letters(text, |y| {
+            let y = (y + LETTERS - self.b) as u32;
+            ((self.a_inverse as u32 * y) % LETTERS as u32) as u8
+        })
+    }
+
+    fn stream(&self, direction: Direction) -> Box<dyn Stream + '_> {
+        Box::new(PerChunk {
+            cipher: self,
+            direction,
+        })
+    }
+}
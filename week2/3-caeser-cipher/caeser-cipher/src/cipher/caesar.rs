@@ -0,0 +1,210 @@
+use super::{substitute_letters, Cipher, Direction, PerChunk, Stream, LETTERS};
+use crate::alphabet::{Alphabet, AlphabetError};
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaesarError {
+    /// The shift is a multiple of the length of the alphabet, the text would not change
+    NoShift {
+        shift: i64,
+        letters: usize,
+    },
+    Alphabet(AlphabetError),
+}
+
+impl Display for CaesarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaesarError::NoShift { shift, letters } => write!(
+                f,
+                "A shift of {} leaves the text unchanged, the alphabet has {} letters",
+                shift, letters
+            ),
+            CaesarError::Alphabet(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for CaesarError {}
+
+impl From<AlphabetError> for CaesarError {
+    fn from(e: AlphabetError) -> Self {
+        CaesarError::Alphabet(e)
+    }
+}
+
+/// Shifts every letter of an [Alphabet] by the same number of places
+///
+/// ```rust
+/// use caeser_cipher::alphabet::Alphabet;
+/// use caeser_cipher::cipher::{Caesar, Cipher};
+///
+/// assert_eq!(Caesar::new(3).encrypt("Hello, World!"), "Khoor, Zruog!");
+/// // shifts are taken modulo the length of the alphabet
+/// assert_eq!(Caesar::new(-23).encrypt("Hello, World!"), "Khoor, Zruog!");
+///
+/// let digits = Caesar::with_chars(1, "0123456789").unwrap();
+/// assert_eq!(digits.encrypt("Agent 009"), "Agent 110");
+/// assert!(Caesar::checked(52, Alphabet::latin()).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Caesar {
+    shift: usize,
+    alphabet: Alphabet,
+}
+
+impl Caesar {
+    /// Any shift of the English alphabet, 0 and the multiples of 26 leave the text unchanged
+    pub fn new(shift: i64) -> Self {
+        Self::with_alphabet(shift, Alphabet::latin())
+    }
+
+    pub fn with_alphabet(shift: i64, alphabet: Alphabet) -> Self {
+        Self {
+            shift: alphabet.normalise(shift),
+            alphabet,
+        }
+    }
+
+    /// Refuses the shifts that leave the text unchanged
+    pub fn checked(shift: i64, alphabet: Alphabet) -> Result<Self, CaesarError> {
+        let caesar = Self::with_alphabet(shift, alphabet);
+        if caesar.shift == 0 {
+            return Err(CaesarError::NoShift {
+                shift,
+                letters: caesar.alphabet.len(),
+            });
+        }
+        Ok(caesar)
+    }
+
+    /// [Caesar::checked] on an alphabet of these characters
+    pub fn with_chars(shift: i64, chars: &str) -> Result<Self, CaesarError> {
+        Self::checked(shift, Alphabet::new(chars)?)
+    }
+
+    /// The shift, between 0 and the length of the alphabet - 1
+    pub fn shift(&self) -> usize {
+        self.shift
+    }
+
+    pub fn alphabet(&self) -> &Alphabet {
+        &self.alphabet
+    }
+}
+
+impl Cipher for Caesar {
+    fn encrypt(&self, text: &str) -> String {
+        self.alphabet.shift_by(text, self.shift)
+    }
+
+    fn decrypt(&self, text: &str) -> String {
+        let back = (self.alphabet.len() - self.shift) % self.alphabet.len();
+        self.alphabet.shift_by(text, back)
+    }
+
+    fn stream(&self, direction: Direction) -> Box<dyn Stream + '_> {
+        Box::new(PerChunk {
+            cipher: self,
+            direction,
+        })
+    }
+}
+
+/// Caesar with a shift of 13, which is its own inverse
+///
+/// ```rust
+/// use caeser_cipher::cipher::{Cipher, Rot13};
+///
+/// assert_eq!(Rot13.encrypt("Hello"), "Uryyb");
+/// assert_eq!(Rot13.encrypt("Uryyb"), "Hello");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rot13;
+
+impl Cipher for Rot13 {
+    fn encrypt(&self, text: &str) -> String {
+        substitute_letters(text, |x| x + 13)
+    }
+
+    fn decrypt(&self, text: &str) -> String {
+        self.encrypt(text)
+    }
+
+    fn stream(&self, direction: Direction) -> Box<dyn Stream + '_> {
+        Box::new(PerChunk {
+            cipher: self,
+            direction,
+        })
+    }
+}
+
+/// Reverses the alphabet: a becomes z, b becomes y, ...
+///
+/// ```rust
+/// use caeser_cipher::cipher::{Atbash, Cipher};
+///
+/// assert_eq!(Atbash.encrypt("Hello"), "Svool");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Atbash;
+
+impl Cipher for Atbash {
+    fn encrypt(&self, text: &str) -> String {
+        substitute_letters(text, |x| LETTERS - 1 - x)
+    }
+
+    fn decrypt(&self, text: &str) -> String {
+        self.encrypt(text)
+    }
+
+    fn stream(&self, direction: Direction) -> Box<dyn Stream + '_> {
+        Box::new(PerChunk {
+            cipher: self,
+            direction,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn large_shifts_and_non_ascii_letters_do_not_panic() {
+        // shifts that overflowed or underflowed the u8 arithmetic of the first version
+        for shift in [230, 255, 27, -1, i64::MAX, i64::MIN] {
+            let caesar = Caesar::checked(shift, Alphabet::latin()).unwrap();
+            assert_eq!(
+                caesar.decrypt(&caesar.encrypt("Zebra è qui")),
+                "Zebra è qui"
+            );
+        }
+        assert_eq!(Caesar::new(255).encrypt("abc"), "vwx");
+        assert_eq!(
+            Caesar::checked(-26, Alphabet::latin()),
+            Err(CaesarError::NoShift {
+                shift: -26,
+                letters: 26
+            })
+        );
+        assert_eq!(
+            Caesar::with_chars(1, "aa"),
+            Err(CaesarError::Alphabet(AlphabetError::Duplicate('a')))
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn checked_shifts_round_trip(text in any::<String>(), shift in any::<i64>()) {
+            match Caesar::checked(shift, Alphabet::latin()) {
+                Ok(caesar) => {
+                    prop_assert_ne!(shift.rem_euclid(26), 0);
+                    prop_assert_eq!(caesar.decrypt(&caesar.encrypt(&text)), text);
+                }
+                Err(e) => prop_assert_eq!(e, CaesarError::NoShift { shift, letters: 26 }),
+            }
+        }
+    }
+}
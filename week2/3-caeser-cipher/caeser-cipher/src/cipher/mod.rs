@@ -0,0 +1,427 @@
+//! Classical ciphers behind a common [Cipher] trait
+//!
+//! The substitution ciphers ([Caesar], [Rot13], [Atbash], [Affine], [Vigenere], [Beaufort])
+//! replace the ASCII letters, keeping their case, and leave everything else unchanged, so that
+//! decrypting gives back exactly the original text. [Playfair] only works on the letters, in
+//! upper case, and [RailFence] moves every character around without changing any. [Caesar]
+//! also works on other [alphabets](crate::alphabet).
+//!
+//! [CipherKind::build] parses the key of a cipher given on the command line:
+//!
+//! ```rust
+//! use caeser_cipher::cipher::{Cipher, CipherKind};
+//!
+//! let cipher = CipherKind::Vigenere.build(Some("lemon")).unwrap();
+//! assert_eq!(cipher.encrypt("Attack at dawn!"), "Lxfopv ef rnhr!");
+//! assert_eq!(cipher.decrypt("Lxfopv ef rnhr!"), "Attack at dawn!");
+//! ```
+
+mod affine;
+mod caesar;
+mod playfair;
+mod rail_fence;
+mod vigenere;
+
+pub use affine::Affine;
+pub use caesar::{Atbash, Caesar, CaesarError, Rot13};
+pub use playfair::Playfair;
+pub use rail_fence::RailFence;
+pub use vigenere::{Beaufort, Vigenere};
+
+use crate::alphabet::Alphabet;
+use clap::ValueEnum;
+use std::fmt::{self, Display};
+
+/// Letters in the English alphabet
+pub const LETTERS: u8 = 26;
+
+pub trait Cipher {
+    fn encrypt(&self, text: &str) -> String;
+    fn decrypt(&self, text: &str) -> String;
+
+    fn apply(&self, text: &str, direction: Direction) -> String {
+        match direction {
+            Direction::Encrypt => self.encrypt(text),
+            Direction::Decrypt => self.decrypt(text),
+        }
+    }
+
+    /// Encrypts or decrypts a text arriving in chunks, see [crate::stream]
+    ///
+    /// By default the chunks are collected and the whole text is transformed at the end,
+    /// ciphers that do not need the whole text override it.
+    fn stream(&self, direction: Direction) -> Box<dyn Stream + '_> {
+        Box::new(Buffered {
+            cipher: self,
+            direction,
+            text: String::new(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Encrypt,
+    Decrypt,
+}
+
+/// A text being encrypted or decrypted chunk after chunk
+pub trait Stream {
+    /// Takes the next chunk, returns the part of the result that is already known
+    fn push(&mut self, chunk: &str) -> String;
+    /// The rest of the result, once every chunk was pushed
+    fn finish(&mut self) -> String;
+}
+
+/// Transforms the whole text at the end
+struct Buffered<'a, C: ?Sized> {
+    cipher: &'a C,
+    direction: Direction,
+    text: String,
+}
+
+impl<C: Cipher + ?Sized> Stream for Buffered<'_, C> {
+    fn push(&mut self, chunk: &str) -> String {
+        self.text.push_str(chunk);
+        String::new()
+    }
+
+    fn finish(&mut self) -> String {
+        self.cipher
+            .apply(&std::mem::take(&mut self.text), self.direction)
+    }
+}
+
+/// Transforms every chunk on its own, for ciphers that replace each character independently
+struct PerChunk<'a, C: ?Sized> {
+    cipher: &'a C,
+    direction: Direction,
+}
+
+impl<C: Cipher + ?Sized> Stream for PerChunk<'_, C> {
+    fn push(&mut self, chunk: &str) -> String {
+        self.cipher.apply(chunk, self.direction)
+    }
+
+    fn finish(&mut self) -> String {
+        String::new()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyError {
+    /// The cipher needs a key
+    Missing(CipherKind),
+    /// The cipher takes no key
+    Unexpected(CipherKind),
+    /// The key is not a number
+    NotANumber(String),
+    /// The key has no letters
+    NoLetters(String),
+    /// The key has characters other than letters
+    NotAlphabetic(String),
+    /// The multiplier of an affine cipher shares a factor with 26, so it cannot be undone
+    NotInvertible(i64),
+    /// A rail fence needs at least two rails
+    TooFewRails(i64),
+    Caesar(CaesarError),
+    /// Only the Caesar cipher works on other alphabets than the English one
+    AlphabetNotSupported(CipherKind),
+}
+
+impl Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyError::Missing(kind) => write!(f, "The {} cipher needs a --key", kind),
+            KeyError::Unexpected(kind) => write!(f, "The {} cipher takes no key", kind),
+            KeyError::NotANumber(key) => write!(f, "{} is not a number", key),
+            KeyError::NoLetters(key) => write!(f, "The key '{}' has no letters", key),
+            KeyError::NotAlphabetic(key) => write!(f, "The key '{}' is not only letters", key),
+            KeyError::NotInvertible(a) => write!(
+                f,
+                "{} shares a factor with {}, the affine cipher could not be decrypted",
+                a, LETTERS
+            ),
+            KeyError::TooFewRails(rails) => {
+                write!(f, "A rail fence needs at least 2 rails, not {}", rails)
+            }
+            KeyError::Caesar(e) => write!(f, "{}", e),
+            KeyError::AlphabetNotSupported(kind) => {
+                write!(f, "The {} cipher only works on the English alphabet", kind)
+            }
+        }
+    }
+}
+
+impl std::error::Error for KeyError {}
+
+impl From<CaesarError> for KeyError {
+    fn from(e: CaesarError) -> Self {
+        KeyError::Caesar(e)
+    }
+}
+
+/// The ciphers that can be picked on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CipherKind {
+    /// Shifts every letter, the key is the shift
+    Caesar,
+    /// Caesar with a shift of 13, no key
+    Rot13,
+    /// Reverses the alphabet, no key
+    Atbash,
+    /// Letter x becomes a * x + b, the key is "a,b" with a coprime with 26
+    Affine,
+    /// Caesar with a shift for every letter of the key, a word
+    Vigenere,
+    /// Subtracts every letter from a letter of the key, a word
+    Beaufort,
+    /// Substitutes pairs of letters in a 5x5 square built from the key, a word
+    Playfair,
+    /// Writes the text in a zigzag on rails, the key is the number of rails
+    RailFence,
+}
+
+impl Display for CipherKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self
+            .to_possible_value()
+            .expect("No cipher is skipped")
+            .get_name()
+            .to_string();
+        f.pad(&name)
+    }
+}
+
+impl CipherKind {
+    /// The cipher of this kind with the key parsed from `key`
+    pub fn build(self, key: Option<&str>) -> Result<Box<dyn Cipher>, KeyError> {
+        self.build_with(key, &Alphabet::latin())
+    }
+
+    /// [CipherKind::build], shifting the Caesar cipher through `alphabet`
+    pub fn build_with(
+        self,
+        key: Option<&str>,
+        alphabet: &Alphabet,
+    ) -> Result<Box<dyn Cipher>, KeyError> {
+        if self != CipherKind::Caesar && *alphabet != Alphabet::latin() {
+            return Err(KeyError::AlphabetNotSupported(self));
+        }
+        let key = key.map(str::trim);
+        let required = || key.ok_or(KeyError::Missing(self));
+        match self {
+            CipherKind::Rot13 | CipherKind::Atbash if key.is_some() => {
+                Err(KeyError::Unexpected(self))
+            }
+            CipherKind::Rot13 => Ok(Box::new(Rot13)),
+            CipherKind::Atbash => Ok(Box::new(Atbash)),
+            CipherKind::Caesar => {
+                let shift = parse_number(required()?)?;
+                Ok(Box::new(Caesar::checked(shift, alphabet.clone())?))
+            }
+            CipherKind::Affine => {
+                let key = required()?;
+                let (a, b) = key
+                    .split_once(',')
+                    .ok_or_else(|| KeyError::NotANumber(key.to_string()))?;
+                Ok(Box::new(Affine::new(parse_number(a)?, parse_number(b)?)?))
+            }
+            CipherKind::Vigenere => Ok(Box::new(Vigenere::new(required()?)?)),
+            CipherKind::Beaufort => Ok(Box::new(Beaufort::new(required()?)?)),
+            CipherKind::Playfair => Ok(Box::new(Playfair::new(required()?)?)),
+            CipherKind::RailFence => {
+                let rails = parse_number(required()?)?;
+                let rails = usize::try_from(rails).map_err(|_| KeyError::TooFewRails(rails))?;
+                Ok(Box::new(RailFence::new(rails)?))
+            }
+        }
+    }
+}
+
+fn parse_number(key: &str) -> Result<i64, KeyError> {
+    key.trim()
+        .parse()
+        .map_err(|_| KeyError::NotANumber(key.to_string()))
+}
+
+/// Replaces the ASCII letters of `text` with `substitute` of their index in the alphabet,
+/// keeping their case
+fn substitute_letters(text: &str, mut substitute: impl FnMut(u8) -> u8) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_ascii_alphabetic() {
+                let base = if c.is_ascii_lowercase() { b'a' } else { b'A' };
+                let index = substitute(c as u8 - base) % LETTERS;
+                (base + index) as char
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Indices in the alphabet of the letters of a key, which must only be letters
+fn key_letters(key: &str) -> Result<Vec<u8>, KeyError> {
+    if key.is_empty() {
+        return Err(KeyError::NoLetters(key.to_string()));
+    }
+    if !key.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(KeyError::NotAlphabetic(key.to_string()));
+    }
+    Ok(key.bytes().map(|b| b.to_ascii_lowercase() - b'a').collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Keys that every cipher accepts
+    fn ciphers() -> impl Strategy<Value = Vec<(CipherKind, Option<String>)>> {
+        let odd_not_13 = (0i64..13).prop_map(|n| if n == 6 { 1 } else { 2 * n + 1 });
+        (
+            any::<i64>().prop_filter("a shift changing the text", |s| s.rem_euclid(26) != 0),
+            odd_not_13,
+            any::<i64>(),
+            "[a-zA-Z]{1,12}",
+            "[a-zA-Z]{1,12}",
+            "[a-zA-Z ]{0,3}[a-zA-Z]{1,12}",
+            2usize..12,
+        )
+            .prop_map(|(shift, a, b, vigenere, beaufort, playfair, rails)| {
+                vec![
+                    (CipherKind::Caesar, Some(shift.to_string())),
+                    (CipherKind::Rot13, None),
+                    (CipherKind::Atbash, None),
+                    (CipherKind::Affine, Some(format!("{},{}", a, b))),
+                    (CipherKind::Vigenere, Some(vigenere)),
+                    (CipherKind::Beaufort, Some(beaufort)),
+                    (CipherKind::Playfair, Some(playfair)),
+                    (CipherKind::RailFence, Some(rails.to_string())),
+                ]
+            })
+    }
+
+    fn keys() -> impl Strategy<Value = Option<String>> {
+        prop_oneof![
+            any::<Option<String>>(),
+            any::<i64>().prop_map(|n| Some(n.to_string())),
+            (any::<i64>(), any::<i64>()).prop_map(|(a, b)| Some(format!("{},{}", a, b))),
+            "[a-zA-Z]{0,8}".prop_map(Some),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn any_key_and_text_never_panic(
+            kind in prop::sample::select(CipherKind::value_variants()),
+            key in keys(),
+            text in any::<String>(),
+            rails in prop_oneof![Just(usize::MAX), any::<usize>()],
+        ) {
+            if let Ok(cipher) = kind.build(key.as_deref()) {
+                cipher.decrypt(&cipher.encrypt(&text));
+                cipher.decrypt(&text);
+            }
+            // beyond the keys that parse as an i64
+            if let Ok(fence) = RailFence::new(rails) {
+                prop_assert_eq!(fence.decrypt(&fence.encrypt(&text)), text);
+            }
+        }
+
+        #[test]
+        fn every_cipher_round_trips(text in "\\PC{0,60}", ciphers in ciphers()) {
+            for (kind, key) in ciphers {
+                let cipher = kind.build(key.as_deref()).unwrap();
+                let decrypted = cipher.decrypt(&cipher.encrypt(&text));
+                if kind == CipherKind::Playfair {
+                    prop_assert_eq!(decrypted, Playfair::prepare(&text), "{}", kind);
+                } else {
+                    prop_assert_eq!(decrypted, text.clone(), "{}", kind);
+                }
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn every_cipher_streams_like_it_transforms_at_once(
+            text in "\\PC{0,60}",
+            splits in prop::collection::vec(any::<prop::sample::Index>(), 0..6),
+            ciphers in ciphers(),
+        ) {
+            let chars: Vec<char> = text.chars().collect();
+            let mut splits: Vec<usize> = splits.iter().map(|i| i.index(chars.len() + 1)).collect();
+            splits.sort_unstable();
+            splits.push(chars.len());
+            for (kind, key) in ciphers {
+                let cipher = kind.build(key.as_deref()).unwrap();
+                for direction in [Direction::Encrypt, Direction::Decrypt] {
+                    let mut stream = cipher.stream(direction);
+                    let mut streamed = String::new();
+                    let mut start = 0;
+                    for &end in &splits {
+                        let chunk: String = chars[start..end].iter().collect();
+                        streamed.push_str(&stream.push(&chunk));
+                        start = end;
+                    }
+                    streamed.push_str(&stream.finish());
+                    prop_assert_eq!(streamed, cipher.apply(&text, direction), "{}", kind);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn keys_are_parsed_and_checked() {
+        let error = |kind: CipherKind, key: Option<&str>| kind.build(key).err();
+        assert!(error(CipherKind::Caesar, Some("-29")).is_none());
+        assert_eq!(
+            error(CipherKind::Caesar, None),
+            Some(KeyError::Missing(CipherKind::Caesar))
+        );
+        assert_eq!(
+            error(CipherKind::Rot13, Some("3")),
+            Some(KeyError::Unexpected(CipherKind::Rot13))
+        );
+        assert_eq!(
+            error(CipherKind::Affine, Some("13,2")),
+            Some(KeyError::NotInvertible(13))
+        );
+        assert_eq!(
+            error(CipherKind::Affine, Some("5")),
+            Some(KeyError::NotANumber("5".to_string()))
+        );
+        assert_eq!(
+            error(CipherKind::Vigenere, Some("key 2")),
+            Some(KeyError::NotAlphabetic("key 2".to_string()))
+        );
+        assert_eq!(
+            error(CipherKind::Playfair, Some("42")),
+            Some(KeyError::NoLetters("42".to_string()))
+        );
+        assert_eq!(
+            error(CipherKind::RailFence, Some("1")),
+            Some(KeyError::TooFewRails(1))
+        );
+        assert_eq!(
+            error(CipherKind::RailFence, Some("-3")),
+            Some(KeyError::TooFewRails(-3))
+        );
+        assert_eq!(
+            error(CipherKind::Caesar, Some("26")),
+            Some(KeyError::Caesar(CaesarError::NoShift {
+                shift: 26,
+                letters: 26
+            }))
+        );
+        let digits = Alphabet::new("0123456789").unwrap();
+        assert!(CipherKind::Caesar.build_with(Some("3"), &digits).is_ok());
+        assert_eq!(
+            CipherKind::Vigenere.build_with(Some("key"), &digits).err(),
+            Some(KeyError::AlphabetNotSupported(CipherKind::Vigenere))
+        );
+        assert_eq!(CipherKind::RailFence.to_string(), "rail-fence");
+    }
+}
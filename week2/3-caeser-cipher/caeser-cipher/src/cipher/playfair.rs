@@ -0,0 +1,130 @@
+use super::{Cipher, KeyError};
+
+/// Substitutes pairs of letters using a 5x5 square of the alphabet, without J, starting with
+/// the letters of the key
+///
+/// The text is [prepared](Playfair::prepare) first: only its letters are kept, in upper case
+/// and with J replaced by I, and an X separates a double letter within a pair or completes
+/// the last pair. Decrypting gives back the prepared text.
+///
+/// ```rust
+/// use caeser_cipher::cipher::{Cipher, Playfair};
+///
+/// let playfair = Playfair::new("playfair example").unwrap();
+/// let encrypted = playfair.encrypt("Hide the gold in the tree stump");
+/// assert_eq!(encrypted, "BMODZBXDNABEKUDMUIXMMOUVIF");
+/// assert_eq!(playfair.decrypt(&encrypted), "HIDETHEGOLDINTHETREXESTUMP");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Playfair {
+    square: [u8; 25],
+    /// Position in the square of every letter, J sharing the one of I
+    positions: [usize; 26],
+}
+
+impl Playfair {
+    /// Other characters than letters in the key are ignored
+    pub fn new(key: &str) -> Result<Self, KeyError> {
+        let key_letters = letters(key);
+        if key_letters.is_empty() {
+            return Err(KeyError::NoLetters(key.to_string()));
+        }
+        let mut square = [0u8; 25];
+        let mut positions = [usize::MAX; 26];
+        let mut filled = 0;
+        for letter in key_letters.into_iter().chain(b'A'..=b'Z') {
+            let index = (letter - b'A') as usize;
+            if letter != b'J' && positions[index] == usize::MAX {
+                square[filled] = letter;
+                positions[index] = filled;
+                filled += 1;
+            }
+        }
+        positions[(b'J' - b'A') as usize] = positions[(b'I' - b'A') as usize];
+        Ok(Self { square, positions })
+    }
+
+    /// The letters of `text` as they are encrypted, in pairs of different letters
+    ///
+    /// ```rust
+    /// use caeser_cipher::cipher::Playfair;
+    ///
+    /// assert_eq!(Playfair::prepare("Jolly good"), "IOLXLYGOOD");
+    /// assert_eq!(Playfair::prepare("box"), "BOXQ");
+    /// ```
+    pub fn prepare(text: &str) -> String {
+        let mut prepared = Vec::new();
+        for letter in letters(text) {
+            let letter = if letter == b'J' { b'I' } else { letter };
+            if prepared.len() % 2 == 1 && prepared.last() == Some(&letter) {
+                prepared.push(filler(letter));
+            }
+            prepared.push(letter);
+        }
+        if let Some(&last) = prepared.last().filter(|_| prepared.len() % 2 == 1) {
+            prepared.push(filler(last));
+        }
+        prepared.into_iter().map(char::from).collect()
+    }
+
+    fn position(&self, letter: u8) -> (usize, usize) {
+        let position = self.positions[(letter - b'A') as usize];
+        (position / 5, position % 5)
+    }
+
+    fn letter(&self, row: usize, column: usize) -> char {
+        self.square[row * 5 + column] as char
+    }
+
+    /// Moves every pair `step` places right in its row or down in its column, which is 1 to
+    /// encrypt and 4 (one back) to decrypt
+    fn substitute_pairs(&self, letters: &[u8], step: usize) -> String {
+        let mut result = String::with_capacity(letters.len());
+        for pair in letters.chunks(2) {
+            let (row1, column1) = self.position(pair[0]);
+            let (row2, column2) = self.position(pair[1]);
+            if row1 == row2 {
+                result.push(self.letter(row1, (column1 + step) % 5));
+                result.push(self.letter(row2, (column2 + step) % 5));
+            } else if column1 == column2 {
+                result.push(self.letter((row1 + step) % 5, column1));
+                result.push(self.letter((row2 + step) % 5, column2));
+            } else {
+                result.push(self.letter(row1, column2));
+                result.push(self.letter(row2, column1));
+            }
+        }
+        result
+    }
+}
+
+/// The ASCII letters of `text` in upper case
+fn letters(text: &str) -> Vec<u8> {
+    text.bytes()
+        .filter(u8::is_ascii_alphabetic)
+        .map(|b| b.to_ascii_uppercase())
+        .collect()
+}
+
+/// Separates two equal letters, X unless they are X
+fn filler(letter: u8) -> u8 {
+    if letter == b'X' {
+        b'Q'
+    } else {
+        b'X'
+    }
+}
+
+impl Cipher for Playfair {
+    fn encrypt(&self, text: &str) -> String {
+        self.substitute_pairs(Self::prepare(text).as_bytes(), 1)
+    }
+
+    fn decrypt(&self, text: &str) -> String {
+        let mut letters = letters(text);
+        if letters.len() % 2 == 1 {
+            letters.push(b'X');
+        }
+        self.substitute_pairs(&letters, 4).replace('J', "I")
+    }
+}
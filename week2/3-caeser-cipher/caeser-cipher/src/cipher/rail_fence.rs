@@ -0,0 +1,59 @@
+use super::{Cipher, KeyError};
+
+/// Writes the text in a zigzag down and up the rails, then reads the rails one after the other
+///
+/// Every character is moved, none is changed.
+///
+/// ```rust
+/// use caeser_cipher::cipher::{Cipher, RailFence};
+///
+/// let fence = RailFence::new(3).unwrap();
+/// assert_eq!(fence.encrypt("WEAREDISCOVEREDFLEEATONCE"), "WECRLTEERDSOEEFEAOCAIVDEN");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RailFence {
+    rails: usize,
+}
+
+impl RailFence {
+    pub fn new(rails: usize) -> Result<Self, KeyError> {
+        if rails < 2 {
+            return Err(KeyError::TooFewRails(rails as i64));
+        }
+        Ok(Self { rails })
+    }
+
+    /// The positions in the text of the characters of the ciphertext, in order
+    fn order(&self, len: usize) -> Vec<usize> {
+        // more rails than characters put every character on its own rail
+        let rails = self.rails.min(len.max(2));
+        let period = 2 * (rails - 1);
+        let rail = |i: usize| {
+            let step = i % period;
+            step.min(period - step)
+        };
+        let mut order: Vec<usize> = (0..len).collect();
+        // stable: the characters of a rail stay in the order of the text
+        order.sort_by_key(|&i| rail(i));
+        order
+    }
+}
+
+impl Cipher for RailFence {
+    fn encrypt(&self, text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        self.order(chars.len())
+            .into_iter()
+            .map(|i| chars[i])
+            .collect()
+    }
+
+    fn decrypt(&self, text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut plain = vec!['\0'; chars.len()];
+        for (&c, i) in chars.iter().zip(self.order(chars.len())) {
+            plain[i] = c;
+        }
+        plain.into_iter().collect()
+    }
+}
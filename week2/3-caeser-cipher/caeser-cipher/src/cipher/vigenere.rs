@@ -0,0 +1,121 @@
+use super::{key_letters, substitute_letters, Cipher, Direction, KeyError, Stream, LETTERS};
+
+/// Caesar with a different shift for every letter, taken in turn from the letters of the key
+///
+/// Only letters use up the key, the other characters are copied.
+///
+/// ```rust
+/// use caeser_cipher::cipher::{Cipher, Vigenere};
+///
+/// let vigenere = Vigenere::new("LEMON").unwrap();
+/// assert_eq!(vigenere.encrypt("ATTACK AT DAWN"), "LXFOPV EF RNHR");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vigenere {
+    shifts: Vec<u8>,
+}
+
+impl Vigenere {
+    /// The key must be only letters, in any case
+    pub fn new(key: &str) -> Result<Self, KeyError> {
+        Ok(Self {
+            shifts: key_letters(key)?,
+        })
+    }
+
+    /// The shift of every letter of the key
+    pub fn shifts(&self) -> &[u8] {
+        &self.shifts
+    }
+}
+
+impl Cipher for Vigenere {
+    fn encrypt(&self, text: &str) -> String {
+        KeyStream::new(&self.shifts, |x, k| x + k).push(text)
+    }
+
+    fn decrypt(&self, text: &str) -> String {
+        KeyStream::new(&self.shifts, |x, k| x + LETTERS - k).push(text)
+    }
+
+    fn stream(&self, direction: Direction) -> Box<dyn Stream + '_> {
+        Box::new(match direction {
+            Direction::Encrypt => KeyStream::new(&self.shifts, |x, k| x + k),
+            Direction::Decrypt => KeyStream::new(&self.shifts, |x, k| x + LETTERS - k),
+        })
+    }
+}
+
+/// Every letter is subtracted from the next letter of the key, which makes encrypting and
+/// decrypting the same operation
+///
+/// ```rust
+/// use caeser_cipher::cipher::{Beaufort, Cipher};
+///
+/// let beaufort = Beaufort::new("FORTIFICATION").unwrap();
+/// assert_eq!(
+///     beaufort.encrypt("DEFENDTHEEASTWALLOFTHECASTLE"),
+///     "CKMPVCPVWPIWUJOGIUAPVWRIWUUK"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Beaufort {
+    key: Vec<u8>,
+}
+
+impl Beaufort {
+    /// The key must be only letters, in any case
+    pub fn new(key: &str) -> Result<Self, KeyError> {
+        Ok(Self {
+            key: key_letters(key)?,
+        })
+    }
+}
+
+impl Cipher for Beaufort {
+    fn encrypt(&self, text: &str) -> String {
+        KeyStream::new(&self.key, |x, k| k + LETTERS - x).push(text)
+    }
+
+    fn decrypt(&self, text: &str) -> String {
+        self.encrypt(text)
+    }
+
+    fn stream(&self, _direction: Direction) -> Box<dyn Stream + '_> {
+        Box::new(KeyStream::new(&self.key, |x, k| k + LETTERS - x))
+    }
+}
+
+/// Substitutes every letter with the next letter of the key, going on from chunk to chunk
+/// where the previous chunk stopped in the key
+struct KeyStream<'a> {
+    key: &'a [u8],
+    /// The position in the key of the next letter
+    position: usize,
+    /// The substitute of a letter, given the letter and the letter of the key
+    substitute: fn(u8, u8) -> u8,
+}
+
+impl<'a> KeyStream<'a> {
+    fn new(key: &'a [u8], substitute: fn(u8, u8) -> u8) -> Self {
+        Self {
+            key,
+            position: 0,
+            substitute,
+        }
+    }
+}
+
+impl Stream for KeyStream<'_> {
+    fn push(&mut self, chunk: &str) -> String {
+        substitute_letters(chunk, |x| {
+            let k = self.key[self.position];
+            self.position = (self.position + 1) % self.key.len();
+            (self.substitute)(x, k)
+        })
+    }
+
+    fn finish(&mut self) -> String {
+        String::new()
+    }
+}
@@ -0,0 +1,410 @@
+//! Breaking Caesar and Vigenère ciphers by frequency analysis
+//!
+//! Shifting the letters of a text back by the right amount gives letter frequencies close to
+//! those of its language, measured with the chi-squared statistic: [crack_caesar] tries the 26
+//! shifts and ranks them. A Vigenère ciphertext is a Caesar ciphertext in every column of
+//! `key length` letters: [key_lengths] guesses the length from the index of coincidence of the
+//! columns and from the distances between repeated sequences (Kasiski examination), then
+//! [crack_vigenere] solves every column as a Caesar cipher.
+//!
+//! ```rust
+//! use caeser_cipher::cipher::{Caesar, Cipher};
+//! use caeser_cipher::crack::{crack_caesar, Language};
+//!
+//! let secret = Caesar::new(11).encrypt("Frequency analysis breaks every simple substitution cipher");
+//! let best = &crack_caesar(&secret, &Language::ALL)[0];
+//! assert_eq!(best.shift, 11);
+//! assert_eq!(best.plaintext, "Frequency analysis breaks every simple substitution cipher");
+//! ```
+
+use crate::cipher::{Caesar, Cipher, Vigenere, LETTERS};
+use clap::ValueEnum;
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+/// Languages with known letter frequencies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum Language {
+    English,
+    Italian,
+}
+
+/// Frequencies of the letters A to Z in English, in percent
+const ENGLISH: [f64; 26] = [
+    8.167, 1.492, 2.782, 4.253, 12.702, 2.228, 2.015, 6.094, 6.966, 0.153, 0.772, 4.025, 2.406,
+    6.749, 7.507, 1.929, 0.095, 5.987, 6.327, 9.056, 2.758, 0.978, 2.360, 0.150, 1.974, 0.074,
+];
+
+/// Frequencies of the letters A to Z in Italian, in percent. J, K, W, X and Y only appear in
+/// foreign words
+const ITALIAN: [f64; 26] = [
+    11.74, 0.92, 4.50, 3.73, 11.79, 0.95, 1.64, 1.54, 11.28, 0.01, 0.01, 6.51, 2.51, 6.88, 9.83,
+    3.05, 0.51, 6.37, 4.98, 5.62, 3.01, 2.10, 0.01, 0.01, 0.01, 0.49,
+];
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Italian];
+
+    /// Frequencies of the letters A to Z, in percent
+    pub fn frequencies(self) -> &'static [f64; 26] {
+        match self {
+            Language::English => &ENGLISH,
+            Language::Italian => &ITALIAN,
+        }
+    }
+
+    /// Probability that two letters picked at random are the same
+    pub fn index_of_coincidence(self) -> f64 {
+        self.frequencies()
+            .iter()
+            .map(|percent| (percent / 100.0).powi(2))
+            .sum()
+    }
+}
+
+impl Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Language::English => "English",
+            Language::Italian => "Italian",
+        };
+        f.pad(name)
+    }
+}
+
+/// How often every letter A to Z appears in `text`, in any case
+pub fn letter_counts(text: &str) -> [usize; 26] {
+    let mut counts = [0; 26];
+    for b in text.bytes().filter(u8::is_ascii_alphabetic) {
+        counts[(b.to_ascii_lowercase() - b'a') as usize] += 1;
+    }
+    counts
+}
+
+/// Distance between the letter counts and the frequencies of `language`, 0 for a perfect
+/// match
+pub fn chi_squared(counts: &[usize; 26], language: Language) -> f64 {
+    let total: usize = counts.iter().sum();
+    counts
+        .iter()
+        .zip(language.frequencies())
+        .map(|(&observed, percent)| {
+            let expected = total as f64 * percent / 100.0;
+            (observed as f64 - expected).powi(2) / expected
+        })
+        .sum()
+}
+
+/// Probability that two letters of the text picked at random are the same, about 0.038 for
+/// random letters and 0.067 for English
+pub fn index_of_coincidence(counts: &[usize; 26]) -> f64 {
+    let total: usize = counts.iter().sum();
+    if total < 2 {
+        return 0.0;
+    }
+    let pairs: usize = counts.iter().map(|n| n * n.saturating_sub(1)).sum();
+    pairs as f64 / (total * (total - 1)) as f64
+}
+
+/// Turns chi-squared scores into weights summing to 1, the lowest score getting the most
+///
+/// The scores are treated as log-likelihoods, `exp(-chi_squared / 2)`: a score 10 points
+/// above the best one is about 150 times less likely.
+fn confidences(scores: &[f64]) -> Vec<f64> {
+    let best = scores.iter().copied().fold(f64::INFINITY, f64::min);
+    let weights: Vec<f64> = scores.iter().map(|s| (-(s - best) / 2.0).exp()).collect();
+    let total: f64 = weights.iter().sum();
+    weights.iter().map(|w| w / total).collect()
+}
+
+/// The best shift of a Caesar ciphertext with these letter counts, its score and confidence
+fn best_shift(counts: &[usize; 26], language: Language) -> (u8, f64, f64) {
+    let scores: Vec<f64> = (0..LETTERS)
+        .map(|shift| chi_squared(&shifted_back(counts, shift), language))
+        .collect();
+    let confidence = confidences(&scores);
+    let shift = (0..LETTERS)
+        .min_by(|&a, &b| scores[a as usize].total_cmp(&scores[b as usize]))
+        .expect("There are 26 shifts");
+    (shift, scores[shift as usize], confidence[shift as usize])
+}
+
+/// The counts of the plaintext, if the text was shifted by `shift`
+fn shifted_back(counts: &[usize; 26], shift: u8) -> [usize; 26] {
+    let mut plain = [0; 26];
+    for (letter, &count) in counts.iter().enumerate() {
+        plain[(letter + (LETTERS - shift) as usize) % LETTERS as usize] = count;
+    }
+    plain
+}
+
+/// A possible decryption of a Caesar ciphertext
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaesarCandidate {
+    pub shift: u8,
+    pub plaintext: String,
+    /// Language whose frequencies fit the plaintext best
+    pub language: Language,
+    pub chi_squared: f64,
+    /// Between 0 and 1, the share of this shift among all the shifts, see the module docs
+    pub confidence: f64,
+}
+
+/// All 26 shifts of `text`, the most likely first
+///
+/// Every shift is scored against each of the `languages` and keeps its best score. Empty if
+/// the text has no letters.
+///
+/// # Panics
+///
+/// If `languages` is empty.
+pub fn crack_caesar(text: &str, languages: &[Language]) -> Vec<CaesarCandidate> {
+    assert!(!languages.is_empty(), "No language to compare with");
+    let counts = letter_counts(text);
+    if counts.iter().all(|&count| count == 0) {
+        return Vec::new();
+    }
+    let scored: Vec<(u8, Language, f64)> = (0..LETTERS)
+        .map(|shift| {
+            let plain = shifted_back(&counts, shift);
+            languages
+                .iter()
+                .map(|&language| (shift, language, chi_squared(&plain, language)))
+                .min_by(|a, b| a.2.total_cmp(&b.2))
+                .expect("There is a language")
+        })
+        .collect();
+    let scores: Vec<f64> = scored.iter().map(|(_, _, score)| *score).collect();
+    let mut candidates: Vec<CaesarCandidate> = scored
+        .into_iter()
+        .zip(confidences(&scores))
+        .map(
+            |((shift, language, chi_squared), confidence)| CaesarCandidate {
+                shift,
+                plaintext: Caesar::new(shift.into()).decrypt(text),
+                language,
+                chi_squared,
+                confidence,
+            },
+        )
+        .collect();
+    candidates.sort_by(|a, b| a.chi_squared.total_cmp(&b.chi_squared));
+    candidates
+}
+
+/// How well a key length fits a Vigenère ciphertext
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyLength {
+    pub length: usize,
+    /// Mean index of coincidence of the columns, close to the one of the language for the
+    /// right length and its multiples
+    pub index_of_coincidence: f64,
+    /// Share of the distances between repeated trigrams that are multiples of the length
+    pub kasiski: f64,
+    /// Both measures combined, the higher the better
+    pub score: f64,
+}
+
+/// The uppercase letters of `text`
+fn letters(text: &str) -> Vec<u8> {
+    text.bytes()
+        .filter(u8::is_ascii_alphabetic)
+        .map(|b| b.to_ascii_uppercase())
+        .collect()
+}
+
+/// Distances between the repetitions of every sequence of three letters
+fn repeat_distances(letters: &[u8]) -> Vec<usize> {
+    let mut last_seen: HashMap<&[u8], usize> = HashMap::new();
+    let mut distances = Vec::new();
+    for (position, trigram) in letters.windows(3).enumerate() {
+        if let Some(previous) = last_seen.insert(trigram, position) {
+            distances.push(position - previous);
+        }
+    }
+    distances
+}
+
+/// The key lengths from 1 to `max_length`, the most likely first
+///
+/// The index of coincidence is highest for the key length and its multiples, the Kasiski
+/// share for the key length and its divisors: the score is their product, with the index
+/// relative to the best one. Without repeated trigrams only the index counts.
+pub fn key_lengths(text: &str, max_length: usize) -> Vec<KeyLength> {
+    let letters = letters(text);
+    let distances = repeat_distances(&letters);
+    let mut lengths: Vec<KeyLength> = (1..=max_length.max(1))
+        .map(|length| {
+            let iocs: Vec<f64> = (0..length)
+                .map(|column| index_of_coincidence(&column_counts(&letters, length, column)))
+                .collect();
+            let kasiski = if distances.is_empty() {
+                1.0
+            } else {
+                let multiples = distances.iter().filter(|d| *d % length == 0).count();
+                multiples as f64 / distances.len() as f64
+            };
+            KeyLength {
+                length,
+                index_of_coincidence: iocs.iter().sum::<f64>() / length as f64,
+                kasiski,
+                score: 0.0,
+            }
+        })
+        .collect();
+    let best_ioc = lengths
+        .iter()
+        .map(|l| l.index_of_coincidence)
+        .fold(0.0, f64::max);
+    for length in lengths.iter_mut() {
+        let ioc = if best_ioc > 0.0 {
+            length.index_of_coincidence / best_ioc
+        } else {
+            0.0
+        };
+        length.score = ioc * length.kasiski;
+    }
+    // stable: the shorter length wins a tie
+    lengths.sort_by(|a, b| b.score.total_cmp(&a.score));
+    lengths
+}
+
+/// Letter counts of every `length`-th letter, starting from `column`
+fn column_counts(letters: &[u8], length: usize, column: usize) -> [usize; 26] {
+    let mut counts = [0; 26];
+    for letter in letters.iter().skip(column).step_by(length) {
+        counts[(letter - b'A') as usize] += 1;
+    }
+    counts
+}
+
+/// The most likely decryption of a Vigenère ciphertext
+#[derive(Debug, Clone, PartialEq)]
+pub struct VigenereSolution {
+    pub key: String,
+    pub plaintext: String,
+    pub language: Language,
+    pub chi_squared: f64,
+    /// Between 0 and 1, the mean confidence of the shifts of the columns
+    pub confidence: f64,
+    /// The key lengths considered, the most likely first
+    pub key_lengths: Vec<KeyLength>,
+}
+
+/// Solves a Vigenère ciphertext with a key of at most `max_key_length` letters
+///
+/// The columns of the [most likely](key_lengths) key length are solved as Caesar ciphers for
+/// every language, and the plaintext closest to its language wins. `None` if the text has
+/// no letters.
+///
+/// # Panics
+///
+/// If `languages` is empty.
+pub fn crack_vigenere(
+    text: &str,
+    languages: &[Language],
+    max_key_length: usize,
+) -> Option<VigenereSolution> {
+    assert!(!languages.is_empty(), "No language to compare with");
+    let letters = letters(text);
+    if letters.is_empty() {
+        return None;
+    }
+    let key_lengths = key_lengths(text, max_key_length.min(letters.len()));
+    let length = key_lengths[0].length;
+    languages
+        .iter()
+        .map(|&language| {
+            let shifts: Vec<(u8, f64, f64)> = (0..length)
+                .map(|column| best_shift(&column_counts(&letters, length, column), language))
+                .collect();
+            let key: String = shifts
+                .iter()
+                .map(|(shift, _, _)| (b'A' + shift) as char)
+                .collect();
+            let plaintext = Vigenere::new(&key)
+                .expect("The key is made of letters")
+                .decrypt(text);
+            let confidence = shifts
+                .iter()
+                .map(|(_, _, confidence)| confidence)
+                .sum::<f64>()
+                / length as f64;
+            VigenereSolution {
+                key,
+                chi_squared: chi_squared(&letter_counts(&plaintext), language),
+                plaintext,
+                language,
+                confidence,
+                key_lengths: key_lengths.clone(),
+            }
+        })
+        .min_by(|a, b| a.chi_squared.total_cmp(&b.chi_squared))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENGLISH_TEXT: &str = "It was a bright cold day in April and the clocks were \
+        striking thirteen. The hallway smelt of boiled cabbage and old rag mats. At one end \
+        of it a coloured poster, too large for indoor display, had been tacked to the wall. \
+        It depicted simply an enormous face, more than a metre wide: the face of a man of \
+        about forty five, with a heavy black moustache and ruggedly handsome features. The \
+        flat was seven flights up, and the man who was thirty nine and had a varicose ulcer \
+        above his right ankle went slowly, resting several times on the way. On each landing, \
+        opposite the lift shaft, the poster with the enormous face gazed from the wall.";
+
+    const ITALIAN_TEXT: &str = "Nel mezzo del cammin di nostra vita mi ritrovai per una \
+        selva oscura, che la diritta via era smarrita. Ahi quanto a dir qual era e cosa dura \
+        esta selva selvaggia e aspra e forte che nel pensier rinova la paura! Tant e amara \
+        che poco e piu morte; ma per trattar del ben ch i vi trovai, diro de l altre cose \
+        ch i v ho scorte.";
+
+    #[test]
+    fn caesar_is_cracked_in_both_languages() {
+        for (text, language, shift) in [
+            (ENGLISH_TEXT, Language::English, 7),
+            (ITALIAN_TEXT, Language::Italian, 19),
+        ] {
+            let secret = Caesar::new(shift.into()).encrypt(text);
+            let candidates = crack_caesar(&secret, &Language::ALL);
+            assert_eq!(candidates.len(), 26);
+            let best = &candidates[0];
+            assert_eq!(
+                (best.shift, best.language, best.plaintext.as_str()),
+                (shift, language, text)
+            );
+            assert!(best.confidence > 0.99, "{}", best.confidence);
+            let total: f64 = candidates.iter().map(|c| c.confidence).sum();
+            assert!((total - 1.0).abs() < 1e-9);
+        }
+        assert!(crack_caesar("1234 !!", &Language::ALL).is_empty());
+    }
+
+    #[test]
+    fn index_of_coincidence_separates_language_from_noise() {
+        let english = index_of_coincidence(&letter_counts(ENGLISH_TEXT));
+        assert!((english - Language::English.index_of_coincidence()).abs() < 0.005);
+        let uniform = index_of_coincidence(&[10; 26]);
+        assert!(uniform < 0.04, "{}", uniform);
+    }
+
+    #[test]
+    fn repeated_trigrams_give_their_distances() {
+        assert_eq!(repeat_distances(b"THEXXTHEYYYYTHE"), vec![5, 1, 7]);
+    }
+
+    #[test]
+    fn vigenere_is_cracked() {
+        for key in ["LEMON", "CIPHER", "KEY"] {
+            let secret = Vigenere::new(key).unwrap().encrypt(ENGLISH_TEXT);
+            let solution = crack_vigenere(&secret, &Language::ALL, 12).unwrap();
+            assert_eq!(solution.key, key);
+            assert_eq!(solution.plaintext, ENGLISH_TEXT);
+            assert_eq!(solution.language, Language::English);
+            assert!(solution.confidence > 0.5, "{}", solution.confidence);
+        }
+        assert_eq!(crack_vigenere("1234", &Language::ALL, 12), None);
+    }
+}
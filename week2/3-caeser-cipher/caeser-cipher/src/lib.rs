@@ -2,23 +2,52 @@
 This code defines two functions: encrypt and decrypt.
 The encrypt function takes a plaintext string and a shift value, and returns the ciphertext string. The decrypt function takes a ciphertext string and a shift value,
 and returns the plaintext string.
+Both return an error for a shift that would leave the text unchanged.
 
 */
 
-pub fn encrypt(text: &str, shift: u8) -> String {
-    let mut result = String::new();
-    for c in text.chars() {
-        if c.is_ascii_alphabetic() {
-            let base = if c.is_ascii_lowercase() { b'a' } else { b'A' };
-            let offset = (c as u8 - base + shift) % 26;
-            result.push((base + offset) as char);
-        } else {
-            result.push(c);
-        }
-    }
-    result
+pub mod alphabet;
+pub mod cipher;
+pub mod crack;
+pub mod stream;
+
+use alphabet::Alphabet;
+use cipher::{Caesar, CaesarError, Cipher};
+
+/// Shifts the letters of the English alphabet by `shift` places
+///
+/// Any shift is taken modulo 26, but the multiples of 26 are refused: they would leave the
+/// text unchanged. Letters outside the alphabet, like "è", are copied, see [encrypt_with]
+/// to shift them too.
+///
+/// ```rust
+/// use caeser_cipher::{decrypt, encrypt};
+///
+/// assert_eq!(encrypt("Hello, World!", 3).unwrap(), "Khoor, Zruog!");
+/// assert_eq!(decrypt("Khoor, Zruog!", 255).unwrap(), "Pmttw, Ewztl!");
+/// assert!(encrypt("Hello", 26).is_err());
+/// ```
+pub fn encrypt(text: &str, shift: i64) -> Result<String, CaesarError> {
+    encrypt_with(text, shift, &Alphabet::latin())
+}
+
+pub fn decrypt(text: &str, shift: i64) -> Result<String, CaesarError> {
+    decrypt_with(text, shift, &Alphabet::latin())
 }
 
-pub fn decrypt(text: &str, shift: u8) -> String {
-    encrypt(text, 26 - shift)
-}
\ No newline at end of file
+/// [encrypt] with the characters of `alphabet`
+///
+/// ```rust
+/// use caeser_cipher::alphabet::{Alphabet, ACCENTED_LATIN};
+/// use caeser_cipher::encrypt_with;
+///
+/// let accented = Alphabet::new(ACCENTED_LATIN).unwrap();
+/// assert_eq!(encrypt_with("È così", 1, &accented).unwrap(), "É dptí");
+/// ```
+pub fn encrypt_with(text: &str, shift: i64, alphabet: &Alphabet) -> Result<String, CaesarError> {
+    Ok(Caesar::checked(shift, alphabet.clone())?.encrypt(text))
+}
+
+pub fn decrypt_with(text: &str, shift: i64, alphabet: &Alphabet) -> Result<String, CaesarError> {
+    Ok(Caesar::checked(shift, alphabet.clone())?.decrypt(text))
+}
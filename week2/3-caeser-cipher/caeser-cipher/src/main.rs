@@ -2,48 +2,264 @@
 
 To run:
 
-cargo run --  --message "Off to the bunker. Every person for themselves" --encrypt --shift 10
+cargo run -- encrypt --message "Off to the bunker. Every person for themselves" --shift 10
 
 To decrypt:
 
-cargo run --  --message "Ypp dy dro lexuob. Ofobi zobcyx pyb drowcovfoc" --decrypt --shift 10
+cargo run -- decrypt --message "Ypp dy dro lexuob. Ofobi zobcyx pyb drowcovfoc" --shift 10
+
+With another cipher:
+
+cargo run -- encrypt --message "Attack at dawn" --cipher vigenere --key lemon
+
+With accented letters:
+
+cargo run -- encrypt --message "Però è già lì" --shift 5 --alphabet accented
+
+From a file to another, or from stdin to stdout without --input and --output:
+
+cargo run -- encrypt --input book.txt --output book.enc --cipher vigenere --key lemon
+cat book.enc | cargo run -- decrypt --cipher vigenere --key lemon
+
+Without the key:
+
+cargo run -- crack --message "Ypp dy dro lexuob. Ofobi zobcyx pyb drowcovfoc"
 
 */
 
 
-use caeser_cipher::{decrypt, encrypt};
-use clap::Parser;
+use caeser_cipher::alphabet::{self, Alphabet};
+use caeser_cipher::cipher::{CipherKind, Direction};
+use caeser_cipher::crack::{self, Language};
+use caeser_cipher::stream;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::process;
 
-/// CLI tool to encrypt and decrypt messages using the caeser cipher
+/// CLI tool to encrypt and decrypt messages using the caeser cipher and other classical ciphers
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
     /// Encrypt the message
-    #[arg(short, long)]
-    encrypt: bool,
+    Encrypt(CipherArgs),
+    /// Decrypt the message
+    Decrypt(CipherArgs),
+    /// Find the key of a Caesar or Vigenère ciphertext by frequency analysis
+    Crack(CrackArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct CipherArgs {
+    #[command(flatten)]
+    text: TextArgs,
 
-    /// decrypt the message
+    /// The file to write to, instead of stdout
     #[arg(short, long)]
-    decrypt: bool,
+    output: Option<PathBuf>,
+
+    /// The shift to use for the cipher, taken modulo the length of the alphabet.
+    /// Multiples of the length leave the message unchanged and are refused, the default is 3
+    #[arg(
+        short,
+        long,
+        default_value = "3",
+        allow_negative_numbers = true,
+        conflicts_with = "key"
+    )]
+    shift: i64,
+
+    /// The alphabet shifted by the Caesar cipher
+    #[arg(short, long, value_enum, default_value_t = AlphabetName::Latin)]
+    alphabet: AlphabetName,
+
+    /// The characters of a custom alphabet for the Caesar cipher, instead of --alphabet
+    #[arg(long, conflicts_with = "alphabet")]
+    alphabet_chars: Option<String>,
 
+    /// The cipher to use
+    #[arg(short, long, value_enum, default_value_t = CipherKind::Caesar)]
+    cipher: CipherKind,
+
+    /// The key of the cipher, see the ciphers above for its format.
+    /// The Caesar cipher uses --shift when there is no key
+    #[arg(short, long, allow_hyphen_values = true)]
+    key: Option<String>,
+}
+
+/// Where the text comes from, stdin without --message and --input
+#[derive(clap::Args, Debug)]
+struct TextArgs {
     /// The message to encrypt or decrypt
+    #[arg(short, long, conflicts_with = "input")]
+    message: Option<String>,
+
+    /// The file to read the text from, instead of stdin
     #[arg(short, long)]
-    message: String,
+    input: Option<PathBuf>,
+}
 
-    /// The shift to use for the cipher
-    /// Must be between 1 and 25, the default is 3
-    #[arg(short, long, default_value = "3")]
-    shift: u8,
+impl TextArgs {
+    fn reader(&self) -> io::Result<Box<dyn Read>> {
+        Ok(match (&self.message, &self.input) {
+            (Some(message), _) => Box::new(io::Cursor::new(message.clone().into_bytes())),
+            (None, Some(path)) => Box::new(File::open(path)?),
+            (None, None) => Box::new(io::stdin().lock()),
+        })
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum AlphabetName {
+    /// a to z
+    Latin,
+    /// a to z and the accented letters of Italian, French, German and Spanish
+    Accented,
+    /// 0 to 9
+    Digits,
+    /// a to z, A to Z and 0 to 9, as one alphabet of 62 characters
+    Alphanumeric,
+}
+
+impl AlphabetName {
+    fn chars(self) -> &'static str {
+        match self {
+            AlphabetName::Latin => alphabet::LATIN,
+            AlphabetName::Accented => alphabet::ACCENTED_LATIN,
+            AlphabetName::Digits => alphabet::DIGITS,
+            AlphabetName::Alphanumeric => alphabet::ALPHANUMERIC,
+        }
+    }
+}
+
+#[derive(clap::Args, Debug)]
+struct CrackArgs {
+    #[command(flatten)]
+    text: TextArgs,
+
+    /// The cipher the message was encrypted with
+    #[arg(short, long, value_enum, default_value_t = Crackable::Caesar)]
+    cipher: Crackable,
+
+    /// The languages the message may be written in
+    #[arg(short, long, value_enum, value_delimiter = ',', default_value = "english,italian")]
+    language: Vec<Language>,
+
+    /// How many Caesar candidates to show
+    #[arg(short = 'n', long, default_value_t = 3)]
+    candidates: usize,
+
+    /// The longest Vigenère key to try
+    #[arg(long, default_value_t = 20)]
+    max_key_length: usize,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Crackable {
+    Caesar,
+    Vigenere,
+}
+
+fn run_crack(args: CrackArgs) -> Result<(), String> {
+    let mut message = String::new();
+    args.text
+        .reader()
+        .and_then(|mut reader| reader.read_to_string(&mut message))
+        .map_err(|e| e.to_string())?;
+    match args.cipher {
+        Crackable::Caesar => {
+            let candidates = crack::crack_caesar(&message, &args.language);
+            if candidates.is_empty() {
+                return Err("The message has no letters".to_string());
+            }
+            for candidate in candidates.iter().take(args.candidates) {
+                println!(
+                    "shift {:>2} ({}, chi-squared {:.1}, confidence {:.1}%): {}",
+                    candidate.shift,
+                    candidate.language,
+                    candidate.chi_squared,
+                    candidate.confidence * 100.0,
+                    candidate.plaintext
+                );
+            }
+        }
+        Crackable::Vigenere => {
+            let solution = crack::crack_vigenere(&message, &args.language, args.max_key_length)
+                .ok_or("The message has no letters")?;
+            for length in solution.key_lengths.iter().take(3) {
+                println!(
+                    "key length {:>2}: index of coincidence {:.4}, Kasiski {:.0}%",
+                    length.length,
+                    length.index_of_coincidence,
+                    length.kasiski * 100.0
+                );
+            }
+            println!(
+                "key {} ({}, chi-squared {:.1}, confidence {:.1}%): {}",
+                solution.key,
+                solution.language,
+                solution.chi_squared,
+                solution.confidence * 100.0,
+                solution.plaintext
+            );
+        }
+    }
+    Ok(())
+}
+
+fn run_cipher(args: CipherArgs, direction: Direction) -> Result<(), String> {
+    let key = match (args.cipher, args.key) {
+        (CipherKind::Caesar, None) => Some(args.shift.to_string()),
+        (_, key) => key,
+    };
+    let chars = args
+        .alphabet_chars
+        .as_deref()
+        .unwrap_or(args.alphabet.chars());
+    let alphabet = Alphabet::new(chars).map_err(|e| e.to_string())?;
+    let cipher = args
+        .cipher
+        .build_with(key.as_deref(), &alphabet)
+        .map_err(|e| e.to_string())?;
+    let reader = args.text.reader().map_err(|e| e.to_string())?;
+    let mut writer: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(
+            File::create(path).map_err(|e| e.to_string())?,
+        )),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    stream::transform(
+        cipher.as_ref(),
+        direction,
+        reader,
+        &mut writer,
+        stream::DEFAULT_CHUNK_SIZE,
+    )
+    .map_err(|e| e.to_string())?;
+    // a message on the command line is printed as a line, as before
+    if args.text.message.is_some() && args.output.is_none() {
+        writeln!(writer).map_err(|e| e.to_string())?;
+    }
+    writer.flush().map_err(|e| e.to_string())
 }
 
 // run it
 fn main() {
     let args = Args::parse();
-    if args.encrypt {
-        println!("{}", encrypt(&args.message, args.shift));
-    } else if args.decrypt {
-        println!("{}", decrypt(&args.message, args.shift));
-    } else {
-        println!("Please specify either --encrypt or --decrypt");
+    let result = match args.command {
+        Command::Encrypt(cipher_args) => run_cipher(cipher_args, Direction::Encrypt),
+        Command::Decrypt(cipher_args) => run_cipher(cipher_args, Direction::Decrypt),
+        Command::Crack(crack_args) => run_crack(crack_args),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(1);
     }
-}
\ No newline at end of file
+}
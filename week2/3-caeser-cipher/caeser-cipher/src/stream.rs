@@ -0,0 +1,181 @@
+//! Encrypting and decrypting texts too large to be read at once
+//!
+//! The text is read in chunks of about `chunk_size` bytes. A chunk never ends in the middle
+//! of a character: the bytes of a character cut by a read are kept for the next chunk. Every
+//! chunk goes through the [Stream](crate::cipher::Stream) of the cipher, so that the ciphers
+//! replacing each character on their own only keep one chunk in memory.
+
+use crate::cipher::{Cipher, Direction};
+use std::io::{self, Read, Write};
+
+/// The chunk size of the command line tool
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The text of a reader, in chunks of whole characters
+///
+/// Bytes that are not UTF-8 are an [io::ErrorKind::InvalidData] error.
+///
+/// ```rust
+/// use caeser_cipher::stream::Utf8Chunks;
+///
+/// // "è" is two bytes, cut by the first read
+/// let chunks: Vec<String> = Utf8Chunks::new("perché".as_bytes(), 6)
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(chunks, ["perch", "é"]);
+/// ```
+#[derive(Debug)]
+pub struct Utf8Chunks<R> {
+    reader: R,
+    chunk_size: usize,
+    /// The first bytes of a character cut by the last read
+    pending: Vec<u8>,
+    done: bool,
+}
+
+impl<R: Read> Utf8Chunks<R> {
+    pub fn new(reader: R, chunk_size: usize) -> Self {
+        Self {
+            reader,
+            chunk_size: chunk_size.max(1),
+            pending: Vec::new(),
+            done: false,
+        }
+    }
+
+    fn read_chunk(&mut self) -> io::Result<Option<String>> {
+        let start = self.pending.len();
+        let mut buffer = std::mem::take(&mut self.pending);
+        buffer.resize(start + self.chunk_size, 0);
+        let read = loop {
+            match self.reader.read(&mut buffer[start..]) {
+                Ok(read) => break read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        };
+        buffer.truncate(start + read);
+        if read == 0 {
+            return if buffer.is_empty() {
+                Ok(None)
+            } else {
+                Err(invalid_data("The text ends in the middle of a character"))
+            };
+        }
+        match std::str::from_utf8(&buffer) {
+            Ok(_) => {}
+            // the last character is cut, the rest of it comes with the next read
+            Err(e) if e.error_len().is_none() => self.pending = buffer.split_off(e.valid_up_to()),
+            Err(e) => return Err(invalid_data(e)),
+        }
+        String::from_utf8(buffer).map(Some).map_err(invalid_data)
+    }
+}
+
+impl<R: Read> Iterator for Utf8Chunks<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let chunk = self.read_chunk();
+        if !matches!(chunk, Ok(Some(_))) {
+            self.done = true;
+        }
+        chunk.transpose()
+    }
+}
+
+fn invalid_data<E>(error: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+/// Encrypts or decrypts the text of `reader` into `writer`, `chunk_size` bytes at a time
+///
+/// ```rust
+/// use caeser_cipher::cipher::{Caesar, Direction};
+/// use caeser_cipher::stream::transform;
+///
+/// let mut ciphertext = Vec::new();
+/// transform(&Caesar::new(3), Direction::Encrypt, "Hello, World!".as_bytes(), &mut ciphertext, 4)
+///     .unwrap();
+/// assert_eq!(ciphertext, b"Khoor, Zruog!");
+/// ```
+pub fn transform<R: Read, W: Write>(
+    cipher: &dyn Cipher,
+    direction: Direction,
+    reader: R,
+    mut writer: W,
+    chunk_size: usize,
+) -> io::Result<()> {
+    let mut stream = cipher.stream(direction);
+    for chunk in Utf8Chunks::new(reader, chunk_size) {
+        writer.write_all(stream.push(&chunk?).as_bytes())?;
+    }
+    writer.write_all(stream.finish().as_bytes())?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alphabet::{Alphabet, ACCENTED_LATIN};
+    use crate::cipher::{Caesar, CipherKind};
+
+    fn chunks(bytes: &[u8], chunk_size: usize) -> io::Result<Vec<String>> {
+        Utf8Chunks::new(bytes, chunk_size).collect()
+    }
+
+    #[test]
+    fn characters_are_never_cut() {
+        let text = "Già, così è: 日本語 🦀!";
+        for chunk_size in 1..10 {
+            let chunks = chunks(text.as_bytes(), chunk_size).unwrap();
+            assert_eq!(chunks.concat(), text);
+            assert!(chunks.iter().all(|chunk| chunk.len() <= chunk_size + 3));
+        }
+        assert!(chunks(b"", 4).unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid_utf8_is_an_error() {
+        for bytes in [&b"abc\xffdef"[..], b"abc\xc3", b"\xe6\x97"] {
+            for chunk_size in 1..6 {
+                let error = chunks(bytes, chunk_size).unwrap_err();
+                assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+            }
+        }
+    }
+
+    #[test]
+    fn streamed_text_matches_the_whole_text() {
+        let text = "Però è già lì, in fondo al mare. ".repeat(40);
+        let accented = Alphabet::new(ACCENTED_LATIN).unwrap();
+        let ciphers = [
+            Box::new(Caesar::with_alphabet(7, accented)) as Box<dyn Cipher>,
+            CipherKind::Vigenere.build(Some("lemon")).unwrap(),
+            CipherKind::RailFence.build(Some("3")).unwrap(),
+        ];
+        for cipher in &ciphers {
+            for chunk_size in [1, 3, 64] {
+                let mut ciphertext = Vec::new();
+                transform(
+                    cipher.as_ref(),
+                    Direction::Encrypt,
+                    text.as_bytes(),
+                    &mut ciphertext,
+                    chunk_size,
+                )
+                .unwrap();
+                assert_eq!(
+                    String::from_utf8(ciphertext).unwrap(),
+                    cipher.encrypt(&text)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,297 @@
+//! Homophonic substitution cipher
+//!
+//! Every letter is replaced by one of its homophones, symbols picked at random when
+//! encrypting, so that the frequent letters do not show up as frequent symbols. No symbol is
+//! the homophone of two letters: decrypting only has to look up the letter of every symbol.
+//!
+//! The [Key] is serialised to JSON as the homophones of every letter, to decrypt later what
+//! was encrypted with it:
+//!
+//! ```json
+//! { "a": ["ж", "Σ"], "b": ["ω", "Ц"] }
+//! ```
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{self, Display};
+
+/// The letters of the first version of the cipher: English and Italian, in both cases
+pub const LETTERS: &str = "abcdeèfghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Greek and Cyrillic letters, unlikely to appear in a text written with [LETTERS]
+pub const SYMBOLS: &str = "αβγδεζηθικλμνξοπρστυφχψωΑΒΓΔΕΖΗΘΙΚΛΜΝΞΟΠΡΣΤΥΦΧΨΩ\
+    абвгдежзийклмнопрстуфхцчшщъыьэюяАБВГДЕЖЗИЙКЛМНОПРСТУФХЦЧШЩЪЫЬЭЮЯ";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyError {
+    NoLetters,
+    /// The letter has no homophones
+    NoHomophones(char),
+    /// The letter or symbol appears twice
+    Duplicate(char),
+    NotEnoughSymbols {
+        needed: usize,
+        available: usize,
+    },
+}
+
+impl Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyError::NoLetters => write!(f, "The key has no letters"),
+            KeyError::NoHomophones(c) => write!(f, "'{}' has no homophones", c),
+            KeyError::Duplicate(c) => write!(f, "'{}' appears twice in the key", c),
+            KeyError::NotEnoughSymbols { needed, available } => write!(
+                f,
+                "The key needs {} symbols but only {} are available",
+                needed, available
+            ),
+        }
+    }
+}
+
+impl std::error::Error for KeyError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncryptError {
+    /// The character is not in the key but is a homophone: it would not be decrypted as
+    /// itself
+    SymbolInText(char),
+}
+
+impl Display for EncryptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncryptError::SymbolInText(c) => write!(
+                f,
+                "'{}' is a homophone of the key, the text cannot contain it",
+                c
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EncryptError {}
+
+/// The homophones of every letter, no two letters sharing a symbol
+///
+/// ```rust
+/// use homophonic_cipher::Key;
+/// use rand::{rngs::StdRng, SeedableRng};
+///
+/// let mut rng = StdRng::seed_from_u64(7);
+/// let key = Key::generate("abc", 3, "0123456789", &mut rng).unwrap();
+/// let encrypted = key.encrypt("a cab", &mut rng).unwrap();
+/// assert_eq!(key.decrypt(&encrypted), "a cab");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    try_from = "BTreeMap<char, Vec<char>>",
+    into = "BTreeMap<char, Vec<char>>"
+)]
+pub struct Key {
+    homophones: BTreeMap<char, Vec<char>>,
+    /// The letter of every symbol
+    letters: HashMap<char, char>,
+}
+
+impl Key {
+    /// Every letter needs at least one homophone, and every symbol can only be used once
+    pub fn new(homophones: BTreeMap<char, Vec<char>>) -> Result<Self, KeyError> {
+        if homophones.is_empty() {
+            return Err(KeyError::NoLetters);
+        }
+        let mut letters = HashMap::new();
+        for (&letter, symbols) in &homophones {
+            if symbols.is_empty() {
+                return Err(KeyError::NoHomophones(letter));
+            }
+            for &symbol in symbols {
+                if letters.insert(symbol, letter).is_some() {
+                    return Err(KeyError::Duplicate(symbol));
+                }
+            }
+        }
+        Ok(Self {
+            homophones,
+            letters,
+        })
+    }
+
+    /// A random key giving `homophones` symbols to every character of `letters`, taken from
+    /// `symbols`
+    ///
+    /// The symbols are shuffled and dealt to the letters, so that the sets of homophones are
+    /// disjoint: `symbols` needs at least `homophones` different symbols per letter.
+    pub fn generate<R: Rng + ?Sized>(
+        letters: &str,
+        homophones: usize,
+        symbols: &str,
+        rng: &mut R,
+    ) -> Result<Self, KeyError> {
+        let letters = distinct(letters)?;
+        let mut symbols = distinct(symbols)?;
+        if letters.is_empty() {
+            return Err(KeyError::NoLetters);
+        }
+        if homophones == 0 {
+            return Err(KeyError::NoHomophones(letters[0]));
+        }
+        let needed = letters.len() * homophones;
+        if symbols.len() < needed {
+            return Err(KeyError::NotEnoughSymbols {
+                needed,
+                available: symbols.len(),
+            });
+        }
+        symbols.shuffle(rng);
+        let homophones = letters
+            .into_iter()
+            .zip(symbols.chunks(homophones))
+            .map(|(letter, symbols)| (letter, symbols.to_vec()))
+            .collect();
+        Self::new(homophones)
+    }
+
+    /// The homophones of `letter`, if it is in the key
+    pub fn homophones(&self, letter: char) -> Option<&[char]> {
+        self.homophones.get(&letter).map(Vec::as_slice)
+    }
+
+    /// Replaces every letter of the key by one of its homophones, picked at random
+    ///
+    /// The other characters are copied, unless they are homophones themselves.
+    pub fn encrypt<R: Rng + ?Sized>(
+        &self,
+        text: &str,
+        rng: &mut R,
+    ) -> Result<String, EncryptError> {
+        text.chars()
+            .map(|c| match self.homophones.get(&c) {
+                Some(symbols) => Ok(*symbols.choose(rng).expect("Every letter has homophones")),
+                None if self.letters.contains_key(&c) => Err(EncryptError::SymbolInText(c)),
+                None => Ok(c),
+            })
+            .collect()
+    }
+
+    /// Replaces every homophone by its letter and copies the other characters
+    pub fn decrypt(&self, text: &str) -> String {
+        text.chars()
+            .map(|c| self.letters.get(&c).copied().unwrap_or(c))
+            .collect()
+    }
+}
+
+impl TryFrom<BTreeMap<char, Vec<char>>> for Key {
+    type Error = KeyError;
+
+    fn try_from(homophones: BTreeMap<char, Vec<char>>) -> Result<Self, Self::Error> {
+        Self::new(homophones)
+    }
+}
+
+impl From<Key> for BTreeMap<char, Vec<char>> {
+    fn from(key: Key) -> Self {
+        key.homophones
+    }
+}
+
+/// The characters of `chars`, which must all be different
+fn distinct(chars: &str) -> Result<Vec<char>, KeyError> {
+    let mut seen = HashSet::new();
+    chars
+        .chars()
+        .map(|c| {
+            if seen.insert(c) {
+                Ok(c)
+            } else {
+                Err(KeyError::Duplicate(c))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn generated_homophones_are_disjoint_and_round_trip() {
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let key = Key::generate(LETTERS, 2, SYMBOLS, &mut rng).unwrap();
+            let mut symbols = HashSet::new();
+            for letter in LETTERS.chars() {
+                let homophones = key.homophones(letter).unwrap();
+                assert_eq!(homophones.len(), 2);
+                assert!(homophones.iter().all(|&symbol| symbols.insert(symbol)));
+            }
+
+            let text = "Mi chiamo Toni e Rust è una figata. Aaaaa bbbb cccc!";
+            let encrypted = key.encrypt(text, &mut rng).unwrap();
+            assert_eq!(key.decrypt(&encrypted), text);
+        }
+    }
+
+    #[test]
+    fn frequent_letters_use_every_homophone() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let key = Key::generate("a", 4, SYMBOLS, &mut rng).unwrap();
+        let encrypted = key.encrypt(&"a".repeat(200), &mut rng).unwrap();
+        let used: HashSet<char> = encrypted.chars().collect();
+        assert_eq!(used.len(), 4);
+    }
+
+    #[test]
+    fn invalid_keys_are_errors() {
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(
+            Key::generate(LETTERS, 3, SYMBOLS, &mut rng),
+            Err(KeyError::NotEnoughSymbols {
+                needed: 159,
+                available: 112
+            })
+        );
+        assert_eq!(
+            Key::generate("abca", 1, SYMBOLS, &mut rng),
+            Err(KeyError::Duplicate('a'))
+        );
+        assert_eq!(
+            Key::generate("ab", 0, SYMBOLS, &mut rng),
+            Err(KeyError::NoHomophones('a'))
+        );
+        assert_eq!(Key::new(BTreeMap::new()), Err(KeyError::NoLetters));
+        let shared = BTreeMap::from([('a', vec!['1', '2']), ('b', vec!['2'])]);
+        assert_eq!(Key::new(shared), Err(KeyError::Duplicate('2')));
+    }
+
+    #[test]
+    fn homophones_in_the_text_are_refused() {
+        let key = Key::new(BTreeMap::from([('a', vec!['1']), ('b', vec!['2'])])).unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(key.encrypt("ab c", &mut rng).unwrap(), "12 c");
+        assert_eq!(
+            key.encrypt("a2", &mut rng),
+            Err(EncryptError::SymbolInText('2'))
+        );
+    }
+
+    #[test]
+    fn keys_are_saved_as_json() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let key = Key::generate(LETTERS, 2, SYMBOLS, &mut rng).unwrap();
+        let json = serde_json::to_string(&key).unwrap();
+        assert_eq!(serde_json::from_str::<Key>(&json).unwrap(), key);
+
+        let json = r#"{"a": ["1"], "b": ["2", "3"]}"#;
+        let key: Key = serde_json::from_str(json).unwrap();
+        assert_eq!(key.decrypt("132!"), "abb!");
+        let shared = r#"{"a": ["1"], "b": ["1"]}"#;
+        let error = serde_json::from_str::<Key>(shared).unwrap_err();
+        assert!(error.to_string().contains("'1' appears twice"));
+    }
+}
@@ -1,59 +1,131 @@
-use std::collections::HashMap;
-use std::ops::Range;
+/*
 
-use rand::prelude::SliceRandom;
-use rand::Rng;
+To create a key, keep it to decrypt later:
 
-fn main() {
-    let cipher = homophonic_cipher();
-    println!("{:?}", cipher);
+cargo run -- keygen --output key.json
+
+To encrypt:
+
+cargo run -- encrypt --key key.json --message "Mi chiamo Toni e Rust è una figata"
+
+To decrypt, the message can also come from stdin:
+
+cargo run -- decrypt --key key.json --message "..."
+
+*/
+
+use clap::{Parser, Subcommand};
+use homophonic_cipher::{Key, LETTERS, SYMBOLS};
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// CLI tool to encrypt and decrypt messages with a homophonic substitution cipher
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
 
-    //  Test
-    let plain_text = String::from("Mi chiamo Toni e Rust è una figata");
-    let encrypted = encrypt(&cipher, &plain_text);
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Create a random key and print it as JSON
+    Keygen(KeygenArgs),
+    /// Encrypt the message with a key
+    Encrypt(MessageArgs),
+    /// Decrypt the message with the key it was encrypted with
+    Decrypt(MessageArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct KeygenArgs {
+    /// The letters to encrypt, the other characters are copied
+    #[arg(short, long, default_value = LETTERS)]
+    letters: String,
 
-    println!("Plain text: {}", plain_text);
-    println!("Encrypted text: {}", encrypted);
+    /// How many homophones every letter gets
+    #[arg(short = 'n', long, default_value_t = 2)]
+    homophones: usize,
 
-    // Test 2
-    let plain_text = String::from("Aaaaa bbbb cccc dddd eeee ffff gggg");
-    let encrypted = encrypt(&cipher, &plain_text);
+    /// The symbols the homophones are taken from
+    #[arg(short, long, default_value = SYMBOLS)]
+    symbols: String,
 
-    println!("Plain text: {}", plain_text);
-    println!("Encrypted text: {}", encrypted);
+    /// The file to save the key to, instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
 
+#[derive(clap::Args, Debug)]
+struct MessageArgs {
+    /// The JSON file of the key
+    #[arg(short, long)]
+    key: PathBuf,
 
+    /// The message to encrypt or decrypt, read from stdin when missing
+    #[arg(short, long)]
+    message: Option<String>,
 }
 
-fn encrypt(cipher: &HashMap<char, Vec<char>>, plain_text: &String) -> String {
-    let mut encrypted = String::new();
-    let mut rng = rand::thread_rng();
-    for c in plain_text.chars() {
-        if c.is_alphabetic() {
-            let random_chars = cipher.get(&c).unwrap();
-            let random_char = random_chars[rng.gen_range(0..random_chars.len())];
-            encrypted.push(random_char);
-        } else {
-            encrypted.push(c);
+impl MessageArgs {
+    fn message(&self) -> Result<String, String> {
+        match &self.message {
+            Some(message) => Ok(message.clone()),
+            None => {
+                let mut message = String::new();
+                io::stdin()
+                    .read_to_string(&mut message)
+                    .map_err(|e| e.to_string())?;
+                // the result is printed as a line
+                Ok(message.strip_suffix('\n').unwrap_or(&message).to_string())
+            }
         }
     }
-    encrypted
 }
 
-fn homophonic_cipher() -> HashMap<char, Vec<char>> {
-    let mut cipher = HashMap::new();
-    let alphabet: Vec<char> = "abcdeèfghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ".chars().collect();
-    let mut rng = rand::thread_rng();
-    let mut shuffled_alphabet = alphabet.clone();
-    shuffled_alphabet.shuffle(&mut rng);
+fn read_key(path: &Path) -> Result<Key, String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    serde_json::from_str(&json).map_err(|e| format!("{}: {}", path.display(), e))
+}
 
-    for &c in &alphabet {
-        let mut random_chars = Vec::new();
-        for _ in 0..2 {
-            random_chars.push(shuffled_alphabet[rng.gen_range(0..shuffled_alphabet.len())]);
+fn run(command: Command) -> Result<(), String> {
+    match command {
+        Command::Keygen(args) => {
+            let key = Key::generate(
+                &args.letters,
+                args.homophones,
+                &args.symbols,
+                &mut rand::thread_rng(),
+            )
+            .map_err(|e| e.to_string())?;
+            let json = serde_json::to_string_pretty(&key).map_err(|e| e.to_string())?;
+            match args.output {
+                Some(path) => fs::write(&path, json + "\n")
+                    .map_err(|e| format!("{}: {}", path.display(), e))?,
+                None => println!("{}", json),
+            }
+        }
+        Command::Encrypt(args) => {
+            let key = read_key(&args.key)?;
+            let encrypted = key
+                .encrypt(&args.message()?, &mut rand::thread_rng())
+                .map_err(|e| e.to_string())?;
+            println!("{}", encrypted);
+        }
+        Command::Decrypt(args) => {
+            let key = read_key(&args.key)?;
+            println!("{}", key.decrypt(&args.message()?));
         }
-        cipher.insert(c, random_chars);
     }
+    Ok(())
+}
 
-    cipher
+fn main() {
+    let args = Args::parse();
+    if let Err(e) = run(args.command) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
 }
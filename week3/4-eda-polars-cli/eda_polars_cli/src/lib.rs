@@ -23,4 +23,10 @@ pub fn print_schema(df: &DataFrame) {
 //print the shape of a dataframe
 pub fn print_shape(df: &DataFrame) {
     println!("{:?}", df.shape());
-}
\ No newline at end of file
+}
+
+//the numeric values of a column, as f64, without nulls
+pub fn column_values(df: &DataFrame, column: &str) -> PolarsResult<Vec<f64>> {
+    let series = df.column(column)?.cast(&DataType::Float64)?;
+    Ok(series.f64()?.into_iter().flatten().collect())
+}
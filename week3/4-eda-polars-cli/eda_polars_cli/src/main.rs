@@ -1,7 +1,8 @@
 //command-line tool that reads a CSV file and prints the contents of the file as a DataFrame
 use clap::Parser;
 use polars::prelude::*;
-use eda_polars_cli::read_csv;
+use eda_polars_cli::{column_values, read_csv};
+use term_charts::{svg, Binning, BoxPlot, Histogram};
 
 const CSV_FILE: &str = "src/data/global-life-expt-2022.csv";
 
@@ -48,6 +49,19 @@ enum Commands {
         #[clap(long, default_value = "true")]
         order: bool,
     },
+    /// Histogram and box plot of a numeric column
+    Chart {
+        #[clap(long, default_value = CSV_FILE)]
+        path: String,
+        #[clap(long, default_value = "2020")]
+        column: String,
+        /// sturges, freedman-diaconis (fd) or a number of bins
+        #[clap(long, default_value = "sturges")]
+        bins: Binning,
+        /// Also write the charts as SVG, `<svg>-histogram.svg` and `<svg>-box-plot.svg`
+        #[clap(long)]
+        svg: Option<String>,
+    },
 }
 
 fn main() {
@@ -89,6 +103,42 @@ fn main() {
             //print the first "rows" of the dataframe
             println!("{:?}", df2.head(Some(rows)));
         }
+        Some(Commands::Chart {
+            path,
+            column,
+            bins,
+            svg,
+        }) => {
+            let df = read_csv(&path);
+            //nulls and non numeric values are left out
+            let values = match column_values(&df, &column) {
+                Ok(values) => values,
+                Err(e) => {
+                    println!("Column {}: {}", column, e);
+                    return;
+                }
+            };
+            let (Some(histogram), Some(box_plot)) =
+                (Histogram::new(&values, bins), BoxPlot::new(&values))
+            else {
+                println!("Column {} has no numeric values", column);
+                return;
+            };
+            println!("{}", histogram.render(40));
+            println!("{}", box_plot.render(60));
+            if let Some(prefix) = svg {
+                std::fs::write(
+                    format!("{}-histogram.svg", prefix),
+                    svg::histogram(&histogram, &column),
+                )
+                .expect("Unable to write file");
+                std::fs::write(
+                    format!("{}-box-plot.svg", prefix),
+                    svg::box_plot(&box_plot, &column),
+                )
+                .expect("Unable to write file");
+            }
+        }
         None => {
             println!("No subcommand was used");
         }